@@ -0,0 +1,639 @@
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::db;
+use crate::recipe::Recipe;
+use crate::secrets::SecretStore;
+
+pub const BUNDLE_FORMAT: &str = "ago-recipe-bundle";
+const BUNDLE_VERSION: u32 = 1;
+const MANIFEST_PATH: &str = "manifest.json";
+const SIGNATURE_PATH: &str = "manifest.sig";
+const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Secret store account holding the hex-encoded PKCS#8 signing key.
+pub const SIGNING_KEY_ACCOUNT: &str = "bundle:signing-key";
+
+/// File in the app data folder where the signing key used to be kept; it is
+/// moved into the secret store on first use so the public key stays the same.
+pub const SIGNING_KEY_FILE: &str = "bundle_signing_key.pk8";
+
+/// Settings holding the trusted publisher keys (a JSON list of `TrustedKey`) and
+/// what to do with bundles none of them signed.
+pub const TRUSTED_KEYS_SETTING: &str = "trusted_bundle_keys";
+pub const UNTRUSTED_POLICY_SETTING: &str = "untrusted_bundle_policy";
+
+/// Largest recipe file read from a bundle.
+const MAX_ENTRY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Lists every recipe file with its hash. The signature covers the manifest's
+/// exact bytes, so changing any recipe breaks either a hash or the signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub publisher: String,
+    #[serde(default)]
+    pub created_at: String,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSignature {
+    pub algorithm: String,
+    pub public_key: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    pub name: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    /// Signed by a key in `trusted_bundle_keys`.
+    Trusted,
+    /// Validly signed, but by a key nobody has trusted.
+    Untrusted,
+    Unsigned,
+}
+
+/// What import does with unsigned or untrusted bundles. Altered bundles are
+/// always refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UntrustedPolicy {
+    Warn,
+    Refuse,
+}
+
+impl UntrustedPolicy {
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            Some("refuse") => UntrustedPolicy::Refuse,
+            _ => UntrustedPolicy::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleVerification {
+    pub status: SignatureStatus,
+    /// Name the signing key was trusted under.
+    pub signer: Option<String>,
+    pub public_key: Option<String>,
+    pub publisher: String,
+    pub warnings: Vec<String>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Lowercase hex of a 32-byte Ed25519 public key.
+pub fn normalize_public_key(value: &str) -> Result<String, String> {
+    let key = value.trim().to_lowercase();
+    match hex::decode(&key) {
+        Ok(bytes) if bytes.len() == 32 => Ok(key),
+        _ => Err("Public key must be 64 hex characters".to_string()),
+    }
+}
+
+pub fn parse_trusted_keys(value: Option<&str>) -> Result<Vec<TrustedKey>, String> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(Vec::new()),
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| format!("Failed to read trusted bundle keys: {}", e)),
+    }
+}
+
+fn signing_key(pkcs8: &[u8]) -> Result<Ed25519KeyPair, String> {
+    Ed25519KeyPair::from_pkcs8(pkcs8)
+        .map_err(|e| format!("Failed to read bundle signing key: {}", e))
+}
+
+/// The app's signing key from `store`, created on first use. A key left in
+/// `legacy_dir` by an earlier version is moved into the store instead.
+pub fn load_or_create_signing_key(
+    store: &dyn SecretStore,
+    legacy_dir: &Path,
+) -> Result<Ed25519KeyPair, String> {
+    if let Some(stored) = store.get(SIGNING_KEY_ACCOUNT)? {
+        let pkcs8 = hex::decode(stored.trim())
+            .map_err(|_| "Stored bundle signing key is corrupt".to_string())?;
+        return signing_key(&pkcs8);
+    }
+
+    let legacy_path = legacy_dir.join(SIGNING_KEY_FILE);
+    let pkcs8 = match fs::read(&legacy_path) {
+        Ok(pkcs8) => pkcs8,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|e| format!("Failed to generate bundle signing key: {}", e))?
+                .as_ref()
+                .to_vec()
+        }
+        Err(e) => return Err(format!("Failed to read bundle signing key: {}", e)),
+    };
+    let key = signing_key(&pkcs8)?;
+    store.set(SIGNING_KEY_ACCOUNT, &hex::encode(&pkcs8))?;
+    if legacy_path.exists() {
+        fs::remove_file(&legacy_path)
+            .map_err(|e| format!("Failed to remove old bundle signing key: {}", e))?;
+    }
+    Ok(key)
+}
+
+pub fn public_key_hex(key: &Ed25519KeyPair) -> String {
+    hex::encode(key.public_key().as_ref())
+}
+
+fn recipe_path(index: usize, recipe: &Recipe) -> String {
+    let slug = recipe
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let slug = slug
+        .split('-')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("recipes/{:03}-{}.json", index + 1, slug)
+}
+
+/// Zip the recipes with a manifest, signed with `key` when one is given.
+pub fn write_bundle(
+    recipes: &[Recipe],
+    publisher: &str,
+    created_at: &str,
+    key: Option<&Ed25519KeyPair>,
+) -> Result<Vec<u8>, String> {
+    let mut files = Vec::with_capacity(recipes.len());
+    let mut contents = Vec::with_capacity(recipes.len());
+    for (index, recipe) in recipes.iter().enumerate() {
+        let json = serde_json::to_vec_pretty(recipe)
+            .map_err(|e| format!("Failed to serialize recipe: {}", e))?;
+        let path = recipe_path(index, recipe);
+        files.push(BundleFile {
+            path: path.clone(),
+            sha256: sha256_hex(&json),
+            size: json.len() as u64,
+        });
+        contents.push((path, json));
+    }
+
+    let manifest = serde_json::to_vec_pretty(&BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        publisher: publisher.to_string(),
+        created_at: created_at.to_string(),
+        files,
+    })
+    .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+
+    let mut entries = vec![(MANIFEST_PATH.to_string(), manifest.clone())];
+    if let Some(key) = key {
+        let signature = serde_json::to_vec_pretty(&BundleSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: public_key_hex(key),
+            signature: hex::encode(key.sign(&manifest).as_ref()),
+        })
+        .map_err(|e| format!("Failed to serialize signature: {}", e))?;
+        entries.push((SIGNATURE_PATH.to_string(), signature));
+    }
+    entries.extend(contents);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, bytes) in entries {
+        zip.start_file(path, options)
+            .map_err(|e| format!("Failed to write bundle: {}", e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    }
+    let cursor = zip
+        .finish()
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+fn read_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let entry = match archive.by_name(path) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read bundle: {}", e)),
+    };
+    let mut bytes = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(format!("Bundle file {} is too large", path));
+    }
+    Ok(Some(bytes))
+}
+
+fn altered(reason: String) -> String {
+    format!("Bundle has been altered and was not imported: {}", reason)
+}
+
+/// Check a bundle's signature and file hashes and return its recipes as stored
+/// by the publisher. Tampering is always an error; unsigned or untrusted bundles
+/// are refused or come back with warnings, depending on `policy`.
+pub fn read_bundle(
+    bytes: &[u8],
+    trusted: &[TrustedKey],
+    policy: UntrustedPolicy,
+) -> Result<(BundleVerification, Vec<Recipe>), String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not a recipe bundle: {}", e))?;
+
+    let manifest_bytes = read_entry(&mut archive, MANIFEST_PATH)?
+        .ok_or_else(|| "Not a recipe bundle: manifest.json is missing".to_string())?;
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Failed to read bundle manifest: {}", e))?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err("Not a recipe bundle".to_string());
+    }
+    if manifest.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is not supported by this version of the app",
+            manifest.version
+        ));
+    }
+
+    let signature = match read_entry(&mut archive, SIGNATURE_PATH)? {
+        Some(bytes) => Some(
+            serde_json::from_slice::<BundleSignature>(&bytes)
+                .map_err(|e| altered(format!("unreadable signature ({})", e)))?,
+        ),
+        None => None,
+    };
+    let (status, signer, public_key) = match &signature {
+        Some(signature) => {
+            if signature.algorithm != SIGNATURE_ALGORITHM {
+                return Err(altered(format!(
+                    "unknown signature algorithm '{}'",
+                    signature.algorithm
+                )));
+            }
+            let key = normalize_public_key(&signature.public_key).map_err(altered)?;
+            let sig = hex::decode(signature.signature.trim())
+                .map_err(|_| altered("signature is not hex".to_string()))?;
+            UnparsedPublicKey::new(&ED25519, hex::decode(&key).unwrap_or_default())
+                .verify(&manifest_bytes, &sig)
+                .map_err(|_| altered("the manifest does not match its signature".to_string()))?;
+
+            let signer = trusted
+                .iter()
+                .find(|t| normalize_public_key(&t.public_key).is_ok_and(|trusted| trusted == key));
+            match signer {
+                Some(t) => (SignatureStatus::Trusted, Some(t.name.clone()), Some(key)),
+                None => (SignatureStatus::Untrusted, None, Some(key)),
+            }
+        }
+        None => (SignatureStatus::Unsigned, None, None),
+    };
+
+    let mut listed = BTreeSet::new();
+    let mut recipes = Vec::with_capacity(manifest.files.len());
+    for file in &manifest.files {
+        let content = read_entry(&mut archive, &file.path)?
+            .ok_or_else(|| altered(format!("{} is missing", file.path)))?;
+        if content.len() as u64 != file.size || sha256_hex(&content) != file.sha256.to_lowercase() {
+            return Err(altered(format!(
+                "{} does not match the manifest",
+                file.path
+            )));
+        }
+        let recipe: Recipe = serde_json::from_slice(&content)
+            .map_err(|e| format!("Failed to read {}: {}", file.path, e))?;
+        listed.insert(file.path.as_str());
+        recipes.push(recipe);
+    }
+    for name in archive.file_names() {
+        let known = name == MANIFEST_PATH || name == SIGNATURE_PATH || listed.contains(name);
+        if !known && !name.ends_with('/') {
+            return Err(altered(format!("{} is not listed in the manifest", name)));
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let problem = match status {
+        SignatureStatus::Trusted => None,
+        SignatureStatus::Untrusted => Some(format!(
+            "Bundle is signed by a key you have not trusted ({})",
+            public_key.as_deref().unwrap_or_default()
+        )),
+        SignatureStatus::Unsigned => Some("Bundle is not signed".to_string()),
+    };
+    if let Some(problem) = problem {
+        if policy == UntrustedPolicy::Refuse {
+            return Err(format!("{}; import refused", problem));
+        }
+        warnings.push(problem);
+    }
+
+    Ok((
+        BundleVerification {
+            status,
+            signer,
+            public_key,
+            publisher: manifest.publisher,
+            warnings,
+        },
+        recipes,
+    ))
+}
+
+/// Give a recipe from a bundle fresh ids so it is stored as a new recipe.
+pub fn as_new_recipe(mut recipe: Recipe) -> Recipe {
+    let now = db::now_iso();
+    recipe.id = db::new_id();
+    recipe.created_at = now.clone();
+    recipe.updated_at = now;
+    recipe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::{self, TemperatureDefaults};
+    use crate::secrets::FileStore;
+    use std::path::PathBuf;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "ago-bundle-{}-{}-{}",
+                name,
+                std::process::id(),
+                db::new_id()
+            ));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn key() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn trust(key: &Ed25519KeyPair) -> Vec<TrustedKey> {
+        vec![TrustedKey {
+            name: "Club".to_string(),
+            public_key: public_key_hex(key).to_uppercase(),
+        }]
+    }
+
+    fn recipes() -> Vec<Recipe> {
+        ["HP5+ Rodinal 1+50", "Tri-X D-76 1+1"]
+            .into_iter()
+            .map(|name| {
+                let mut recipe = recipe::new_recipe(name);
+                recipe.steps = recipe::DEFAULT_TEMPLATE_STEPS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, step)| {
+                        recipe::default_step(step, i as i64, TemperatureDefaults::default())
+                    })
+                    .collect();
+                recipe
+            })
+            .collect()
+    }
+
+    /// Copy a bundle, letting `edit` change each entry, then append `extra`.
+    fn rewrite(
+        bytes: &[u8],
+        edit: impl Fn(&str, Vec<u8>) -> Vec<u8>,
+        extra: Option<(&str, &[u8])>,
+    ) -> Vec<u8> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let name = entry.name().to_string();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            zip.start_file(name.as_str(), SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(&edit(&name, content)).unwrap();
+        }
+        if let Some((name, content)) = extra {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn replace(content: Vec<u8>, from: &str, to: &str) -> Vec<u8> {
+        String::from_utf8(content)
+            .unwrap()
+            .replacen(from, to, 1)
+            .into_bytes()
+    }
+
+    #[test]
+    fn signed_bundle_round_trips_as_trusted() {
+        let key = key();
+        let originals = recipes();
+        let bytes = write_bundle(&originals, "Club", "2026-01-01", Some(&key)).unwrap();
+        let (verification, read) =
+            read_bundle(&bytes, &trust(&key), UntrustedPolicy::Refuse).unwrap();
+
+        assert_eq!(verification.status, SignatureStatus::Trusted);
+        assert_eq!(verification.signer.as_deref(), Some("Club"));
+        assert_eq!(verification.public_key, Some(public_key_hex(&key)));
+        assert_eq!(verification.publisher, "Club");
+        assert!(verification.warnings.is_empty());
+        let names = read.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["HP5+ Rodinal 1+50", "Tri-X D-76 1+1"]);
+        assert_eq!(read[0].id, originals[0].id);
+        assert_eq!(read[1].steps.len(), 4);
+    }
+
+    #[test]
+    fn unknown_keys_and_unsigned_bundles_are_flagged() {
+        let key = key();
+        let signed = write_bundle(&recipes(), "Club", "", Some(&key)).unwrap();
+        let (verification, read) =
+            read_bundle(&signed, &trust(&self::key()), UntrustedPolicy::Warn).unwrap();
+        assert_eq!(verification.status, SignatureStatus::Untrusted);
+        assert_eq!(verification.signer, None);
+        assert_eq!(verification.warnings.len(), 1);
+        assert_eq!(read.len(), 2);
+
+        let unsigned = write_bundle(&recipes(), "Club", "", None).unwrap();
+        let (verification, _) =
+            read_bundle(&unsigned, &trust(&key), UntrustedPolicy::Warn).unwrap();
+        assert_eq!(verification.status, SignatureStatus::Unsigned);
+        assert_eq!(verification.public_key, None);
+        assert_eq!(verification.warnings, ["Bundle is not signed"]);
+    }
+
+    #[test]
+    fn refuse_policy_rejects_untrusted_and_unsigned() {
+        let key = key();
+        let signed = write_bundle(&recipes(), "Club", "", Some(&key)).unwrap();
+        let unsigned = write_bundle(&recipes(), "Club", "", None).unwrap();
+
+        let error = read_bundle(&signed, &[], UntrustedPolicy::Refuse).unwrap_err();
+        assert!(error.ends_with("import refused"), "{}", error);
+        let error = read_bundle(&unsigned, &trust(&key), UntrustedPolicy::Refuse).unwrap_err();
+        assert_eq!(error, "Bundle is not signed; import refused");
+    }
+
+    #[test]
+    fn altered_bundles_are_refused() {
+        let key = key();
+        let trusted = trust(&key);
+        let bytes = write_bundle(&recipes(), "Club", "", Some(&key)).unwrap();
+        let read = |bytes: &[u8]| read_bundle(bytes, &trusted, UntrustedPolicy::Warn).unwrap_err();
+
+        // One byte of a recipe, same length.
+        let recipe_byte = rewrite(
+            &bytes,
+            |name, content| {
+                if name.starts_with("recipes/001") {
+                    replace(content, "Rodinal", "Rodinol")
+                } else {
+                    content
+                }
+            },
+            None,
+        );
+        assert!(read(&recipe_byte)
+            .ends_with("recipes/001-hp5-rodinal-1-50.json does not match the manifest"));
+
+        let manifest = rewrite(
+            &bytes,
+            |name, content| {
+                if name == MANIFEST_PATH {
+                    replace(content, "\"Club\"", "\"Clu8\"")
+                } else {
+                    content
+                }
+            },
+            None,
+        );
+        assert!(read(&manifest).ends_with("the manifest does not match its signature"));
+
+        let extra = rewrite(
+            &bytes,
+            |_, content| content,
+            Some(("recipes/999-extra.json", b"{}")),
+        );
+        assert!(read(&extra).ends_with("recipes/999-extra.json is not listed in the manifest"));
+
+        let algorithm = rewrite(
+            &bytes,
+            |name, content| {
+                if name == SIGNATURE_PATH {
+                    replace(content, SIGNATURE_ALGORITHM, "rsa")
+                } else {
+                    content
+                }
+            },
+            None,
+        );
+        assert!(read(&algorithm).ends_with("unknown signature algorithm 'rsa'"));
+
+        // Altered is refused even when unsigned bundles are only warned about.
+        let unsigned = write_bundle(&recipes(), "Club", "", None).unwrap();
+        let unsigned = rewrite(
+            &unsigned,
+            |name, content| {
+                if name.starts_with("recipes/002") {
+                    replace(content, "D-76", "D-96")
+                } else {
+                    content
+                }
+            },
+            None,
+        );
+        assert!(read(&unsigned).starts_with("Bundle has been altered"));
+    }
+
+    #[test]
+    fn signing_key_lives_in_the_secret_store() {
+        let dir = TempDir::new("store");
+        let store = FileStore::open(&dir.0.join("secrets.json")).unwrap();
+
+        let created = load_or_create_signing_key(&store, &dir.0).unwrap();
+        assert!(!dir.0.join(SIGNING_KEY_FILE).exists());
+        let stored = store.get(SIGNING_KEY_ACCOUNT).unwrap().unwrap();
+        assert!(!fs::read_to_string(dir.0.join("secrets.json"))
+            .unwrap()
+            .contains(&stored));
+
+        let loaded = load_or_create_signing_key(&store, &dir.0).unwrap();
+        assert_eq!(public_key_hex(&loaded), public_key_hex(&created));
+
+        store.set(SIGNING_KEY_ACCOUNT, "not hex").unwrap();
+        assert_eq!(
+            load_or_create_signing_key(&store, &dir.0).err().unwrap(),
+            "Stored bundle signing key is corrupt"
+        );
+    }
+
+    #[test]
+    fn old_key_file_moves_into_the_store() {
+        let dir = TempDir::new("legacy");
+        let store = FileStore::open(&dir.0.join("secrets.json")).unwrap();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        fs::write(dir.0.join(SIGNING_KEY_FILE), pkcs8.as_ref()).unwrap();
+        let published = public_key_hex(&Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap());
+
+        let key = load_or_create_signing_key(&store, &dir.0).unwrap();
+        assert_eq!(public_key_hex(&key), published);
+        assert!(!dir.0.join(SIGNING_KEY_FILE).exists());
+        assert_eq!(
+            store.get(SIGNING_KEY_ACCOUNT).unwrap(),
+            Some(hex::encode(pkcs8.as_ref()))
+        );
+
+        let again = load_or_create_signing_key(&store, &dir.0).unwrap();
+        assert_eq!(public_key_hex(&again), published);
+    }
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashSet;
+
+use crate::ago_format::{self, TitleParts};
+use crate::mdc::{self, normalize_name};
+
+pub const CATALOG_KINDS: [&str; 2] = ["film", "developer"];
+
+/// Alternatives returned next to the best reading of a title.
+const MAX_ALTERNATIVES: usize = 3;
+
+/// Developers carry more of the confidence than films: the chart lists films
+/// with and without the manufacturer, but developer names are fairly stable.
+const DEVELOPER_WEIGHT: f64 = 0.55;
+
+/// Films are open-ended, so an unknown or missing film still scores this much;
+/// on a tie the reading with the longer film wins.
+const UNKNOWN_FILM_SCORE: f64 = 0.3;
+
+/// Below this a part is not considered matched. Developers under it score
+/// nothing, so leftover words fall to the film rather than a made-up developer.
+const MIN_MATCH_SCORE: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+struct CatalogEntry {
+    name: String,
+    key: String,
+    bigrams: Vec<(char, char)>,
+}
+
+impl CatalogEntry {
+    fn new(name: &str) -> Option<Self> {
+        let key = normalize_name(name);
+        if key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: name.trim().to_string(),
+            bigrams: bigrams(&key),
+            key,
+        })
+    }
+}
+
+/// Known film and developer names used to read expanded titles.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    films: Vec<CatalogEntry>,
+    developers: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleCandidate {
+    pub film_stock: String,
+    pub developer: String,
+    pub dilution: String,
+    /// Catalog names the parts were matched to, which may add a manufacturer.
+    pub matched_film: Option<String>,
+    pub matched_developer: Option<String>,
+    /// 0 to 1.
+    pub confidence: f64,
+}
+
+impl TitleCandidate {
+    pub fn parts(&self) -> TitleParts {
+        TitleParts {
+            film_stock: self.film_stock.clone(),
+            developer: self.developer.clone(),
+            dilution: self.dilution.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleParse {
+    #[serde(flatten)]
+    pub best: TitleCandidate,
+    pub alternatives: Vec<TitleCandidate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CatalogName {
+    pub kind: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+pub fn bigrams(key: &str) -> Vec<(char, char)> {
+    let chars = key.chars().collect::<Vec<_>>();
+    let mut pairs = chars.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Sørensen–Dice coefficient over sorted bigram lists.
+pub fn dice(a: &[(char, char)], b: &[(char, char)]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+/// Best catalog match for `text`: exact key 1.0, the catalog name with a
+/// manufacturer in front ("Tri-X 400" for "Kodak Tri-X 400") 0.9, otherwise
+/// scaled bigram similarity.
+fn best_match<'a>(entries: &'a [CatalogEntry], text: &str) -> (f64, Option<&'a CatalogEntry>) {
+    let key = normalize_name(text);
+    if key.is_empty() {
+        return (0.0, None);
+    }
+    let grams = bigrams(&key);
+
+    let mut best = (0.0, None);
+    for entry in entries {
+        let score = if entry.key == key {
+            1.0
+        } else if key.len() >= 3 && entry.key.ends_with(&key) {
+            0.9
+        } else {
+            0.8 * dice(&grams, &entry.bigrams)
+        };
+        if score > best.0 {
+            best = (score, Some(entry));
+            if score >= 1.0 {
+                break;
+            }
+        }
+    }
+    best
+}
+
+fn is_dilution_token(token: &str) -> bool {
+    let token = token.trim_matches(|c| c == '(' || c == ')');
+    if token.eq_ignore_ascii_case("stock") {
+        return true;
+    }
+    let numbers = token.split(['+', ':']).collect::<Vec<_>>();
+    numbers.len() >= 2
+        && numbers.iter().all(|n| {
+            !n.is_empty()
+                && n.chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+        })
+}
+
+/// Text to read film, developer and dilution from. Exported files carry them all
+/// in the title; device programs put the film in the program name instead.
+pub fn program_title_text(name: &str, expanded_title: &str) -> String {
+    let title = expanded_title.trim_start();
+    let title = title.strip_prefix('-').unwrap_or(title).trim();
+    let name = name.trim();
+    let is_category = ["B&W", "BW", "C-41", "E-6", "ECN-2"]
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(name));
+
+    if name.is_empty() || is_category || title.starts_with(name) {
+        title.to_string()
+    } else {
+        format!("{} {}", name, title).trim().to_string()
+    }
+}
+
+impl Catalog {
+    pub fn new<F, D>(films: F, developers: D) -> Self
+    where
+        F: IntoIterator<Item = String>,
+        D: IntoIterator<Item = String>,
+    {
+        fn entries(names: impl Iterator<Item = String>) -> Vec<CatalogEntry> {
+            let mut seen = HashSet::new();
+            names
+                .filter_map(|name| CatalogEntry::new(&name))
+                .filter(|entry| seen.insert(entry.key.clone()))
+                .collect()
+        }
+
+        let known = ago_format::KNOWN_DEVELOPERS.iter().map(|d| d.to_string());
+        Self {
+            films: entries(films.into_iter()),
+            developers: entries(developers.into_iter().chain(known)),
+        }
+    }
+
+    /// The current chart's films and developers plus names the user added.
+    pub async fn load(pool: &Pool<Sqlite>) -> Result<Self, String> {
+        let mut films = mdc::all_names(pool, "film").await?;
+        let mut developers = mdc::all_names(pool, "developer").await?;
+
+        for entry in list_names(pool, None).await? {
+            match entry.kind.as_str() {
+                "film" => films.push(entry.name),
+                _ => developers.push(entry.name),
+            }
+        }
+        Ok(Self::new(films, developers))
+    }
+
+    fn candidate(&self, film: &str, developer: &str, dilution: &str) -> TitleCandidate {
+        let (film_score, film_match) = best_match(&self.films, film);
+        let (developer_score, developer_match) = best_match(&self.developers, developer);
+        let film_match = film_match.filter(|_| film_score >= MIN_MATCH_SCORE);
+        let developer_match = developer_match.filter(|_| developer_score >= MIN_MATCH_SCORE);
+
+        let film_score = film_score.max(UNKNOWN_FILM_SCORE);
+        let developer_score = if developer_match.is_some() {
+            developer_score
+        } else {
+            0.0
+        };
+        let confidence = DEVELOPER_WEIGHT * developer_score + (1.0 - DEVELOPER_WEIGHT) * film_score;
+
+        TitleCandidate {
+            film_stock: film.to_string(),
+            developer: developer.to_string(),
+            dilution: dilution.to_string(),
+            matched_film: film_match.map(|e| e.name.clone()),
+            matched_developer: developer_match.map(|e| e.name.clone()),
+            confidence: (confidence * 100.0).round() / 100.0,
+        }
+    }
+
+    /// Read "Tri-X 400 D-76 1+1" as film, developer and dilution by trying every
+    /// film/developer split of the words before the dilution and scoring each
+    /// against the catalog.
+    pub fn parse_title(&self, title: &str) -> TitleParse {
+        let title = title.trim_start();
+        let title = title.strip_prefix('-').unwrap_or(title).trim();
+        let mut words = title.split_whitespace().collect::<Vec<_>>();
+
+        let dilution = match words.last() {
+            Some(last) if is_dilution_token(last) => {
+                let token = last.trim_matches(|c| c == '(' || c == ')').to_string();
+                words.pop();
+                token
+            }
+            _ => String::new(),
+        };
+
+        let mut candidates = (0..=words.len())
+            .map(|split| {
+                self.candidate(
+                    &words[..split].join(" "),
+                    &words[split..].join(" "),
+                    &dilution,
+                )
+            })
+            .collect::<Vec<_>>();
+        // Stable sort keeps the split with the longer film first on ties.
+        candidates.reverse();
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        let mut candidates = candidates.into_iter();
+        let best = candidates
+            .next()
+            .unwrap_or_else(|| self.candidate("", "", &dilution));
+        TitleParse {
+            best,
+            alternatives: candidates
+                .filter(|c| c.confidence > 0.0)
+                .take(MAX_ALTERNATIVES)
+                .collect(),
+        }
+    }
+}
+
+/// Names the user added; `kind` filters to films or developers.
+pub async fn list_names(
+    pool: &Pool<Sqlite>,
+    kind: Option<&str>,
+) -> Result<Vec<CatalogName>, String> {
+    sqlx::query_as::<_, CatalogName>(
+        "SELECT kind, name, created_at FROM catalog_names WHERE ?1 IS NULL OR kind = ?1 ORDER BY kind, name",
+    )
+    .bind(kind)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load catalog: {}", e))
+}
+
+pub fn validate_kind(kind: &str) -> Result<String, String> {
+    let kind = kind.trim().to_lowercase();
+    if CATALOG_KINDS.contains(&kind.as_str()) {
+        Ok(kind)
+    } else {
+        Err(format!(
+            "Unknown catalog kind '{}', expected one of: {}",
+            kind,
+            CATALOG_KINDS.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Catalog {
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        Catalog::new(
+            names(&[
+                "Kodak Tri-X 400",
+                "Ilford FP4 Plus",
+                "Ilford HP5 Plus",
+                "Kodak Portra 400",
+            ]),
+            names(&["Kodak D-76", "D-76", "Ilford Ilfosol 3", "Ilfosol 3"]),
+        )
+    }
+
+    fn split(candidate: &TitleCandidate) -> (&str, &str, &str, f64) {
+        (
+            candidate.film_stock.as_str(),
+            candidate.developer.as_str(),
+            candidate.dilution.as_str(),
+            candidate.confidence,
+        )
+    }
+
+    #[test]
+    fn reads_film_developer_and_dilution() {
+        let parse = catalog().parse_title("Tri-X 400 D-76 1+1");
+        assert_eq!(split(&parse.best), ("Tri-X 400", "D-76", "1+1", 0.96));
+        assert_eq!(parse.best.matched_film.as_deref(), Some("Kodak Tri-X 400"));
+        assert_eq!(parse.best.matched_developer.as_deref(), Some("D-76"));
+        let alternatives = parse.alternatives.iter().map(split).collect::<Vec<_>>();
+        assert_eq!(
+            alternatives,
+            [
+                ("Tri-X 400 D-76", "", "1+1", 0.22),
+                ("Tri-X", "400 D-76", "1+1", 0.15),
+                ("", "Tri-X 400 D-76", "1+1", 0.13),
+            ]
+        );
+
+        // The developer's own number is not taken for a dilution or the film.
+        let parse = catalog().parse_title("FP4+ Ilfosol 3 1+9");
+        assert_eq!(split(&parse.best), ("FP4+", "Ilfosol 3", "1+9", 0.96));
+        assert_eq!(parse.best.matched_film.as_deref(), Some("Ilford FP4 Plus"));
+        assert_eq!(
+            split(&parse.alternatives[0]),
+            ("", "FP4+ Ilfosol 3", "1+9", 0.48)
+        );
+    }
+
+    #[test]
+    fn known_developers_and_program_prefixes() {
+        let parse = catalog().parse_title(" - HP5+ Rodinal (1+50)");
+        assert_eq!(split(&parse.best), ("HP5+", "Rodinal", "1+50", 0.96));
+        assert_eq!(parse.best.matched_developer.as_deref(), Some("Rodinal"));
+    }
+
+    #[test]
+    fn missing_parts_lower_the_confidence() {
+        let parse = catalog().parse_title("Portra 400");
+        assert_eq!(split(&parse.best), ("Portra 400", "", "", 0.41));
+        assert_eq!(parse.best.matched_developer, None);
+
+        let parse = catalog().parse_title("Mystery Film Mystery Dev 1:4");
+        assert_eq!(parse.best.dilution, "1:4");
+        assert_eq!(parse.best.confidence, 0.13);
+        assert_eq!(parse.best.matched_film, None);
+        assert_eq!(parse.alternatives.len(), MAX_ALTERNATIVES);
+
+        let parse = catalog().parse_title("");
+        assert_eq!(split(&parse.best), ("", "", "", 0.13));
+        assert!(parse.alternatives.is_empty());
+    }
+
+    #[test]
+    fn program_text_joins_name_and_title() {
+        assert_eq!(
+            program_title_text("Tri-X 400", " - D-76 1+1"),
+            "Tri-X 400 D-76 1+1"
+        );
+        assert_eq!(
+            program_title_text("B&W", " - Tri-X 400 D-76 1+1"),
+            "Tri-X 400 D-76 1+1"
+        );
+        assert_eq!(
+            program_title_text("Tri-X 400", "Tri-X 400 D-76"),
+            "Tri-X 400 D-76"
+        );
+    }
+}
@@ -0,0 +1,608 @@
+use serde::{Deserialize, Serialize};
+
+use crate::mdc::normalize_name;
+
+/// What the parts of a dilution are measured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DilutionBase {
+    /// A liquid concentrate such as Rodinal or HC-110 syrup.
+    Concentrate,
+    /// A stock solution mixed from powder, such as D-76 or Xtol.
+    Stock,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dilution {
+    /// One entry per chemical part: `[1]` for "1+50", `[1, 1]` for "1+1+100".
+    pub parts: Vec<f64>,
+    pub water: f64,
+    pub base: DilutionBase,
+    pub notation: String,
+}
+
+impl Dilution {
+    pub fn total_parts(&self) -> f64 {
+        self.parts.iter().sum::<f64>() + self.water
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReelVolume {
+    /// One of the frontend's `REEL_TYPES`.
+    pub reel: String,
+    pub ml: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TankVolume {
+    pub tank: String,
+    pub volumes: Vec<ReelVolume>,
+}
+
+/// Fill volumes per tank and reel type; the frontend lists them through `list_tank_volumes`.
+const TANK_VOLUMES: [(&str, &[(&str, f64)]); 5] = [
+    ("35mm tank", &[("35mm", 250.0)]),
+    ("Universal tank", &[("35mm", 350.0)]),
+    ("Multi-reel 3", &[("35mm", 450.0), ("4x5", 350.0)]),
+    (
+        "Multi-reel 5",
+        &[("35mm", 650.0), ("4x5", 500.0), ("5x7", 350.0), ("paper", 250.0)],
+    ),
+    (
+        "Multi-reel 8",
+        &[("35mm", 950.0), ("4x5", 750.0), ("5x7", 500.0), ("paper", 400.0)],
+    ),
+];
+
+/// Developers sold as powder and mixed to a stock solution first, so their
+/// dilutions ("D-76 1+1") are parts of stock rather than of a concentrate.
+const STOCK_DEVELOPERS: [&str; 9] = [
+    "d76", "id11", "xtol", "perceptol", "microphen", "d23", "d25", "d96", "fx2",
+];
+
+/// Kodak's lettered HC-110 dilutions, from syrup concentrate.
+const HC110_DILUTIONS: [(&str, f64); 8] = [
+    ("A", 15.0),
+    ("B", 31.0),
+    ("C", 19.0),
+    ("D", 39.0),
+    ("E", 47.0),
+    ("F", 79.0),
+    ("G", 119.0),
+    ("H", 63.0),
+];
+
+/// Minimum concentrate (or stock, for powder developers) per roll — 35mm 36
+/// exposures or one 120 roll — below which the developer exhausts before the
+/// film is fully developed. Keys are `normalize_name` fragments; first match wins.
+/// Only manufacturer-documented minimums are listed; other developers get no check.
+const MIN_CONCENTRATE_ML_PER_ROLL: [(&str, f64); 5] = [
+    // Agfa Rodinal and its successors (Adox Adonal, Compard R09 One Shot)
+    // datasheets: at least 10 ml of concentrate per film.
+    ("rodinal", 10.0),
+    ("adonal", 10.0),
+    ("r09", 10.0),
+    // Jay DeFehr's 510-Pyro instructions: at least 5 ml of concentrate per
+    // roll, whatever the dilution.
+    ("510pyro", 5.0),
+    // Kodak J-109 (Xtol): at least 100 ml of stock solution per 80 sq in roll.
+    ("xtol", 100.0),
+];
+
+/// Setting holding the smallest fill the processor will run a program with.
+pub const MIN_FILL_SETTING: &str = "ago_min_fill_ml";
+
+/// Used when [`MIN_FILL_SETTING`] is unset. There is no published AGO figure;
+/// this is the fill of the smallest tank in `TANK_VOLUMES`.
+pub const DEFAULT_MIN_FILL_ML: f64 = 250.0;
+
+/// Fills suggested when the tank's own fill holds too little concentrate.
+const FILL_STEP_ML: f64 = 50.0;
+
+pub fn tank_volumes() -> Vec<TankVolume> {
+    TANK_VOLUMES
+        .iter()
+        .map(|(tank, volumes)| TankVolume {
+            tank: tank.to_string(),
+            volumes: volumes
+                .iter()
+                .map(|(reel, ml)| ReelVolume {
+                    reel: reel.to_string(),
+                    ml: *ml,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn tank_volume(tank: &str, reel: &str) -> Option<f64> {
+    TANK_VOLUMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(tank.trim()))
+        .and_then(|(_, volumes)| {
+            volumes
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(reel.trim()))
+                .map(|(_, ml)| *ml)
+        })
+}
+
+fn is_stock_developer(developer: &str) -> bool {
+    let key = normalize_name(developer);
+    !key.is_empty() && STOCK_DEVELOPERS.iter().any(|d| key.contains(d))
+}
+
+pub fn min_concentrate_per_roll(developer: &str) -> Option<f64> {
+    let key = normalize_name(developer);
+    if key.is_empty() {
+        return None;
+    }
+    MIN_CONCENTRATE_ML_PER_ROLL
+        .iter()
+        .find(|(name, _)| key.contains(name))
+        .map(|(_, ml)| *ml)
+}
+
+fn parse_number(raw: &str) -> Option<f64> {
+    raw.trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+}
+
+/// Parse a dilution notation:
+/// - "Stock", "undiluted" or "replenished" — the working solution is the stock itself
+/// - "1+50", "1:50" — one part concentrate (or stock) to N parts water
+/// - "1+1+100", "20+15+965" — two-part developers as A+B+water
+/// - "2%", "2% solution" — percentage solutions
+/// - "stock 1+1" — a working solution made from stock
+/// - "B", "H" — Kodak's lettered HC-110 dilutions
+///
+/// `developer` decides whether plain "1+N" dilutions are measured from stock or concentrate.
+pub fn parse_dilution(notation: &str, developer: &str) -> Result<Dilution, String> {
+    let original = notation.trim();
+    let mut text = original.to_lowercase();
+    if text.is_empty() {
+        return Err("Dilution is empty".to_string());
+    }
+
+    let mut base = if is_stock_developer(developer) {
+        DilutionBase::Stock
+    } else {
+        DilutionBase::Concentrate
+    };
+    if let Some(rest) = text.strip_prefix("stock") {
+        if rest.trim().is_empty() {
+            text = String::new();
+        } else {
+            base = DilutionBase::Stock;
+            text = rest.trim().to_string();
+        }
+    }
+
+    let dilution = |parts: Vec<f64>, water: f64, base: DilutionBase| Dilution {
+        parts,
+        water,
+        base,
+        notation: original.to_string(),
+    };
+
+    if text.is_empty() || text == "undiluted" || text.contains("replenish") {
+        return Ok(dilution(vec![1.0], 0.0, DilutionBase::Stock));
+    }
+
+    if let Some((_, water)) = HC110_DILUTIONS
+        .iter()
+        .find(|(letter, _)| letter.eq_ignore_ascii_case(&text))
+    {
+        return Ok(dilution(vec![1.0], *water, DilutionBase::Concentrate));
+    }
+
+    if let Some((percent, _)) = text.split_once('%') {
+        let percent = parse_number(percent)
+            .filter(|p| *p > 0.0 && *p <= 100.0)
+            .ok_or_else(|| format!("Invalid percentage dilution '{}'", original))?;
+        return Ok(dilution(vec![percent], 100.0 - percent, base));
+    }
+
+    let numbers = text
+        .split(['+', ':'])
+        .map(parse_number)
+        .collect::<Option<Vec<_>>>()
+        .filter(|n| n.len() >= 2)
+        .ok_or_else(|| format!("Unrecognised dilution '{}'", original))?;
+
+    let (water, parts) = numbers.split_last().expect("at least two numbers");
+    if parts.iter().any(|p| *p <= 0.0) {
+        return Err(format!("Dilution '{}' has a zero part", original));
+    }
+    Ok(dilution(parts.to_vec(), *water, base))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartVolume {
+    pub label: String,
+    pub ml: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemistryRequest {
+    #[serde(default)]
+    pub developer: String,
+    pub dilution: String,
+    #[serde(default)]
+    pub tank: Option<String>,
+    #[serde(default)]
+    pub reel: Option<String>,
+    /// Overrides the tank/reel fill volume.
+    #[serde(default)]
+    pub volume_ml: Option<f64>,
+    /// Roll equivalents being developed (one 35mm 36 exposure or 120 roll each).
+    #[serde(default)]
+    pub rolls: Option<f64>,
+    /// Smallest fill to accept; [`DEFAULT_MIN_FILL_ML`] when unset.
+    #[serde(default)]
+    pub min_fill_ml: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemistryResult {
+    pub dilution: Dilution,
+    pub total_ml: f64,
+    pub parts: Vec<PartVolume>,
+    pub water_ml: f64,
+    /// Sum of all parts, i.e. the concentrate or stock taken from the bottles.
+    pub concentrate_ml: f64,
+    pub min_concentrate_ml: Option<f64>,
+    pub warnings: Vec<String>,
+}
+
+fn round_ml(ml: f64) -> f64 {
+    (ml * 10.0).round() / 10.0
+}
+
+fn part_labels(dilution: &Dilution, developer: &str) -> Vec<String> {
+    let base = match dilution.base {
+        DilutionBase::Stock => "stock",
+        DilutionBase::Concentrate => "concentrate",
+    };
+    let name = if developer.trim().is_empty() {
+        "Developer".to_string()
+    } else {
+        developer.trim().to_string()
+    };
+
+    if dilution.parts.len() == 1 {
+        return vec![format!("{} {}", name, base)];
+    }
+    (0..dilution.parts.len())
+        .map(|i| format!("{} part {}", name, (b'A' + i as u8) as char))
+        .collect()
+}
+
+pub fn resolve_total_ml(request: &ChemistryRequest) -> Result<f64, String> {
+    if let Some(volume) = request.volume_ml.filter(|v| *v > 0.0) {
+        return Ok(volume);
+    }
+    let tank = request.tank.as_deref().unwrap_or_default();
+    let reel = request.reel.as_deref().unwrap_or("35mm");
+    tank_volume(tank, reel)
+        .ok_or_else(|| format!("No fill volume for {} reels in '{}'", reel, tank))
+}
+
+/// Split a tank fill into part and water volumes and check the concentrate minimum.
+pub fn calculate(request: &ChemistryRequest) -> Result<ChemistryResult, String> {
+    let dilution = parse_dilution(&request.dilution, &request.developer)?;
+    let total_ml = resolve_total_ml(request)?;
+    let total_parts = dilution.total_parts();
+
+    let parts = part_labels(&dilution, &request.developer)
+        .into_iter()
+        .zip(&dilution.parts)
+        .map(|(label, part)| PartVolume {
+            label,
+            ml: round_ml(total_ml * part / total_parts),
+        })
+        .collect::<Vec<_>>();
+    let concentrate_ml = round_ml(parts.iter().map(|p| p.ml).sum());
+    let water_ml = round_ml(total_ml - concentrate_ml);
+
+    let rolls = request.rolls.unwrap_or(1.0).max(0.0);
+    let min_concentrate_ml = min_concentrate_per_roll(&request.developer).map(|ml| ml * rolls);
+
+    let mut warnings = Vec::new();
+    if let Some(minimum) = min_concentrate_ml.filter(|min| concentrate_ml < *min) {
+        warnings.push(format!(
+            "{} ml of {} is below the {} ml minimum for {} roll(s)",
+            concentrate_ml,
+            request.developer.trim(),
+            minimum,
+            rolls
+        ));
+    }
+
+    Ok(ChemistryResult {
+        dilution,
+        total_ml,
+        parts,
+        water_ml,
+        concentrate_ml,
+        min_concentrate_ml,
+        warnings,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemistryIssue {
+    /// `below_min_concentrate` or `below_tank_fill`.
+    pub kind: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemistrySuggestion {
+    /// `volume` or `dilution`.
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    pub volume_ml: Option<f64>,
+    #[serde(default)]
+    pub dilution: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemistryValidation {
+    pub ok: bool,
+    pub result: ChemistryResult,
+    pub issues: Vec<ChemistryIssue>,
+    pub suggestions: Vec<ChemistrySuggestion>,
+}
+
+pub fn format_ml(ml: f64) -> String {
+    if ml.fract().abs() < f64::EPSILON {
+        format!("{}", ml as i64)
+    } else {
+        format!("{:.1}", ml)
+    }
+}
+
+/// The fill the chosen tank and reel need, if the request names one.
+fn required_fill_ml(request: &ChemistryRequest) -> Option<f64> {
+    let tank = request.tank.as_deref()?;
+    tank_volume(tank, request.reel.as_deref().unwrap_or("35mm"))
+}
+
+/// Check a dilution against the developer's per-roll minimum and the tank fill,
+/// suggesting a bigger volume or a stronger dilution when either falls short.
+pub fn validate(request: &ChemistryRequest) -> Result<ChemistryValidation, String> {
+    let result = calculate(request)?;
+    let mut issues = Vec::new();
+    let mut suggestions = Vec::new();
+
+    let processor_min = request
+        .min_fill_ml
+        .filter(|ml| *ml > 0.0)
+        .unwrap_or(DEFAULT_MIN_FILL_ML);
+    let min_fill = required_fill_ml(request)
+        .unwrap_or(processor_min)
+        .max(processor_min);
+    if result.total_ml < min_fill {
+        issues.push(ChemistryIssue {
+            kind: "below_tank_fill".to_string(),
+            message: format!(
+                "{} ml does not cover the reels; fill at least {} ml",
+                format_ml(result.total_ml),
+                format_ml(min_fill)
+            ),
+        });
+        suggestions.push(ChemistrySuggestion {
+            kind: "volume".to_string(),
+            message: format!("Mix {} ml of working solution", format_ml(min_fill)),
+            volume_ml: Some(min_fill),
+            dilution: None,
+        });
+    }
+
+    if let Some(minimum) = result
+        .min_concentrate_ml
+        .filter(|min| result.concentrate_ml < *min)
+    {
+        issues.push(ChemistryIssue {
+            kind: "below_min_concentrate".to_string(),
+            message: result.warnings.first().cloned().unwrap_or_default(),
+        });
+
+        // Volume at which the current dilution reaches the minimum, rounded up
+        // to a measurable step and never below the tank fill.
+        let concentrate_share =
+            result.dilution.parts.iter().sum::<f64>() / result.dilution.total_parts();
+        let needed_ml = ((minimum / concentrate_share) / FILL_STEP_ML).ceil() * FILL_STEP_ML;
+        let needed_ml = needed_ml.max(min_fill);
+        suggestions.push(ChemistrySuggestion {
+            kind: "volume".to_string(),
+            message: format!(
+                "Mix {} ml at {} to get {} ml of {}",
+                format_ml(needed_ml),
+                result.dilution.notation,
+                format_ml(round_ml(needed_ml * concentrate_share)),
+                request.developer.trim()
+            ),
+            volume_ml: Some(needed_ml),
+            dilution: None,
+        });
+
+        // Strongest single-part dilution that still fits the current volume.
+        if result.dilution.parts.len() == 1 && result.dilution.water > 0.0 {
+            let fill_ml = result.total_ml.max(min_fill);
+            let water = (fill_ml / minimum - 1.0).floor();
+            if water >= 1.0 {
+                let notation = format!("1+{}", water as i64);
+                suggestions.push(ChemistrySuggestion {
+                    kind: "dilution".to_string(),
+                    message: format!(
+                        "Use {} in {} ml for {} ml of {}",
+                        notation,
+                        format_ml(fill_ml),
+                        format_ml(round_ml(fill_ml / (water + 1.0))),
+                        request.developer.trim()
+                    ),
+                    volume_ml: Some(fill_ml),
+                    dilution: Some(notation),
+                });
+            }
+        }
+    }
+
+    Ok(ChemistryValidation {
+        ok: issues.is_empty(),
+        result,
+        issues,
+        suggestions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(developer: &str, dilution: &str, volume_ml: f64) -> ChemistryRequest {
+        ChemistryRequest {
+            developer: developer.to_string(),
+            dilution: dilution.to_string(),
+            tank: None,
+            reel: None,
+            volume_ml: Some(volume_ml),
+            rolls: None,
+            min_fill_ml: None,
+        }
+    }
+
+    #[test]
+    fn parses_dilution_notations() {
+        use DilutionBase::{Concentrate, Stock};
+        let cases: [(&str, &str, &[f64], f64, DilutionBase); 12] = [
+            ("1+50", "Rodinal", &[1.0], 50.0, Concentrate),
+            ("1:50", "Rodinal", &[1.0], 50.0, Concentrate),
+            (" 1 + 9 ", "Ilfosol 3", &[1.0], 9.0, Concentrate),
+            ("1+1", "D-76", &[1.0], 1.0, Stock),
+            ("stock 1+1", "Ilfotec DD-X", &[1.0], 1.0, Stock),
+            ("Stock", "ID-11", &[1.0], 0.0, Stock),
+            ("undiluted", "Ilfotec DD-X", &[1.0], 0.0, Stock),
+            ("replenished", "Xtol", &[1.0], 0.0, Stock),
+            ("1+1+100", "510 Pyro", &[1.0, 1.0], 100.0, Concentrate),
+            ("20+15+965", "Pyrocat-HD", &[20.0, 15.0], 965.0, Concentrate),
+            ("2%", "Pyrocat-HD", &[2.0], 98.0, Concentrate),
+            ("B", "HC-110", &[1.0], 31.0, Concentrate),
+        ];
+        for (notation, developer, parts, water, base) in cases {
+            let dilution = parse_dilution(notation, developer).unwrap();
+            assert_eq!(dilution.parts, parts, "{}", notation);
+            assert_eq!(dilution.water, water, "{}", notation);
+            assert_eq!(dilution.base, base, "{}", notation);
+            assert_eq!(dilution.notation, notation.trim());
+        }
+
+        for bad in ["", "1+", "one to fifty", "0+50", "120%", "1+-5"] {
+            assert!(parse_dilution(bad, "Rodinal").is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn splits_the_fill_into_parts_and_water() {
+        let cases: [(&str, &str, f64, &[f64], f64); 5] = [
+            ("Rodinal", "1+50", 510.0, &[10.0], 500.0),
+            ("D-76", "1+1", 500.0, &[250.0], 250.0),
+            ("510 Pyro", "1+1+100", 510.0, &[5.0, 5.0], 500.0),
+            ("Pyrocat-HD", "2%", 500.0, &[10.0], 490.0),
+            ("Xtol", "Stock", 300.0, &[300.0], 0.0),
+        ];
+        for (developer, dilution, volume_ml, parts, water_ml) in cases {
+            let result = calculate(&request(developer, dilution, volume_ml)).unwrap();
+            let volumes = result.parts.iter().map(|p| p.ml).collect::<Vec<_>>();
+            assert_eq!(volumes, parts, "{} {}", developer, dilution);
+            assert_eq!(result.water_ml, water_ml, "{} {}", developer, dilution);
+        }
+
+        let two_part = calculate(&request("510 Pyro", "1+1+100", 510.0)).unwrap();
+        let labels = two_part
+            .parts
+            .iter()
+            .map(|p| p.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["510 Pyro part A", "510 Pyro part B"]);
+        let stock = calculate(&request("D-76", "1+1", 500.0)).unwrap();
+        assert_eq!(stock.parts[0].label, "D-76 stock");
+    }
+
+    #[test]
+    fn rodinal_needs_ten_ml_per_roll() {
+        let one_roll = calculate(&request("Rodinal", "1+50", 510.0)).unwrap();
+        assert_eq!(one_roll.min_concentrate_ml, Some(10.0));
+        assert!(one_roll.warnings.is_empty());
+
+        let two_rolls = calculate(&ChemistryRequest {
+            rolls: Some(2.0),
+            ..request("Rodinal", "1+50", 510.0)
+        })
+        .unwrap();
+        assert_eq!(two_rolls.min_concentrate_ml, Some(20.0));
+        assert_eq!(
+            two_rolls.warnings,
+            ["10 ml of Rodinal is below the 20 ml minimum for 2 roll(s)"]
+        );
+
+        let tank = calculate(&ChemistryRequest {
+            tank: Some("35mm tank".to_string()),
+            volume_ml: None,
+            ..request("Rodinal", "1+100", 0.0)
+        })
+        .unwrap();
+        assert_eq!(tank.total_ml, 250.0);
+        assert_eq!(tank.warnings.len(), 1);
+    }
+
+    fn suggestions(validation: &ChemistryValidation) -> Vec<(&str, Option<f64>, Option<&str>)> {
+        validation
+            .suggestions
+            .iter()
+            .map(|s| (s.kind.as_str(), s.volume_ml, s.dilution.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn low_concentrate_is_flagged_with_fixes() {
+        let pyro = validate(&request("510-Pyro", "1+100", 250.0)).unwrap();
+        assert!(!pyro.ok);
+        assert_eq!(pyro.result.min_concentrate_ml, Some(5.0));
+        assert_eq!(pyro.result.concentrate_ml, 2.5);
+        assert_eq!(pyro.issues[0].kind, "below_min_concentrate");
+        assert_eq!(
+            suggestions(&pyro),
+            [
+                ("volume", Some(550.0), None),
+                ("dilution", Some(250.0), Some("1+49")),
+            ]
+        );
+
+        let rodinal = validate(&request("Rodinal", "1+100", 500.0)).unwrap();
+        assert!(!rodinal.ok);
+        assert_eq!(rodinal.result.concentrate_ml, 5.0);
+        assert_eq!(
+            suggestions(&rodinal),
+            [
+                ("volume", Some(1050.0), None),
+                ("dilution", Some(500.0), Some("1+49")),
+            ]
+        );
+    }
+
+    #[test]
+    fn enough_concentrate_passes() {
+        assert!(validate(&request("510 Pyro", "1+50", 300.0)).unwrap().ok);
+        assert!(validate(&request("Rodinal", "1+25", 300.0)).unwrap().ok);
+        // No published minimum, so no check.
+        assert!(validate(&request("HC-110", "1+63", 300.0)).unwrap().ok);
+    }
+}
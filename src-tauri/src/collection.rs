@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+const COLLECTION_COLUMNS: &str = "id, name, description, created_at, updated_at";
+
+/// A named set of recipes, such as the house programs every unit should carry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Member recipes in display order.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub recipe_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCollection {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub recipe_ids: Vec<String>,
+}
+
+pub fn validate(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Collection name is required".to_string());
+    }
+    Ok(())
+}
+
+/// Replace a collection's members, keeping the given order and dropping repeats.
+pub async fn replace_members(
+    conn: &mut SqliteConnection,
+    collection_id: &str,
+    recipe_ids: &[String],
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM collection_recipes WHERE collection_id = ?")
+        .bind(collection_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to clear collection: {}", e))?;
+
+    for (index, recipe_id) in recipe_ids.iter().enumerate() {
+        sqlx::query(
+            "INSERT OR IGNORE INTO collection_recipes (collection_id, recipe_id, sort_order) VALUES (?, ?, ?)",
+        )
+        .bind(collection_id)
+        .bind(recipe_id)
+        .bind(index as i64)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to add recipe to collection: {}", e))?;
+    }
+    Ok(())
+}
+
+async fn load_members(pool: &Pool<Sqlite>, collection_id: &str) -> Result<Vec<String>, String> {
+    sqlx::query_scalar(
+        "SELECT recipe_id FROM collection_recipes WHERE collection_id = ? ORDER BY sort_order",
+    )
+    .bind(collection_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load collection recipes: {}", e))
+}
+
+pub async fn load_collection(pool: &Pool<Sqlite>, id: &str) -> Result<Collection, String> {
+    let mut collection = sqlx::query_as::<_, Collection>(&format!(
+        "SELECT {} FROM collections WHERE id = ?",
+        COLLECTION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load collection: {}", e))?
+    .ok_or_else(|| format!("Collection {} not found", id))?;
+    collection.recipe_ids = load_members(pool, &collection.id).await?;
+    Ok(collection)
+}
+
+pub async fn load_all_collections(pool: &Pool<Sqlite>) -> Result<Vec<Collection>, String> {
+    let mut collections = sqlx::query_as::<_, Collection>(&format!(
+        "SELECT {} FROM collections ORDER BY name COLLATE NOCASE",
+        COLLECTION_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load collections: {}", e))?;
+    for collection in &mut collections {
+        collection.recipe_ids = load_members(pool, &collection.id).await?;
+    }
+    Ok(collections)
+}
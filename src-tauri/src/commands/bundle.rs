@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::bundle::{self, BundleVerification, TrustedKey, UntrustedPolicy};
+use crate::db;
+use crate::external_import::ImportedRecipe;
+use crate::recipe;
+use crate::secrets;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportReport {
+    pub verification: BundleVerification,
+    pub imported: Vec<ImportedRecipe>,
+}
+
+fn app_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to find app data folder: {}", e))
+}
+
+/// The public half of this app's bundle signing key, for recipients to trust.
+#[tauri::command]
+pub async fn bundle_public_key(app: tauri::AppHandle) -> Result<String, String> {
+    let store = secrets::default_store()?;
+    let key = bundle::load_or_create_signing_key(store.as_ref(), &app_data_dir(&app)?)?;
+    Ok(bundle::public_key_hex(&key))
+}
+
+/// Save recipes as a bundle archive, signed with the app's key unless `sign` is false.
+#[tauri::command]
+pub async fn export_recipe_bundle(
+    app: tauri::AppHandle,
+    recipe_ids: Vec<String>,
+    publisher: Option<String>,
+    sign: Option<bool>,
+    default_name: Option<String>,
+) -> Result<String, String> {
+    if recipe_ids.is_empty() {
+        return Err("Select at least one recipe to export".to_string());
+    }
+    let pool = db::pool(&app).await?;
+    let mut recipes = Vec::with_capacity(recipe_ids.len());
+    for id in &recipe_ids {
+        recipes.push(recipe::load_recipe(&pool, id).await?);
+    }
+
+    let key = if sign.unwrap_or(true) {
+        let store = secrets::default_store()?;
+        Some(bundle::load_or_create_signing_key(
+            store.as_ref(),
+            &app_data_dir(&app)?,
+        )?)
+    } else {
+        None
+    };
+    let bytes = bundle::write_bundle(
+        &recipes,
+        publisher.as_deref().unwrap_or_default().trim(),
+        &db::now_iso(),
+        key.as_ref(),
+    )?;
+
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(default_name.unwrap_or_else(|| "recipes.agobundle".to_string()))
+        .add_filter("Recipe bundle", &["agobundle", "zip"])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_str = path.to_string();
+            fs::write(&path_str, &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+            Ok(path_str)
+        }
+        None => Err("Export cancelled".to_string()),
+    }
+}
+
+/// Verify and import a bundle, picked with a dialog when `path` is not given.
+/// Altered bundles are refused; unsigned or untrusted ones follow the
+/// `untrusted_bundle_policy` setting.
+#[tauri::command]
+pub async fn import_recipe_bundle(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<BundleImportReport, String> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => path,
+        None => app
+            .dialog()
+            .file()
+            .add_filter("Recipe bundle", &["agobundle", "zip"])
+            .blocking_pick_file()
+            .map(|path| path.to_string())
+            .ok_or_else(|| "Import cancelled".to_string())?,
+    };
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let pool = db::pool(&app).await?;
+    let trusted = bundle::parse_trusted_keys(
+        db::setting(&pool, bundle::TRUSTED_KEYS_SETTING)
+            .await?
+            .as_deref(),
+    )?;
+    let policy = UntrustedPolicy::from_setting(
+        db::setting(&pool, bundle::UNTRUSTED_POLICY_SETTING)
+            .await?
+            .as_deref(),
+    );
+    let (verification, recipes) = bundle::read_bundle(&bytes, &trusted, policy)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut imported = Vec::with_capacity(recipes.len());
+    for recipe in recipes {
+        let source = recipe.name.clone();
+        let mut recipe = bundle::as_new_recipe(recipe);
+        recipe::insert_recipe(&mut tx, &mut recipe).await?;
+        imported.push(ImportedRecipe {
+            source,
+            recipe_id: recipe.id,
+            name: recipe.name,
+        });
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipes: {}", e))?;
+
+    Ok(BundleImportReport {
+        verification,
+        imported,
+    })
+}
+
+/// Add a publisher's key to the trusted list, replacing any entry with the same key.
+#[tauri::command]
+pub async fn trust_bundle_key(
+    app: tauri::AppHandle,
+    name: String,
+    public_key: String,
+) -> Result<Vec<TrustedKey>, String> {
+    let public_key = bundle::normalize_public_key(&public_key)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Give the key a name".to_string());
+    }
+
+    let pool = db::pool(&app).await?;
+    let mut keys = bundle::parse_trusted_keys(
+        db::setting(&pool, bundle::TRUSTED_KEYS_SETTING)
+            .await?
+            .as_deref(),
+    )?;
+    keys.retain(|k| bundle::normalize_public_key(&k.public_key).ok().as_ref() != Some(&public_key));
+    keys.push(TrustedKey {
+        name: name.to_string(),
+        public_key,
+    });
+    save_trusted_keys(&pool, &keys).await?;
+    Ok(keys)
+}
+
+#[tauri::command]
+pub async fn untrust_bundle_key(
+    app: tauri::AppHandle,
+    public_key: String,
+) -> Result<Vec<TrustedKey>, String> {
+    let public_key = public_key.trim().to_lowercase();
+    let pool = db::pool(&app).await?;
+    let mut keys = bundle::parse_trusted_keys(
+        db::setting(&pool, bundle::TRUSTED_KEYS_SETTING)
+            .await?
+            .as_deref(),
+    )?;
+    keys.retain(|k| k.public_key.trim().to_lowercase() != public_key);
+    save_trusted_keys(&pool, &keys).await?;
+    Ok(keys)
+}
+
+async fn save_trusted_keys(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    keys: &[TrustedKey],
+) -> Result<(), String> {
+    let json = serde_json::to_string(keys)
+        .map_err(|e| format!("Failed to save trusted bundle keys: {}", e))?;
+    db::set_setting(pool, bundle::TRUSTED_KEYS_SETTING, &json).await
+}
@@ -0,0 +1,74 @@
+use crate::catalog::{self, Catalog, CatalogName, TitleParse};
+use crate::db;
+
+/// Split a program title into film, developer and dilution. `name` is the
+/// program name, which holds the film on programs read from the AGO.
+#[tauri::command]
+pub async fn parse_program_title(
+    app: tauri::AppHandle,
+    title: String,
+    name: Option<String>,
+) -> Result<TitleParse, String> {
+    let pool = db::pool(&app).await?;
+    let catalog = Catalog::load(&pool).await?;
+    Ok(catalog.parse_title(&catalog::program_title_text(
+        name.as_deref().unwrap_or_default(),
+        &title,
+    )))
+}
+
+#[tauri::command]
+pub async fn list_catalog_names(
+    app: tauri::AppHandle,
+    kind: Option<String>,
+) -> Result<Vec<CatalogName>, String> {
+    let pool = db::pool(&app).await?;
+    let kind = kind.map(|k| catalog::validate_kind(&k)).transpose()?;
+    catalog::list_names(&pool, kind.as_deref()).await
+}
+
+/// Teach the title parser a film or developer the chart doesn't know.
+#[tauri::command]
+pub async fn add_catalog_name(
+    app: tauri::AppHandle,
+    kind: String,
+    name: String,
+) -> Result<CatalogName, String> {
+    let pool = db::pool(&app).await?;
+    let kind = catalog::validate_kind(&kind)?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Name is required".to_string());
+    }
+
+    let entry = CatalogName {
+        kind,
+        name,
+        created_at: db::now_iso(),
+    };
+    sqlx::query("INSERT OR IGNORE INTO catalog_names (kind, name, created_at) VALUES (?, ?, ?)")
+        .bind(&entry.kind)
+        .bind(&entry.name)
+        .bind(&entry.created_at)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to add catalog name: {}", e))?;
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn delete_catalog_name(
+    app: tauri::AppHandle,
+    kind: String,
+    name: String,
+) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query("DELETE FROM catalog_names WHERE kind = ? AND name = ?")
+        .bind(kind.trim().to_lowercase())
+        .bind(name.trim())
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete catalog name: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::chemistry::{
+    self, ChemistryRequest, ChemistryResult, ChemistryValidation, Dilution, TankVolume,
+};
+use crate::{db, recipe};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeChemistryCheck {
+    /// Takes developer and dilution from a saved recipe; the fields below override it.
+    #[serde(default)]
+    pub recipe_id: Option<String>,
+    #[serde(default)]
+    pub developer: Option<String>,
+    #[serde(default)]
+    pub dilution: Option<String>,
+    #[serde(default)]
+    pub tank: Option<String>,
+    #[serde(default)]
+    pub reel: Option<String>,
+    #[serde(default)]
+    pub volume_ml: Option<f64>,
+    #[serde(default)]
+    pub rolls: Option<f64>,
+}
+
+/// The configured minimum fill, or the default when unset or not a positive number.
+pub(crate) async fn min_fill_ml(pool: &Pool<Sqlite>) -> Result<f64, String> {
+    Ok(db::setting(pool, chemistry::MIN_FILL_SETTING)
+        .await?
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|ml| *ml > 0.0)
+        .unwrap_or(chemistry::DEFAULT_MIN_FILL_ML))
+}
+
+#[tauri::command]
+pub async fn parse_dilution(dilution: String, developer: Option<String>) -> Result<Dilution, String> {
+    chemistry::parse_dilution(&dilution, developer.as_deref().unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn calculate_chemistry(request: ChemistryRequest) -> Result<ChemistryResult, String> {
+    chemistry::calculate(&request)
+}
+
+#[tauri::command]
+pub async fn list_tank_volumes() -> Result<Vec<TankVolume>, String> {
+    Ok(chemistry::tank_volumes())
+}
+
+#[tauri::command]
+pub async fn validate_recipe_chemistry(
+    app: tauri::AppHandle,
+    check: RecipeChemistryCheck,
+) -> Result<ChemistryValidation, String> {
+    let pool = db::pool(&app).await?;
+    let (mut developer, mut dilution) = (String::new(), String::new());
+    if let Some(recipe_id) = &check.recipe_id {
+        let recipe = recipe::load_recipe(&pool, recipe_id).await?;
+        developer = recipe.developer;
+        dilution = recipe.dilution;
+    }
+
+    let request = ChemistryRequest {
+        developer: check.developer.unwrap_or(developer),
+        dilution: check.dilution.unwrap_or(dilution),
+        tank: check.tank,
+        reel: check.reel,
+        volume_ml: check.volume_ml,
+        rolls: check.rolls,
+        min_fill_ml: Some(min_fill_ml(&pool).await?),
+    };
+    chemistry::validate(&request)
+}
@@ -0,0 +1,87 @@
+use crate::collection::{self, Collection, NewCollection};
+use crate::db;
+
+#[tauri::command]
+pub async fn list_collections(app: tauri::AppHandle) -> Result<Vec<Collection>, String> {
+    let pool = db::pool(&app).await?;
+    collection::load_all_collections(&pool).await
+}
+
+#[tauri::command]
+pub async fn create_collection(
+    app: tauri::AppHandle,
+    collection: NewCollection,
+) -> Result<Collection, String> {
+    let pool = db::pool(&app).await?;
+    collection::validate(&collection.name)?;
+
+    let id = db::new_id();
+    let now = db::now_iso();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    sqlx::query(
+        "INSERT INTO collections (id, name, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(collection.name.trim())
+    .bind(&collection.description)
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to insert collection: {}", e))?;
+    collection::replace_members(&mut tx, &id, &collection.recipe_ids).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save collection: {}", e))?;
+
+    collection::load_collection(&pool, &id).await
+}
+
+/// Save a collection's name and description and replace its recipes.
+#[tauri::command]
+pub async fn update_collection(
+    app: tauri::AppHandle,
+    collection: Collection,
+) -> Result<Collection, String> {
+    let pool = db::pool(&app).await?;
+    collection::validate(&collection.name)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let result = sqlx::query(
+        "UPDATE collections SET name = ?, description = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(collection.name.trim())
+    .bind(&collection.description)
+    .bind(db::now_iso())
+    .bind(&collection.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update collection: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Collection {} not found", collection.id));
+    }
+    collection::replace_members(&mut tx, &collection.id, &collection.recipe_ids).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save collection: {}", e))?;
+
+    collection::load_collection(&pool, &collection.id).await
+}
+
+#[tauri::command]
+pub async fn delete_collection(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query("DELETE FROM collections WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete collection: {}", e))?;
+    Ok(())
+}
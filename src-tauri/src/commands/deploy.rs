@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::ago_format::AgoProgramFile;
+use crate::collection;
+use crate::commands::device_session::DeviceSession;
+use crate::commands::{export, wifi};
+use crate::db;
+use crate::deploy::{self, DesiredProgram, InstalledProgram, SyncAction, SyncPlan};
+use crate::device::{self, Device};
+use crate::recipe;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStatus {
+    /// Dry run: the plan was worked out but nothing was written.
+    Planned,
+    /// Every change was applied, or none were needed.
+    Synced,
+    /// Some changes failed; see the log.
+    Partial,
+    /// The device could not be reached or read.
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDeployReport {
+    pub device_id: String,
+    pub device_name: String,
+    pub status: DeployStatus,
+    pub plan: SyncPlan,
+    /// One line per change applied or failed.
+    pub log: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployReport {
+    pub collection_id: String,
+    pub collection_name: String,
+    pub dry_run: bool,
+    pub devices: Vec<DeviceDeployReport>,
+    /// Network rejoined at the end after switching to the devices' networks.
+    pub restored_network: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Push a collection to each device in turn so every unit carries one copy of
+/// each program. Devices that do not answer on their own network are joined
+/// over Wi-Fi on `interface`, detected when not given, and the network in use
+/// before is rejoined at the end. With
+/// `dry_run` the devices are still read so the plan shows what would change.
+/// `prune` also removes custom programs that are not in the collection.
+#[tauri::command]
+pub async fn deploy_collection(
+    app: tauri::AppHandle,
+    collection_id: String,
+    device_ids: Vec<String>,
+    interface: Option<String>,
+    dry_run: Option<bool>,
+    prune: Option<bool>,
+) -> Result<DeployReport, String> {
+    if device_ids.is_empty() {
+        return Err("Choose at least one device".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    let prune = prune.unwrap_or(false);
+
+    let pool = db::pool(&app).await?;
+    let collection = collection::load_collection(&pool, &collection_id).await?;
+    if collection.recipe_ids.is_empty() {
+        return Err(format!("{} has no recipes", collection.name));
+    }
+    let mut desired = Vec::with_capacity(collection.recipe_ids.len());
+    for id in &collection.recipe_ids {
+        desired.push(desired_program(&pool, id).await?);
+    }
+    deploy::check_labels(&desired)?;
+
+    let mut devices = Vec::with_capacity(device_ids.len());
+    for id in &device_ids {
+        devices.push(device::load_device(&pool, id).await?);
+    }
+
+    let interface = interface
+        .filter(|i| !i.trim().is_empty())
+        .or_else(|| wifi::wifi_interface().ok());
+    let mut session = DeviceSession::begin(&app, interface);
+    let mut reports = Vec::with_capacity(devices.len());
+    for device in &devices {
+        let report = match session.join(device).await {
+            Ok(()) => sync_device(&pool, device, &desired, dry_run, prune).await,
+            Err(e) => failed(device, e),
+        };
+        reports.push(report);
+    }
+
+    let restore = session.finish();
+    let restored_network = restore.original_network.filter(|_| restore.restored);
+    let warnings = restore.error.into_iter().collect();
+
+    Ok(DeployReport {
+        collection_id: collection.id,
+        collection_name: collection.name,
+        dry_run,
+        devices: reports,
+        restored_network,
+        warnings,
+    })
+}
+
+/// The device program a recipe converts to.
+pub(crate) async fn desired_program(
+    pool: &Pool<Sqlite>,
+    recipe_id: &str,
+) -> Result<DesiredProgram, String> {
+    let recipe = recipe::load_recipe(pool, recipe_id).await?;
+    let program = AgoProgramFile::from_recipe(&recipe)
+        .to_device_program(
+            &recipe.name,
+            &recipe.film_stock,
+            &recipe.developer,
+            &recipe.dilution,
+        )
+        .map_err(|e| format!("{}: {}", recipe.name, e))?;
+    Ok(DesiredProgram {
+        recipe_id: recipe.id,
+        recipe_name: recipe.name,
+        program,
+    })
+}
+
+fn failed(device: &Device, error: String) -> DeviceDeployReport {
+    DeviceDeployReport {
+        device_id: device.id.clone(),
+        device_name: device.name.clone(),
+        status: DeployStatus::Failed,
+        plan: SyncPlan::default(),
+        log: Vec::new(),
+        error: Some(error),
+    }
+}
+
+async fn sync_device(
+    pool: &Pool<Sqlite>,
+    device: &Device,
+    desired: &[DesiredProgram],
+    dry_run: bool,
+    prune: bool,
+) -> DeviceDeployReport {
+    // A unit with no custom programs yet reports nothing to discover.
+    let files = match export::read_program_files(device).await {
+        Ok(files) => files,
+        Err(_) if wifi::is_reachable(device).await.unwrap_or(false) => Vec::new(),
+        Err(e) => return failed(device, e),
+    };
+    let installed = files
+        .into_iter()
+        .map(|(filename, body)| InstalledProgram {
+            program: serde_json::from_str(&body).ok(),
+            filename,
+        })
+        .collect::<Vec<_>>();
+    let plan = match deploy::plan(desired, &installed, prune) {
+        Ok(plan) => plan,
+        Err(e) => return failed(device, e),
+    };
+
+    let mut report = DeviceDeployReport {
+        device_id: device.id.clone(),
+        device_name: device.name.clone(),
+        status: if dry_run {
+            DeployStatus::Planned
+        } else {
+            DeployStatus::Synced
+        },
+        plan,
+        log: Vec::new(),
+        error: None,
+    };
+    if dry_run {
+        return report;
+    }
+
+    let mut failures = 0;
+    for change in &report.plan.changes {
+        let result = match change.action {
+            SyncAction::Keep => continue,
+            SyncAction::Upload | SyncAction::Replace => {
+                let Some(want) = desired
+                    .iter()
+                    .find(|d| change.recipe_id.as_deref() == Some(d.recipe_id.as_str()))
+                else {
+                    continue;
+                };
+                upload(pool, device, want, &change.filenames).await
+            }
+            SyncAction::Remove => remove(pool, device, &change.filenames).await,
+        };
+        match result {
+            Ok(line) => report.log.push(line),
+            Err(e) => {
+                failures += 1;
+                report.log.push(format!("{}: {}", change.label, e));
+            }
+        }
+    }
+    if failures > 0 {
+        report.status = DeployStatus::Partial;
+    }
+    report
+}
+
+/// Upload a program, then delete the files it replaces.
+async fn upload(
+    pool: &Pool<Sqlite>,
+    device: &Device,
+    want: &DesiredProgram,
+    replaces: &[String],
+) -> Result<String, String> {
+    let result = export::upload_program(pool, device, want).await?;
+    if replaces.is_empty() {
+        return Ok(result.message);
+    }
+    remove(pool, device, replaces).await?;
+    Ok(format!(
+        "{}, replacing {}",
+        result.message,
+        replaces.join(", ")
+    ))
+}
+
+async fn remove(
+    pool: &Pool<Sqlite>,
+    device: &Device,
+    filenames: &[String],
+) -> Result<String, String> {
+    for filename in filenames {
+        export::delete_program(pool, device, filename).await?;
+    }
+    Ok(format!(
+        "Deleted {} from {}",
+        filenames.join(", "),
+        device.name
+    ))
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::export::{self, AgoProgram};
+use crate::commands::{deploy, wifi};
+use crate::db;
+use crate::device::{self, Device};
+use crate::supervisor::{self, Hold};
+
+/// How long to wait for a device to answer after joining its network.
+const JOIN_ATTEMPTS: u32 = 10;
+const JOIN_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// One step run on the device while its network is joined.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionOperation {
+    Upload { recipe_id: String },
+    List,
+    Delete { filename: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationResult {
+    pub operation: SessionOperation,
+    pub ok: bool,
+    pub message: String,
+    /// The device's programs, for `list`.
+    pub programs: Option<Vec<AgoProgram>>,
+}
+
+/// How the session left the Wi-Fi network.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// Network in use when the session began, if any.
+    pub original_network: Option<String>,
+    /// Whether the session joined another network to reach a device.
+    pub switched: bool,
+    pub restored: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub device_id: String,
+    pub device_name: String,
+    /// The device answered, directly or after joining its network.
+    pub joined: bool,
+    pub join_error: Option<String>,
+    /// Empty when the device could not be reached.
+    pub operations: Vec<OperationResult>,
+    pub restore: RestoreReport,
+}
+
+/// Joins device networks as needed and goes back to the network in use when
+/// it began, whether the work succeeded or not. Restoring happens in
+/// `finish`, or on drop if the session ends early.
+pub(crate) struct DeviceSession {
+    app: AppHandle,
+    interface: Option<String>,
+    original_network: Option<String>,
+    switched: bool,
+    finished: bool,
+    _hold: Hold,
+}
+
+impl DeviceSession {
+    pub fn begin(app: &AppHandle, interface: Option<String>) -> Self {
+        let interface = interface.filter(|i| !i.trim().is_empty());
+        let original_network = interface
+            .as_deref()
+            .and_then(|i| wifi::current_network(i).ok())
+            .filter(|ssid| !ssid.is_empty());
+        DeviceSession {
+            app: app.clone(),
+            interface,
+            original_network,
+            switched: false,
+            finished: false,
+            _hold: supervisor::hold(app),
+        }
+    }
+
+    /// Make sure the device answers on its own network, joining it when it
+    /// does not.
+    pub async fn join(&mut self, device: &Device) -> Result<(), String> {
+        let current_network = self
+            .interface
+            .as_deref()
+            .map(|i| wifi::current_network(i).unwrap_or_default());
+        if on_device_network(device, current_network.as_deref())
+            && wifi::is_reachable(device).await?
+        {
+            return Ok(());
+        }
+        let Some(interface) = self.interface.as_deref() else {
+            return Err(format!(
+                "{} is not reachable at {} and no Wi-Fi interface was given",
+                device.name, device.ip
+            ));
+        };
+
+        let password = device.wifi_password()?.unwrap_or_default();
+        self.switched = true;
+        wifi::connect(interface, &device.ssid, &password)?;
+        for _ in 0..JOIN_ATTEMPTS {
+            tokio::time::sleep(JOIN_INTERVAL).await;
+            if wifi::is_reachable(device).await? {
+                return Ok(());
+            }
+        }
+        Err(format!(
+            "Joined {} but {} did not answer at {}",
+            device.ssid, device.name, device.ip
+        ))
+    }
+
+    pub fn finish(mut self) -> RestoreReport {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> RestoreReport {
+        self.finished = true;
+        let mut report = RestoreReport {
+            original_network: self.original_network.clone(),
+            switched: self.switched,
+            ..Default::default()
+        };
+        let (true, Some(interface), Some(ssid)) = (
+            self.switched,
+            self.interface.as_deref(),
+            self.original_network.as_deref(),
+        ) else {
+            return report;
+        };
+        match wifi::reconnect(interface, ssid) {
+            Ok(()) => {
+                report.restored = true;
+                supervisor::mark_restored(&self.app, ssid);
+            }
+            Err(e) => report.error = Some(format!("Could not rejoin {}: {}", ssid, e)),
+        }
+        report
+    }
+}
+
+/// Whether an answer at the device's address can be taken as this device.
+/// Units running their own access point all answer at the same address, so
+/// when the device has an SSID the interface must be joined to it. With no
+/// interface the network cannot be read and the address has to do.
+fn on_device_network(device: &Device, current_network: Option<&str>) -> bool {
+    let ssid = device.ssid.trim();
+    ssid.is_empty() || !matches!(current_network, Some(current) if current != ssid)
+}
+
+impl Drop for DeviceSession {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Some(error) = self.restore().error {
+            log::error!("{}", error);
+        }
+    }
+}
+
+/// Join a device, run `operations` in order and always rejoin the network in
+/// use before, even when joining or an operation fails. The interface is
+/// detected when not given.
+#[tauri::command]
+pub async fn run_device_session(
+    app: tauri::AppHandle,
+    device_id: String,
+    interface: Option<String>,
+    operations: Vec<SessionOperation>,
+) -> Result<SessionReport, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let interface = interface
+        .filter(|i| !i.trim().is_empty())
+        .or_else(|| wifi::wifi_interface().ok());
+
+    let mut session = DeviceSession::begin(&app, interface);
+    let (joined, join_error) = match session.join(&device).await {
+        Ok(()) => (true, None),
+        Err(e) => (false, Some(e)),
+    };
+    let mut results = Vec::new();
+    if joined {
+        for operation in operations {
+            results.push(run_operation(&pool, &device, operation).await);
+        }
+    }
+
+    Ok(SessionReport {
+        device_id: device.id,
+        device_name: device.name,
+        joined,
+        join_error,
+        operations: results,
+        restore: session.finish(),
+    })
+}
+
+async fn run_operation(
+    pool: &Pool<Sqlite>,
+    device: &Device,
+    operation: SessionOperation,
+) -> OperationResult {
+    let mut programs = None;
+    let result = match &operation {
+        SessionOperation::Upload { recipe_id } => upload(pool, device, recipe_id).await,
+        SessionOperation::List => export::list_programs(pool, device).await.map(|list| {
+            let message = format!("{} custom programs on {}", list.len(), device.name);
+            programs = Some(list);
+            message
+        }),
+        SessionOperation::Delete { filename } => {
+            export::delete_program(pool, device, filename).await
+        }
+    };
+    let (ok, message) = match result {
+        Ok(message) => (true, message),
+        Err(e) => (false, e),
+    };
+    OperationResult {
+        operation,
+        ok,
+        message,
+        programs,
+    }
+}
+
+async fn upload(pool: &Pool<Sqlite>, device: &Device, recipe_id: &str) -> Result<String, String> {
+    let want = deploy::desired_program(pool, recipe_id).await?;
+    export::upload_program(pool, device, &want)
+        .await
+        .map(|result| result.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_counts_on_the_device_network() {
+        let device = Device {
+            ip: "10.10.10.1".to_string(),
+            ssid: "AGO-3F2A".to_string(),
+            ..Default::default()
+        };
+        assert!(on_device_network(&device, Some("AGO-3F2A")));
+        assert!(!on_device_network(&device, Some("Studio")));
+        assert!(!on_device_network(&device, Some("")));
+        assert!(on_device_network(&device, None));
+
+        let station = Device {
+            ip: "192.168.1.40".to_string(),
+            ..Default::default()
+        };
+        assert!(on_device_network(&station, Some("Studio")));
+    }
+
+    #[test]
+    fn units_sharing_an_address_are_told_apart_by_ssid() {
+        let unit = |ssid: &str| Device {
+            ip: "10.10.10.1".to_string(),
+            ssid: ssid.to_string(),
+            ..Default::default()
+        };
+        let (first, second) = (unit("AGO-3F2A"), unit("AGO-91C0"));
+        // Still on the first unit's network after deploying to it.
+        assert!(on_device_network(&first, Some("AGO-3F2A")));
+        assert!(!on_device_network(&second, Some("AGO-3F2A")));
+        assert!(on_device_network(&second, Some("AGO-91C0")));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::db;
+use crate::device::{self, Device, NewDevice};
+use crate::secrets;
+
+fn save_password(device: &Device, password: Option<String>) -> Result<(), String> {
+    match password {
+        Some(password) if !password.is_empty() => {
+            secrets::default_store()?.set(&device.credential_account(), &password)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub async fn list_devices(app: tauri::AppHandle) -> Result<Vec<Device>, String> {
+    let pool = db::pool(&app).await?;
+    device::load_all_devices(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_device(app: tauri::AppHandle, id: String) -> Result<Device, String> {
+    let pool = db::pool(&app).await?;
+    device::load_device(&pool, &id).await
+}
+
+/// Add a device; `password` goes to the secret store, not the database.
+#[tauri::command]
+pub async fn create_device(
+    app: tauri::AppHandle,
+    device: NewDevice,
+    password: Option<String>,
+) -> Result<Device, String> {
+    let pool = db::pool(&app).await?;
+    device::validate(&device.name, &device.ip)?;
+
+    let now = db::now_iso();
+    let ssid = device.ssid.trim().to_string();
+    let entry = Device {
+        id: db::new_id(),
+        name: device.name.trim().to_string(),
+        ip: device.ip.trim().to_string(),
+        credential_ref: match device.credential_ref.trim() {
+            "" => secrets::wifi_account(&ssid),
+            other => other.to_string(),
+        },
+        ssid,
+        firmware_notes: device.firmware_notes,
+        upload_endpoint: device.upload_endpoint.trim().to_string(),
+        upload_field: device.upload_field.trim().to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+
+    sqlx::query(&format!(
+        "INSERT INTO devices ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        device::DEVICE_COLUMNS
+    ))
+    .bind(&entry.id)
+    .bind(&entry.name)
+    .bind(&entry.ip)
+    .bind(&entry.ssid)
+    .bind(&entry.credential_ref)
+    .bind(&entry.firmware_notes)
+    .bind(&entry.upload_endpoint)
+    .bind(&entry.upload_field)
+    .bind(&entry.created_at)
+    .bind(&entry.updated_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to insert device: {}", e))?;
+
+    save_password(&entry, password)?;
+    Ok(entry)
+}
+
+/// Save a device's fields, and its password when one is given.
+#[tauri::command]
+pub async fn update_device(
+    app: tauri::AppHandle,
+    device: Device,
+    password: Option<String>,
+) -> Result<Device, String> {
+    let pool = db::pool(&app).await?;
+    device::validate(&device.name, &device.ip)?;
+
+    let result = sqlx::query(
+        "UPDATE devices SET name = ?, ip = ?, ssid = ?, credential_ref = ?, firmware_notes = ?, upload_endpoint = ?, upload_field = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(device.name.trim())
+    .bind(device.ip.trim())
+    .bind(device.ssid.trim())
+    .bind(device.credential_ref.trim())
+    .bind(&device.firmware_notes)
+    .bind(device.upload_endpoint.trim())
+    .bind(device.upload_field.trim())
+    .bind(db::now_iso())
+    .bind(&device.id)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to update device: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Device {} not found", device.id));
+    }
+
+    let device = device::load_device(&pool, &device.id).await?;
+    save_password(&device, password)?;
+    Ok(device)
+}
+
+/// Remove a device. Its stored password is removed too unless another device
+/// shares the same credential.
+#[tauri::command]
+pub async fn delete_device(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &id).await?;
+
+    sqlx::query("DELETE FROM devices WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete device: {}", e))?;
+
+    let account = device.credential_account();
+    let shared = device::load_all_devices(&pool)
+        .await?
+        .iter()
+        .any(|d| d.credential_account() == account);
+    if !shared {
+        secrets::default_store()?.delete(&account)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::commands::wifi;
+use crate::db;
+use crate::device;
+use crate::discovery::{self, DiscoveredDevice, DiscoverySource};
+
+/// Hosts probed at once during a subnet scan.
+const SCAN_CONCURRENCY: usize = 64;
+const MDNS_WAIT: Duration = Duration::from_secs(3);
+
+async fn probe_host(
+    client: reqwest::Client,
+    ip: Ipv4Addr,
+    source: DiscoverySource,
+    hostname: Option<String>,
+) -> Option<DiscoveredDevice> {
+    let result = wifi::run_probe(&client, &format!("http://{}", ip)).await;
+    if !result.programs_api {
+        return None;
+    }
+    Some(DiscoveredDevice {
+        ip: ip.to_string(),
+        latency_ms: result.latency_ms.unwrap_or_default(),
+        source,
+        hostname,
+        program_count: result.program_count.unwrap_or_default(),
+        firmware_hints: result.firmware_hints,
+        device_id: None,
+    })
+}
+
+/// Look for AGO units on the local network by listening for mDNS and by
+/// scanning each local subnet, and keep the addresses whose custom programs
+/// endpoint answers like an AGO. Both methods are on unless turned off.
+#[tauri::command]
+pub async fn discover_devices(
+    app: tauri::AppHandle,
+    scan: Option<bool>,
+    mdns: Option<bool>,
+) -> Result<Vec<DiscoveredDevice>, String> {
+    let mut candidates: Vec<(Ipv4Addr, DiscoverySource, Option<String>)> = Vec::new();
+
+    if mdns.unwrap_or(true) {
+        let found = tokio::task::spawn_blocking(|| discovery::mdns_candidates(MDNS_WAIT))
+            .await
+            .map_err(|e| format!("mDNS browse failed: {}", e))?;
+        // mDNS is a bonus; a blocked multicast socket should not stop the scan.
+        for (ip, hostname) in found.unwrap_or_default() {
+            candidates.push((ip, DiscoverySource::Mdns, Some(hostname)));
+        }
+    }
+    if scan.unwrap_or(true) {
+        for ip in discovery::scan_targets(&discovery::local_networks()?) {
+            if !candidates.iter().any(|(seen, _, _)| *seen == ip) {
+                candidates.push((ip, DiscoverySource::Scan, None));
+            }
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_millis(700))
+        .timeout(Duration::from_secs(3))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let permits = Arc::new(Semaphore::new(SCAN_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (ip, source, hostname) in candidates {
+        let client = client.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.ok()?;
+            probe_host(client, ip, source, hostname).await
+        });
+    }
+
+    let mut found = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        if let Ok(Some(device)) = result {
+            found.push(device);
+        }
+    }
+
+    let pool = db::pool(&app).await?;
+    let known = device::load_all_devices(&pool)
+        .await?
+        .into_iter()
+        .map(|d| (d.ip.trim().to_string(), d.id))
+        .collect::<HashMap<_, _>>();
+    for device in &mut found {
+        device.device_id = known.get(&device.ip).cloned();
+    }
+    found.sort_by_key(|d| (d.ip.parse::<Ipv4Addr>().ok(), d.latency_ms));
+    Ok(found)
+}
@@ -1,13 +1,27 @@
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Map, Value};
 use std::collections::BTreeSet;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::Path;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri_plugin_dialog::DialogExt;
 
+use crate::ago_format::{self, AgoDeviceProgram, AgoProgramFile};
+use crate::catalog::{self, Catalog, TitleParse};
+use crate::db;
+use crate::deploy::DesiredProgram;
+use crate::device::{self, Device};
+use crate::external_import::{self, ExternalFormat, ExternalImportReport, ImportedRecipe};
+use crate::folder_import::{self, FileImportResult, FolderImportReport, ImportStatus};
+use crate::probe::{self, WriteMethod};
+use crate::recipe::{self, Recipe};
+use crate::recipe_card::{self, CardChemistry};
+use crate::recipe_export::{self, ExportFormat};
+use crate::share_code::{self, ShareCode};
+use crate::supervisor;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UploadResult {
     pub message: String,
@@ -19,6 +33,9 @@ pub struct AgoProgram {
     pub filename: String,
     pub name: String,
     pub expanded_title: String,
+    /// Film, developer and dilution read from the name and title.
+    #[serde(default)]
+    pub title: Option<TitleParse>,
 }
 
 const UPLOAD_DEBUG_LOG_PATH: &str = "/tmp/ago-recipe-manager-upload-debug.log";
@@ -52,131 +69,6 @@ fn normalize_url(ip: &str, endpoint: &str) -> String {
     }
 }
 
-fn to_string_field(value: &Value, key: &str) -> String {
-    value
-        .get(key)
-        .and_then(Value::as_str)
-        .unwrap_or_default()
-        .trim()
-        .to_string()
-}
-
-fn to_i64_field(value: &Value, key: &str) -> i64 {
-    value
-        .get(key)
-        .and_then(Value::as_i64)
-        .unwrap_or(0)
-}
-
-fn to_f64_field(value: &Value, key: &str) -> f64 {
-    value
-        .get(key)
-        .and_then(Value::as_f64)
-        .unwrap_or(0.0)
-}
-
-fn sanitize_name_from_filename(filename: &str) -> String {
-    let stem = filename.strip_suffix(".json").unwrap_or(filename);
-    let cleaned = stem
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
-                c
-            } else {
-                '_'
-            }
-        })
-        .collect::<String>()
-        .trim_matches('_')
-        .replace('_', " ");
-
-    if cleaned.is_empty() {
-        "Custom Program".to_string()
-    } else {
-        cleaned
-    }
-}
-
-fn is_custom_program_filename(candidate: &str) -> bool {
-    let name = candidate
-        .trim()
-        .trim_matches('"')
-        .trim_matches('\'')
-        .rsplit('/')
-        .next()
-        .unwrap_or_default();
-    name.starts_with("_P_C") && name.ends_with(".txt")
-}
-
-fn normalize_custom_program_filename(candidate: &str) -> Option<String> {
-    let name = candidate
-        .trim()
-        .trim_matches('"')
-        .trim_matches('\'')
-        .rsplit('/')
-        .next()
-        .unwrap_or_default()
-        .to_string();
-    if is_custom_program_filename(&name) {
-        Some(name)
-    } else {
-        None
-    }
-}
-
-fn collect_custom_filenames_from_json(value: &Value, out: &mut BTreeSet<String>) {
-    match value {
-        Value::String(s) => {
-            if let Some(name) = normalize_custom_program_filename(s) {
-                out.insert(name);
-            }
-        }
-        Value::Array(items) => {
-            for item in items {
-                collect_custom_filenames_from_json(item, out);
-            }
-        }
-        Value::Object(map) => {
-            for (key, inner) in map {
-                if let Some(name) = normalize_custom_program_filename(key) {
-                    out.insert(name);
-                }
-                collect_custom_filenames_from_json(inner, out);
-            }
-        }
-        _ => {}
-    }
-}
-
-fn collect_custom_filenames_from_text(text: &str) -> BTreeSet<String> {
-    let mut out = BTreeSet::new();
-    let bytes = text.as_bytes();
-    let mut i = 0;
-
-    while i + 4 <= bytes.len() {
-        if &bytes[i..i + 4] == b"_P_C" {
-            let mut j = i + 4;
-            while j < bytes.len() {
-                let c = bytes[j] as char;
-                if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
-                    j += 1;
-                } else {
-                    break;
-                }
-            }
-            let candidate = &text[i..j];
-            if let Some(name) = normalize_custom_program_filename(candidate) {
-                out.insert(name);
-            }
-            i = j;
-            continue;
-        }
-        i += 1;
-    }
-
-    out
-}
-
 fn short_upload_token() -> String {
     let nanos = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -206,138 +98,183 @@ fn build_custom_program_payload(
     film_stock: &str,
     developer: &str,
     dilution: &str,
-) -> Result<Value, String> {
-    let parsed: Value =
-        serde_json::from_str(json_content).map_err(|e| format!("Invalid recipe JSON: {}", e))?;
-
-    let source_steps = parsed
-        .get("steps")
-        .and_then(Value::as_array)
-        .ok_or_else(|| "Recipe JSON missing steps array".to_string())?;
-
-    let category = {
-        let raw = to_string_field(&parsed, "category");
-        if raw.is_empty() {
-            "BW".to_string()
-        } else {
-            raw
-        }
-    };
+) -> Result<AgoDeviceProgram, String> {
+    ago_format::parse_lenient(json_content)?.to_device_program(
+        filename,
+        film_stock,
+        developer,
+        dilution,
+    )
+}
 
-    let name = if film_stock.trim().is_empty() {
-        sanitize_name_from_filename(filename)
-    } else {
-        film_stock.trim().to_string()
-    };
+async fn response_text(resp: reqwest::Response) -> String {
+    resp.text().await.unwrap_or_default()
+}
 
-    let expanded_title = if developer.trim().is_empty() && dilution.trim().is_empty() {
-        let existing = to_string_field(&parsed, "expanded_title");
-        if existing.is_empty() {
-            String::new()
-        } else if existing.starts_with(" -") || existing.starts_with('-') {
-            existing
-        } else {
-            format!(" - {}", existing)
-        }
+/// Serialize a recipe as an AGO program file, the format "Export" writes.
+#[tauri::command]
+pub async fn recipe_to_ago_json(recipe: Recipe) -> Result<String, String> {
+    AgoProgramFile::from_recipe(&recipe).to_json()
+}
+
+/// Parse an AGO program file into an unsaved recipe. `strict` rejects anything
+/// the exporter would not have written.
+#[tauri::command]
+pub async fn parse_ago_json(
+    app: tauri::AppHandle,
+    content: String,
+    strict: Option<bool>,
+) -> Result<Recipe, String> {
+    let program = if strict.unwrap_or(false) {
+        ago_format::parse_strict(&content)?
     } else {
-        let mut parts = Vec::new();
-        if !developer.trim().is_empty() {
-            parts.push(developer.trim().to_string());
-        }
-        if !dilution.trim().is_empty() {
-            parts.push(dilution.trim().to_string());
-        }
-        format!(" - {}", parts.join(" "))
+        ago_format::parse_lenient(&content)?
     };
+    if program.steps.is_empty() {
+        return Err("Recipe JSON missing steps array".to_string());
+    }
 
-    let mut steps = Vec::with_capacity(source_steps.len());
-    for step in source_steps {
-        let time = if step.get("time").is_some() {
-            to_i64_field(step, "time")
-        } else {
-            (to_i64_field(step, "time_min") * 60) + to_i64_field(step, "time_sec")
-        };
+    let pool = db::pool(&app).await?;
+    let catalog = Catalog::load(&pool).await?;
+    let title = catalog.parse_title(&catalog::program_title_text(
+        &program.name,
+        &program.expanded_title,
+    ));
+    Ok(program.to_recipe(title.best.parts()))
+}
 
-        let mut out = Map::new();
-        out.insert("name".to_string(), json!(to_string_field(step, "name")));
-        out.insert("time".to_string(), json!(time.max(0)));
-
-        let agitation = to_string_field(step, "agitation");
-        out.insert(
-            "agitation".to_string(),
-            json!(if agitation.is_empty() { "Roll" } else { &agitation }),
-        );
-
-        let compensation = to_string_field(step, "compensation");
-        out.insert(
-            "compensation".to_string(),
-            json!(if compensation.is_empty() {
-                "Off"
-            } else {
-                &compensation
-            }),
-        );
-
-        let formula = to_string_field(step, "formula_designator");
-        if !formula.is_empty() {
-            out.insert("formula_designator".to_string(), json!(formula));
-        }
+/// Save a recipe as an AGO program file (the default) or, with `format`, as
+/// Markdown, CSV with one row per step, or a plain-text summary.
+#[tauri::command]
+pub async fn export_recipe_file(
+    app: tauri::AppHandle,
+    recipe: Recipe,
+    default_name: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let format = ExportFormat::from_name(format.as_deref().unwrap_or_default())?;
+    let content = recipe_export::render(&recipe, format)?;
 
-        if compensation != "Off" {
-            let min_temp = if step.get("min_temperature").is_some() {
-                to_f64_field(step, "min_temperature")
-            } else {
-                18.0
-            };
-            let max_temp = if step.get("max_temperature").is_some() {
-                to_f64_field(step, "max_temperature")
-            } else {
-                24.0
-            };
-            out.insert("min_temperature".to_string(), json!(min_temp));
-            out.insert("max_temperature".to_string(), json!(max_temp));
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(format.file_name(&default_name))
+        .add_filter(format.filter_name(), &[format.extension()])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_str = path.to_string();
+            fs::write(&path_str, &content).map_err(|e| format!("Failed to write file: {}", e))?;
+            Ok(path_str)
         }
+        None => Err("Export cancelled".to_string()),
+    }
+}
 
-        steps.push(Value::Object(out));
+/// Render recipe cards to a PDF: one card for a single recipe, a booklet with a
+/// contents page for several. `chemistry` adds developer amounts for that tank.
+#[tauri::command]
+pub async fn export_recipe_pdf(
+    app: tauri::AppHandle,
+    recipe_ids: Vec<String>,
+    mut chemistry: Option<CardChemistry>,
+    default_name: Option<String>,
+) -> Result<String, String> {
+    if recipe_ids.is_empty() {
+        return Err("Select at least one recipe to export".to_string());
+    }
+    let pool = db::pool(&app).await?;
+    if let Some(tank) = chemistry.as_mut() {
+        tank.min_fill_ml = Some(crate::commands::chemistry::min_fill_ml(&pool).await?);
     }
+    let mut recipes = Vec::with_capacity(recipe_ids.len());
+    for id in &recipe_ids {
+        recipes.push(recipe::load_recipe(&pool, id).await?);
+    }
+
+    let default_name = default_name.unwrap_or_else(|| match recipes.as_slice() {
+        [recipe] => share_code::export_file_name(&recipe.name, "pdf"),
+        _ => "recipes.pdf".to_string(),
+    });
+    let generated = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let bytes = recipe_card::render(&recipes, chemistry.as_ref(), &generated);
 
-    Ok(json!({
-        "name": name,
-        "designator": "C2",
-        "category": category,
-        "expanded_title": expanded_title,
-        "steps": steps,
-    }))
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(&default_name)
+        .add_filter("PDF", &["pdf"])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_str = path.to_string();
+            fs::write(&path_str, &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+            Ok(path_str)
+        }
+        None => Err("Export cancelled".to_string()),
+    }
 }
 
-async fn response_text(resp: reqwest::Response) -> String {
-    resp.text().await.unwrap_or_default()
+/// Pack a recipe into a share code and render it as a QR code for showing on screen.
+#[tauri::command]
+pub async fn recipe_share_code(recipe: Recipe) -> Result<ShareCode, String> {
+    let code = share_code::encode(&recipe);
+    let svg = share_code::qr_svg(&code)?;
+    Ok(ShareCode { code, svg })
 }
 
+/// Save a recipe's QR code as a PNG (the default) or, with `format` "svg", as SVG.
 #[tauri::command]
-pub async fn export_recipe_file(
+pub async fn export_recipe_qr(
     app: tauri::AppHandle,
-    json_content: String,
-    default_name: String,
+    recipe: Recipe,
+    format: Option<String>,
+    default_name: Option<String>,
 ) -> Result<String, String> {
+    let code = share_code::encode(&recipe);
+    let (bytes, extension, filter) = match format.as_deref().unwrap_or("png") {
+        "png" => (share_code::qr_png(&code)?, "png", "PNG"),
+        "svg" => (share_code::qr_svg(&code)?.into_bytes(), "svg", "SVG"),
+        other => return Err(format!("Unknown QR format '{}', expected png or svg", other)),
+    };
+    let default_name = default_name.unwrap_or_else(|| recipe.name.clone());
+
     let file_path = app
         .dialog()
         .file()
-        .set_file_name(&default_name)
-        .add_filter("JSON", &["json"])
+        .set_file_name(share_code::export_file_name(&default_name, extension))
+        .add_filter(filter, &[extension])
         .blocking_save_file();
 
     match file_path {
         Some(path) => {
             let path_str = path.to_string();
-            fs::write(&path_str, &json_content)
-                .map_err(|e| format!("Failed to write file: {}", e))?;
+            fs::write(&path_str, &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
             Ok(path_str)
         }
         None => Err("Export cancelled".to_string()),
     }
 }
 
+/// Decode a scanned or pasted share code and save it as a new recipe.
+#[tauri::command]
+pub async fn import_share_code(app: tauri::AppHandle, code: String) -> Result<Recipe, String> {
+    let mut recipe = share_code::decode(&code)?;
+
+    let pool = db::pool(&app).await?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    recipe::insert_recipe(&mut tx, &mut recipe).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipe: {}", e))?;
+    Ok(recipe)
+}
+
 #[tauri::command]
 pub async fn import_recipe_file(app: tauri::AppHandle) -> Result<String, String> {
     let file_path = app
@@ -357,21 +294,184 @@ pub async fn import_recipe_file(app: tauri::AppHandle) -> Result<String, String>
     }
 }
 
+/// Import every program file under a folder, picked with a dialog when `folder`
+/// is not given. Files whose content matches a stored recipe, or an earlier file
+/// in the same folder, are reported as duplicates rather than imported.
+#[tauri::command]
+pub async fn import_recipe_folder(
+    app: tauri::AppHandle,
+    folder: Option<String>,
+) -> Result<FolderImportReport, String> {
+    let folder = match folder.filter(|f| !f.trim().is_empty()) {
+        Some(folder) => folder,
+        None => app
+            .dialog()
+            .file()
+            .blocking_pick_folder()
+            .map(|path| path.to_string())
+            .ok_or_else(|| "Import cancelled".to_string())?,
+    };
+    let root = Path::new(&folder);
+    let paths = folder_import::find_program_files(root)?;
+
+    let pool = db::pool(&app).await?;
+    let catalog = Catalog::load(&pool).await?;
+    let mut known = folder_import::existing_hashes(&recipe::load_all_recipes(&pool).await?);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut files = Vec::new();
+    for path in paths {
+        let display = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+
+        let mut recipe = match folder_import::read_program_file(&path, &catalog) {
+            Ok(recipe) => recipe,
+            Err(e) => {
+                files.push(FileImportResult {
+                    path: display,
+                    status: ImportStatus::Invalid,
+                    recipe_id: None,
+                    recipe_name: None,
+                    message: Some(e),
+                });
+                continue;
+            }
+        };
+
+        let hash = folder_import::recipe_hash(&recipe);
+        if let Some((id, name)) = known.get(&hash) {
+            files.push(FileImportResult {
+                path: display,
+                status: ImportStatus::Duplicate,
+                recipe_id: Some(id.clone()),
+                recipe_name: Some(name.clone()),
+                message: None,
+            });
+            continue;
+        }
+
+        recipe::insert_recipe(&mut tx, &mut recipe).await?;
+        known.insert(hash, (recipe.id.clone(), recipe.name.clone()));
+        files.push(FileImportResult {
+            path: display,
+            status: ImportStatus::Imported,
+            recipe_id: Some(recipe.id),
+            recipe_name: Some(recipe.name),
+            message: None,
+        });
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipes: {}", e))?;
+
+    Ok(FolderImportReport::new(&folder, files))
+}
+
+/// Import recipes from another app's CSV or JSON export, picked with a dialog
+/// when `path` is not given. `format` ("csv" or "json") overrides detection by
+/// extension and content.
+#[tauri::command]
+pub async fn import_external_recipes(
+    app: tauri::AppHandle,
+    path: Option<String>,
+    format: Option<String>,
+) -> Result<ExternalImportReport, String> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => path,
+        None => app
+            .dialog()
+            .file()
+            .add_filter("Recipes", &["csv", "tsv", "json"])
+            .blocking_pick_file()
+            .map(|path| path.to_string())
+            .ok_or_else(|| "Import cancelled".to_string())?,
+    };
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let format = match format {
+        Some(name) => ExternalFormat::from_name(&name)?,
+        None => ExternalFormat::detect(&path, &content),
+    };
+
+    let pool = db::pool(&app).await?;
+    let temps = recipe::temperature_defaults(&pool).await?;
+    let parsed = external_import::parse(format, &content, temps)?;
+    if parsed.recipes.is_empty() && parsed.skipped.is_empty() {
+        return Err("No recipes found in file".to_string());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut imported = Vec::new();
+    for (source, mut recipe) in parsed.recipes {
+        recipe::insert_recipe(&mut tx, &mut recipe).await?;
+        imported.push(ImportedRecipe {
+            source,
+            recipe_id: recipe.id,
+            name: recipe.name,
+        });
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipes: {}", e))?;
+
+    Ok(ExternalImportReport {
+        format,
+        imported,
+        skipped: parsed.skipped,
+        unmapped_fields: parsed.unmapped_fields.into_iter().collect(),
+    })
+}
+
 #[tauri::command]
 pub async fn upload_recipe_file(
-    ip: String,
-    endpoint: String,
-    _field_name: String,
+    app: tauri::AppHandle,
+    device_id: String,
     filename: String,
     json_content: String,
     film_stock: String,
     developer: String,
     dilution: String,
 ) -> Result<UploadResult, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let payload = build_custom_program_payload(
+        &json_content,
+        &filename,
+        &film_stock,
+        &developer,
+        &dilution,
+    )?;
+    let _hold = supervisor::hold(&app);
+    push_program(&pool, &device, &filename, &payload).await
+}
+
+/// Write a custom program to the device under a fresh `_P_C*.txt` name, trying
+/// the programs API by POST and PUT, in the order cached for the device, then
+/// the device's compatibility endpoint. `filename` only labels the attempt in
+/// messages and the debug log.
+pub(crate) async fn push_program(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    device: &Device,
+    filename: &str,
+    payload: &AgoDeviceProgram,
+) -> Result<UploadResult, String> {
+    let ip = device.ip.trim();
+    let endpoint = device.upload_endpoint.as_str();
     let mut debug_lines = vec![
+        format!("device={} ({})", device.name, device.id),
         format!("ip={}", ip),
         format!("endpoint_setting={}", endpoint),
-        format!("field_setting={}", _field_name),
+        format!("field_setting={}", device.upload_field),
         format!("filename={}", filename),
     ];
 
@@ -385,14 +485,7 @@ pub async fn upload_recipe_file(
             msg
         })?;
 
-    let payload = build_custom_program_payload(
-        &json_content,
-        &filename,
-        &film_stock,
-        &developer,
-        &dilution,
-    )?;
-    let payload_text = serde_json::to_string(&payload).map_err(|e| format!("Failed to serialize upload payload: {}", e))?;
+    let payload_text = serde_json::to_string(payload).map_err(|e| format!("Failed to serialize upload payload: {}", e))?;
     let payload_snippet = payload_text
         .chars()
         .take(360)
@@ -400,81 +493,73 @@ pub async fn upload_recipe_file(
     debug_lines.push(format!("custom_payload={}", payload_snippet));
 
     let custom_filename = build_custom_program_filename();
-    let custom_url = format!("http://{}/api/files/programs/custom/{}", ip, custom_filename);
+    let custom_url = device.program_url(&custom_filename);
     debug_lines.push(format!("primary_url={}", custom_url));
 
     let mut attempts = Vec::new();
 
-    let primary_resp = client
-        .post(&custom_url)
-        .header("Accept", "application/json, text/plain, */*")
-        .header("Origin", format!("http://{}", ip))
-        .header("Referer", format!("http://{}/programs", ip))
-        .header("Content-Type", "application/json")
-        .body(payload_text.clone())
-        .send()
-        .await;
-
-    match primary_resp {
-        Ok(resp) => {
-            let status = resp.status();
-            let body = response_text(resp).await;
-            let snippet = body.chars().take(180).collect::<String>();
-            if status.is_success() && !looks_like_html(&body) {
-                let msg = format!(
-                    "Uploaded {} to AGO as {} via API",
-                    filename, custom_filename
-                );
-                debug_lines.push(format!("success={}", msg));
-                debug_lines.push(format!("primary_status={}", status));
-                debug_lines.push(format!("primary_body={}", snippet));
-                append_upload_debug(&debug_lines);
-                return Ok(UploadResult { message: msg, ago_filename: custom_filename.clone() });
-            }
-            attempts.push(format!(
-                "POST {} -> HTTP {} ({})",
-                custom_url, status, snippet
-            ));
-        }
-        Err(e) => {
-            attempts.push(format!("POST {} -> {}", custom_url, e));
-        }
-    }
+    // Try the method that worked last time first; the other stays as a fallback
+    // for firmware variants that write by direct resource path.
+    let order = probe::load_probe(pool, &device.id)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .upload_order();
+    for method in order {
+        let request = match method {
+            WriteMethod::Post => client
+                .post(&custom_url)
+                .header("Accept", "application/json, text/plain, */*")
+                .header("Origin", format!("http://{}", ip))
+                .header("Referer", format!("http://{}/programs", ip)),
+            WriteMethod::Put => client.put(&custom_url),
+        };
+        let resp = request
+            .header("Content-Type", "application/json")
+            .body(payload_text.clone())
+            .send()
+            .await;
 
-    // PUT fallback for firmware variants that write by direct resource path.
-    let put_resp = client
-        .put(&custom_url)
-        .header("Content-Type", "application/json")
-        .body(payload_text.clone())
-        .send()
-        .await;
-
-    match put_resp {
-        Ok(resp) => {
-            let status = resp.status();
-            let body = response_text(resp).await;
-            let snippet = body.chars().take(180).collect::<String>();
-            if status.is_success() && !looks_like_html(&body) {
-                let msg = format!(
-                    "Uploaded {} to AGO as {} via API (PUT)",
-                    filename, custom_filename
-                );
-                debug_lines.push(format!("success={}", msg));
-                debug_lines.push(format!("put_status={}", status));
-                debug_lines.push(format!("put_body={}", snippet));
-                append_upload_debug(&debug_lines);
-                return Ok(UploadResult { message: msg, ago_filename: custom_filename.clone() });
+        match resp {
+            Ok(resp) => {
+                let status = resp.status();
+                let body = response_text(resp).await;
+                let snippet = body.chars().take(180).collect::<String>();
+                if status.is_success() && !looks_like_html(&body) {
+                    let msg = match method {
+                        WriteMethod::Post => format!(
+                            "Uploaded {} to AGO as {} via API",
+                            filename, custom_filename
+                        ),
+                        WriteMethod::Put => format!(
+                            "Uploaded {} to AGO as {} via API (PUT)",
+                            filename, custom_filename
+                        ),
+                    };
+                    let key = method.as_str().to_lowercase();
+                    debug_lines.push(format!("success={}", msg));
+                    debug_lines.push(format!("{}_status={}", key, status));
+                    debug_lines.push(format!("{}_body={}", key, snippet));
+                    append_upload_debug(&debug_lines);
+                    // The upload worked; a failed cache write should not report otherwise.
+                    let _ = probe::record_write_method(pool, &device.id, method).await;
+                    return Ok(UploadResult { message: msg, ago_filename: custom_filename.clone() });
+                }
+                attempts.push(format!(
+                    "{} {} -> HTTP {} ({})",
+                    method.as_str(), custom_url, status, snippet
+                ));
+            }
+            Err(e) => {
+                attempts.push(format!("{} {} -> {}", method.as_str(), custom_url, e));
             }
-            attempts.push(format!("PUT {} -> HTTP {} ({})", custom_url, status, snippet));
-        }
-        Err(e) => {
-            attempts.push(format!("PUT {} -> {}", custom_url, e));
         }
     }
 
     // Optional compatibility fallback if user explicitly overrides endpoint.
-    if !endpoint.trim().is_empty() && endpoint.trim() != "/api/files/programs/custom" {
-        let legacy_url = normalize_url(&ip, &endpoint);
+    if !endpoint.trim().is_empty() && endpoint.trim() != device::CUSTOM_PROGRAMS_PATH {
+        let legacy_url = normalize_url(ip, endpoint);
         let legacy_raw = client
             .post(&legacy_url)
             .header("Content-Type", "application/json")
@@ -511,24 +596,68 @@ pub async fn upload_recipe_file(
 }
 
 #[tauri::command]
-pub async fn delete_ago_program(ip: String, filename: String) -> Result<String, String> {
+pub async fn delete_ago_program(
+    app: tauri::AppHandle,
+    device_id: String,
+    filename: String,
+) -> Result<String, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let _hold = supervisor::hold(&app);
+    delete_program(&pool, &device, &filename).await
+}
+
+/// Upload a recipe's program and add it to the upload history under the
+/// recipe name, or the name the device file gives when the recipe has none.
+pub(crate) async fn upload_program(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    device: &Device,
+    want: &DesiredProgram,
+) -> Result<UploadResult, String> {
+    let result = push_program(pool, device, &want.recipe_name, &want.program).await?;
+    let display_name = if want.recipe_name.trim().is_empty() {
+        ago_format::sanitize_name_from_filename(&result.ago_filename)
+    } else {
+        want.recipe_name.clone()
+    };
+    record_upload(
+        pool,
+        &want.recipe_id,
+        &device.id,
+        &result.ago_filename,
+        &display_name,
+    )
+    .await?;
+    Ok(result)
+}
+
+/// Delete a program from the device and from the upload history.
+pub(crate) async fn delete_program(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    device: &Device,
+    filename: &str,
+) -> Result<String, String> {
+    remove_program(device, filename).await?;
+    forget_upload(pool, &device.id, filename).await?;
+    Ok(format!("Deleted {} from {}", filename, device.name))
+}
+
+pub(crate) async fn remove_program(device: &Device, filename: &str) -> Result<(), String> {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(8))
         .build()
         .map_err(|e| format!("HTTP client error: {}", e))?;
 
-    let url = format!("http://{}/api/files/programs/custom/{}", ip, filename);
-
     let resp = client
-        .delete(&url)
+        .delete(device.program_url(filename))
         .header("Accept", "application/json")
-        .header("Origin", format!("http://{}", ip))
+        .header("Origin", device.base_url())
         .send()
         .await
         .map_err(|e| format!("Failed to reach AGO: {}", e))?;
 
     if resp.status().is_success() {
-        return Ok(format!("Deleted {}", filename));
+        return Ok(());
     }
 
     Err(format!(
@@ -538,8 +667,105 @@ pub async fn delete_ago_program(ip: String, filename: String) -> Result<String,
     ))
 }
 
+/// Add a program written to a device to the upload history.
+pub(crate) async fn record_upload(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    recipe_id: &str,
+    device_id: &str,
+    filename: &str,
+    display_name: &str,
+) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO ago_uploads (id, recipe_id, device_id, filename, display_name, uploaded_at) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(db::new_id())
+    .bind(recipe_id)
+    .bind(device_id)
+    .bind(filename)
+    .bind(display_name)
+    .bind(db::now_iso())
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to record upload: {}", e))?;
+    Ok(())
+}
+
+/// Drop upload history for a file deleted from a device.
+pub(crate) async fn forget_upload(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    device_id: &str,
+    filename: &str,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM ago_uploads WHERE filename = ? AND (device_id = ? OR device_id IS NULL)")
+        .bind(filename)
+        .bind(device_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to update upload history: {}", e))?;
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn list_ago_programs(ip: String) -> Result<Vec<AgoProgram>, String> {
+pub async fn list_ago_programs(
+    app: tauri::AppHandle,
+    device_id: String,
+) -> Result<Vec<AgoProgram>, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let _hold = supervisor::hold(&app);
+    list_programs(&pool, &device).await
+}
+
+/// Custom programs on a device with their names and parsed titles, sorted by name.
+pub(crate) async fn list_programs(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    device: &Device,
+) -> Result<Vec<AgoProgram>, String> {
+    let files = read_program_files(device).await?;
+
+    // Listing still works without the catalog; titles are just not split.
+    let catalog = Catalog::load(pool).await.ok();
+
+    let mut programs = Vec::new();
+    for (filename, body) in files {
+        let parsed = ago_format::parse_lenient(&body).ok();
+        let mut name = parsed.as_ref().map(|p| p.name.clone()).unwrap_or_default();
+        if name.is_empty() {
+            name = ago_format::sanitize_name_from_filename(&filename);
+        }
+        let expanded_title = parsed
+            .map(|p| p.expanded_title.trim().to_string())
+            .unwrap_or_default();
+
+        let title = catalog
+            .as_ref()
+            .map(|c| c.parse_title(&catalog::program_title_text(&name, &expanded_title)));
+
+        programs.push(AgoProgram {
+            filename,
+            name,
+            expanded_title,
+            title,
+        });
+    }
+
+    programs.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.filename.cmp(&b.filename))
+    });
+
+    if programs.is_empty() {
+        return Err("Could not read custom program contents from AGO".to_string());
+    }
+
+    Ok(programs)
+}
+
+/// Filenames and bodies of the custom programs on a device. Files that cannot
+/// be fetched are skipped; finding no files at all is an error.
+pub(crate) async fn read_program_files(device: &Device) -> Result<Vec<(String, String)>, String> {
     let client = reqwest::Client::builder()
         .cookie_store(true)
         .timeout(Duration::from_secs(10))
@@ -548,8 +774,8 @@ pub async fn list_ago_programs(ip: String) -> Result<Vec<AgoProgram>, String> {
 
     let mut filenames = BTreeSet::new();
     let discovery_urls = [
-        format!("http://{}/api/files/programs/custom", ip),
-        format!("http://{}/programs", ip),
+        format!("{}{}", device.base_url(), device::CUSTOM_PROGRAMS_PATH),
+        format!("{}/programs", device.base_url()),
     ];
 
     for url in discovery_urls {
@@ -567,19 +793,16 @@ pub async fn list_ago_programs(ip: String) -> Result<Vec<AgoProgram>, String> {
         }
 
         let body = response_text(resp).await;
-        if let Ok(parsed) = serde_json::from_str::<Value>(&body) {
-            collect_custom_filenames_from_json(&parsed, &mut filenames);
-        }
-        filenames.extend(collect_custom_filenames_from_text(&body));
+        filenames.extend(ago_format::custom_program_filenames(&body));
     }
 
     if filenames.is_empty() {
         return Err("Could not discover custom programs on AGO".to_string());
     }
 
-    let mut programs = Vec::new();
+    let mut files = Vec::new();
     for filename in filenames {
-        let file_url = format!("http://{}/api/files/programs/custom/{}", ip, filename);
+        let file_url = device.program_url(&filename);
         let response = client
             .get(&file_url)
             .header("Accept", "application/json, text/plain, */*")
@@ -593,33 +816,10 @@ pub async fn list_ago_programs(ip: String) -> Result<Vec<AgoProgram>, String> {
             continue;
         }
 
-        let body = response_text(resp).await;
-        let parsed = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
-        let mut name = to_string_field(&parsed, "name");
-        if name.is_empty() {
-            name = sanitize_name_from_filename(&filename);
-        }
-        let expanded_title = to_string_field(&parsed, "expanded_title");
-
-        programs.push(AgoProgram {
-            filename,
-            name,
-            expanded_title,
-        });
-    }
-
-    programs.sort_by(|a, b| {
-        a.name
-            .to_lowercase()
-            .cmp(&b.name.to_lowercase())
-            .then_with(|| a.filename.cmp(&b.filename))
-    });
-
-    if programs.is_empty() {
-        return Err("Could not read custom program contents from AGO".to_string());
+        files.push((filename, response_text(resp).await));
     }
 
-    Ok(programs)
+    Ok(files)
 }
 
 #[tauri::command]
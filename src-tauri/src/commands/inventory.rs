@@ -0,0 +1,444 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use crate::db;
+
+const CHEMICAL_KINDS: [&str; 3] = ["developer", "stop", "fix"];
+
+/// Warn this many days before a bottle's expiry date.
+const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// Warn once a reusable bath has used this share of its rated capacity.
+const CAPACITY_WARNING_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Chemical {
+    pub id: String,
+    pub name: String,
+    /// One of `developer`, `stop` or `fix`.
+    pub kind: String,
+    pub opened_at: Option<String>,
+    pub volume_remaining_ml: f64,
+    /// Volume when the bottle was added; a reusable bath's capacity is rated on this.
+    pub volume_initial_ml: f64,
+    pub one_shot: bool,
+    pub capacity_rolls_per_litre: f64,
+    pub rolls_processed: i64,
+    pub expires_at: Option<String>,
+    pub notes: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewChemical {
+    pub name: String,
+    pub kind: String,
+    #[serde(default)]
+    pub opened_at: Option<String>,
+    pub volume_remaining_ml: f64,
+    #[serde(default = "default_one_shot")]
+    pub one_shot: bool,
+    #[serde(default)]
+    pub capacity_rolls_per_litre: f64,
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_one_shot() -> bool {
+    true
+}
+
+/// How much of a chemical a development session consumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChemicalUsage {
+    pub chemical_id: String,
+    /// Volume taken from the bottle. Required for one-shot chemistry; optional for
+    /// reusable baths, where it records top-up or carry-over loss.
+    #[serde(default)]
+    pub volume_ml: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryWarning {
+    pub chemical_id: String,
+    pub chemical_name: String,
+    /// `low_stock`, `exhausted`, `near_capacity`, `expiring` or `expired`.
+    pub kind: String,
+    pub message: String,
+}
+
+const CHEMICAL_COLUMNS: &str = "id, name, kind, opened_at, volume_remaining_ml, volume_initial_ml, one_shot, capacity_rolls_per_litre, rolls_processed, expires_at, notes, created_at";
+
+fn validate_kind(kind: &str) -> Result<String, String> {
+    let kind = kind.trim().to_lowercase();
+    if CHEMICAL_KINDS.contains(&kind.as_str()) {
+        Ok(kind)
+    } else {
+        Err(format!(
+            "Unknown chemical type '{}', expected one of: {}",
+            kind,
+            CHEMICAL_KINDS.join(", ")
+        ))
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let date = value.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn warning(chemical: &Chemical, kind: &str, message: String) -> InventoryWarning {
+    InventoryWarning {
+        chemical_id: chemical.id.clone(),
+        chemical_name: chemical.name.clone(),
+        kind: kind.to_string(),
+        message,
+    }
+}
+
+/// Rolls a reusable bath is rated for. Usage is counted in `rolls_processed`,
+/// so this stays on the bottle's starting volume rather than what is left.
+fn rated_roll_capacity(chemical: &Chemical) -> f64 {
+    chemical.capacity_rolls_per_litre * chemical.volume_initial_ml / 1000.0
+}
+
+/// Merge usages naming the same bottle twice, adding up their volumes.
+pub(crate) fn merge_usages(usages: &[ChemicalUsage]) -> Vec<ChemicalUsage> {
+    let mut merged: Vec<ChemicalUsage> = Vec::with_capacity(usages.len());
+    for usage in usages {
+        match merged.iter_mut().find(|u| u.chemical_id == usage.chemical_id) {
+            Some(seen) => {
+                seen.volume_ml = match (seen.volume_ml, usage.volume_ml) {
+                    (None, None) => None,
+                    (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+                }
+            }
+            None => merged.push(usage.clone()),
+        }
+    }
+    merged
+}
+
+/// Stock and expiry warnings for one chemical. `last_usage_ml` is the amount the
+/// most recent session used, which is the best guess for what the next one needs.
+pub(crate) fn warnings_for(
+    chemical: &Chemical,
+    last_usage_ml: Option<f64>,
+    today: NaiveDate,
+) -> Vec<InventoryWarning> {
+    let mut warnings = Vec::new();
+
+    if chemical.volume_remaining_ml <= 0.0 {
+        warnings.push(warning(
+            chemical,
+            "exhausted",
+            format!("{} is used up", chemical.name),
+        ));
+    } else if chemical.one_shot {
+        if let Some(needed) = last_usage_ml.filter(|ml| *ml > chemical.volume_remaining_ml) {
+            warnings.push(warning(
+                chemical,
+                "low_stock",
+                format!(
+                    "{} has {:.0} ml left, less than the {:.0} ml the last session used",
+                    chemical.name, chemical.volume_remaining_ml, needed
+                ),
+            ));
+        }
+    } else if chemical.capacity_rolls_per_litre > 0.0 {
+        let capacity = rated_roll_capacity(chemical);
+        let remaining = capacity - chemical.rolls_processed as f64;
+        if remaining <= 0.0 {
+            warnings.push(warning(
+                chemical,
+                "exhausted",
+                format!(
+                    "{} has processed {} rolls and is past its capacity of {:.0}",
+                    chemical.name, chemical.rolls_processed, capacity
+                ),
+            ));
+        } else if chemical.rolls_processed as f64 >= capacity * CAPACITY_WARNING_RATIO {
+            warnings.push(warning(
+                chemical,
+                "near_capacity",
+                format!(
+                    "{} has about {:.0} rolls of capacity left",
+                    chemical.name,
+                    remaining.floor()
+                ),
+            ));
+        }
+    }
+
+    if let Some(expires) = chemical.expires_at.as_deref().and_then(parse_date) {
+        let days_left = (expires - today).num_days();
+        if days_left < 0 {
+            warnings.push(warning(
+                chemical,
+                "expired",
+                format!("{} expired on {}", chemical.name, expires),
+            ));
+        } else if days_left <= EXPIRY_WARNING_DAYS {
+            warnings.push(warning(
+                chemical,
+                "expiring",
+                format!("{} expires in {} days", chemical.name, days_left),
+            ));
+        }
+    }
+
+    warnings
+}
+
+pub(crate) fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+async fn fetch_chemical(conn: &mut SqliteConnection, id: &str) -> Result<Chemical, String> {
+    sqlx::query_as::<_, Chemical>(&format!(
+        "SELECT {} FROM chemicals WHERE id = ?",
+        CHEMICAL_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| format!("Failed to load chemical: {}", e))?
+    .ok_or_else(|| format!("Chemical {} not found", id))
+}
+
+/// Take a session's usage out of stock. Returns the updated chemical and the
+/// volume actually deducted, which is less than asked when the bottle ran dry.
+pub(crate) async fn apply_usage(
+    conn: &mut SqliteConnection,
+    usage: &ChemicalUsage,
+    roll_count: i64,
+) -> Result<(Chemical, f64), String> {
+    let chemical = fetch_chemical(conn, &usage.chemical_id).await?;
+    let volume = usage.volume_ml.unwrap_or(0.0).max(0.0);
+
+    if chemical.one_shot && volume <= 0.0 {
+        return Err(format!(
+            "{} is one-shot; the volume used must be given",
+            chemical.name
+        ));
+    }
+    let rolls = if chemical.one_shot { 0 } else { roll_count };
+    let deducted = volume.min(chemical.volume_remaining_ml.max(0.0));
+
+    sqlx::query(
+        "UPDATE chemicals SET volume_remaining_ml = volume_remaining_ml - ?, rolls_processed = rolls_processed + ? WHERE id = ?",
+    )
+    .bind(deducted)
+    .bind(rolls)
+    .bind(&chemical.id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to update {}: {}", chemical.name, e))?;
+
+    Ok((fetch_chemical(conn, &chemical.id).await?, deducted))
+}
+
+/// Put a deleted session's usage back into stock. `deducted_ml` is what
+/// `apply_usage` actually took, not what the session asked for.
+pub(crate) async fn revert_usage(
+    conn: &mut SqliteConnection,
+    chemical_id: &str,
+    deducted_ml: f64,
+    roll_count: i64,
+) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE chemicals SET volume_remaining_ml = volume_remaining_ml + ?, rolls_processed = MAX(rolls_processed - CASE WHEN one_shot = 1 THEN 0 ELSE ? END, 0) WHERE id = ?",
+    )
+    .bind(deducted_ml)
+    .bind(roll_count)
+    .bind(chemical_id)
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Failed to restore chemical stock: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_chemicals(app: tauri::AppHandle) -> Result<Vec<Chemical>, String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query_as::<_, Chemical>(&format!(
+        "SELECT {} FROM chemicals ORDER BY kind, name",
+        CHEMICAL_COLUMNS
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load chemicals: {}", e))
+}
+
+#[tauri::command]
+pub async fn add_chemical(app: tauri::AppHandle, chemical: NewChemical) -> Result<Chemical, String> {
+    let pool = db::pool(&app).await?;
+
+    let name = chemical.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Chemical name is required".to_string());
+    }
+
+    let entry = Chemical {
+        id: db::new_id(),
+        name,
+        kind: validate_kind(&chemical.kind)?,
+        opened_at: non_empty(chemical.opened_at),
+        volume_remaining_ml: chemical.volume_remaining_ml.max(0.0),
+        volume_initial_ml: chemical.volume_remaining_ml.max(0.0),
+        one_shot: chemical.one_shot,
+        capacity_rolls_per_litre: chemical.capacity_rolls_per_litre.max(0.0),
+        rolls_processed: 0,
+        expires_at: non_empty(chemical.expires_at),
+        notes: chemical.notes,
+        created_at: db::now_iso(),
+    };
+
+    sqlx::query(&format!(
+        "INSERT INTO chemicals ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        CHEMICAL_COLUMNS
+    ))
+    .bind(&entry.id)
+    .bind(&entry.name)
+    .bind(&entry.kind)
+    .bind(&entry.opened_at)
+    .bind(entry.volume_remaining_ml)
+    .bind(entry.volume_initial_ml)
+    .bind(entry.one_shot)
+    .bind(entry.capacity_rolls_per_litre)
+    .bind(entry.rolls_processed)
+    .bind(&entry.expires_at)
+    .bind(&entry.notes)
+    .bind(&entry.created_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to add chemical: {}", e))?;
+
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn update_chemical(app: tauri::AppHandle, chemical: Chemical) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    let kind = validate_kind(&chemical.kind)?;
+
+    let result = sqlx::query(
+        "UPDATE chemicals SET name = ?, kind = ?, opened_at = ?, volume_remaining_ml = ?, volume_initial_ml = ?, one_shot = ?, capacity_rolls_per_litre = ?, rolls_processed = ?, expires_at = ?, notes = ? WHERE id = ?",
+    )
+    .bind(chemical.name.trim())
+    .bind(&kind)
+    .bind(non_empty(chemical.opened_at))
+    .bind(chemical.volume_remaining_ml.max(0.0))
+    // A refill above the starting volume becomes the new starting volume.
+    .bind(chemical.volume_initial_ml.max(chemical.volume_remaining_ml).max(0.0))
+    .bind(chemical.one_shot)
+    .bind(chemical.capacity_rolls_per_litre.max(0.0))
+    .bind(chemical.rolls_processed.max(0))
+    .bind(non_empty(chemical.expires_at))
+    .bind(&chemical.notes)
+    .bind(&chemical.id)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to update chemical: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Chemical {} not found", chemical.id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_chemical(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query("DELETE FROM chemicals WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete chemical: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_inventory(app: tauri::AppHandle) -> Result<Vec<InventoryWarning>, String> {
+    let pool = db::pool(&app).await?;
+    let chemicals = list_chemicals(app).await?;
+
+    let today = today();
+    let mut warnings = Vec::new();
+    for chemical in &chemicals {
+        let last_usage: Option<f64> = sqlx::query_scalar(
+            "SELECT sc.volume_ml FROM session_chemicals sc JOIN sessions s ON s.id = sc.session_id WHERE sc.chemical_id = ? ORDER BY s.processed_at DESC LIMIT 1",
+        )
+        .bind(&chemical.id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load chemical usage: {}", e))?;
+
+        warnings.extend(warnings_for(chemical, last_usage, today));
+    }
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixer(remaining: f64, initial: f64, rolls: i64) -> Chemical {
+        Chemical {
+            id: "fix".to_string(),
+            name: "Fixer".to_string(),
+            kind: "fix".to_string(),
+            opened_at: None,
+            volume_remaining_ml: remaining,
+            volume_initial_ml: initial,
+            one_shot: false,
+            capacity_rolls_per_litre: 24.0,
+            rolls_processed: rolls,
+            expires_at: None,
+            notes: String::new(),
+            created_at: String::new(),
+        }
+    }
+
+    fn kinds(chemical: &Chemical) -> Vec<String> {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        warnings_for(chemical, None, today)
+            .into_iter()
+            .map(|w| w.kind)
+            .collect()
+    }
+
+    #[test]
+    fn reusable_capacity_is_rated_on_starting_volume() {
+        // 1 l rated for 24 rolls; carry-over loss has left 800 ml after 10 rolls.
+        assert!(kinds(&fixer(800.0, 1000.0, 10)).is_empty());
+        assert_eq!(kinds(&fixer(800.0, 1000.0, 20)), ["near_capacity"]);
+        assert_eq!(kinds(&fixer(800.0, 1000.0, 24)), ["exhausted"]);
+    }
+
+    #[test]
+    fn duplicate_usages_are_merged() {
+        let usage = |id: &str, ml: Option<f64>| ChemicalUsage {
+            chemical_id: id.to_string(),
+            volume_ml: ml,
+        };
+        let merged = merge_usages(&[
+            usage("dev", Some(10.0)),
+            usage("fix", None),
+            usage("dev", Some(5.0)),
+            usage("fix", None),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].volume_ml, Some(15.0));
+        assert_eq!(merged[1].volume_ml, None);
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::Path;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::db;
+use crate::mdc::{self, MdcEntry, MdcPage, MdcQuery};
+use crate::mdc_import::{self, MdcImportReport, MdcSnapshot};
+use crate::recipe::{self, Recipe};
+use crate::template::{self, TemplateStep};
+
+const DEFAULT_NAME_LIMIT: i64 = 12;
+
+#[tauri::command]
+pub async fn search_mdc(app: tauri::AppHandle, query: MdcQuery) -> Result<MdcPage, String> {
+    let pool = db::pool(&app).await?;
+    mdc::search(&pool, &query).await
+}
+
+#[tauri::command]
+pub async fn get_mdc_entry(app: tauri::AppHandle, id: i64) -> Result<MdcEntry, String> {
+    let pool = db::pool(&app).await?;
+    mdc::get_entry(&pool, id).await
+}
+
+#[tauri::command]
+pub async fn list_mdc_films(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let pool = db::pool(&app).await?;
+    mdc::distinct_names(&pool, "film", &query, None, limit.unwrap_or(DEFAULT_NAME_LIMIT)).await
+}
+
+#[tauri::command]
+pub async fn list_mdc_developers(
+    app: tauri::AppHandle,
+    query: String,
+    film: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let pool = db::pool(&app).await?;
+    mdc::distinct_names(
+        &pool,
+        "developer",
+        &query,
+        film.as_deref(),
+        limit.unwrap_or(DEFAULT_NAME_LIMIT),
+    )
+    .await
+}
+
+/// Generate a recipe from a chart row for the given film format and store it.
+/// `template_id` picks a saved template; otherwise `template_steps` names the
+/// steps, defaulting to the standard DEV/STOP/FIX/RINSE skeleton.
+#[tauri::command]
+pub async fn create_recipe_from_mdc(
+    app: tauri::AppHandle,
+    entry_id: i64,
+    format: String,
+    template_id: Option<String>,
+    template_steps: Option<Vec<String>>,
+) -> Result<Recipe, String> {
+    let pool = db::pool(&app).await?;
+    let entry = mdc::get_entry(&pool, entry_id).await?;
+
+    let (category, skeleton) = match template_id {
+        Some(id) => {
+            let template = template::load_template(&pool, &id).await?;
+            let steps = template.steps.iter().map(TemplateStep::to_step).collect();
+            (template.category, steps)
+        }
+        None => {
+            let temps = recipe::temperature_defaults(&pool).await?;
+            let names = template_steps
+                .filter(|steps| !steps.is_empty())
+                .unwrap_or_else(|| {
+                    recipe::DEFAULT_TEMPLATE_STEPS
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect()
+                });
+            let steps = names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| recipe::default_step(name, index as i64, temps))
+                .collect::<Vec<_>>();
+            ("BW".to_string(), steps)
+        }
+    };
+    let mut recipe = mdc::recipe_from_entry(&entry, &format, &skeleton)?;
+    recipe.category = category;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    recipe::insert_recipe(&mut tx, &mut recipe).await?;
+    sqlx::query("UPDATE recipes SET mdc_source = ? WHERE id = ?")
+        .bind(mdc::row_key(&entry.film, &entry.developer, &entry.dilution, &entry.iso))
+        .bind(&recipe.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to link recipe to chart row: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipe: {}", e))?;
+
+    Ok(recipe)
+}
+
+/// Import a newer Massive Dev Chart CSV export as the current snapshot.
+#[tauri::command]
+pub async fn import_mdc_csv(app: tauri::AppHandle) -> Result<MdcImportReport, String> {
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("CSV", &["csv"])
+        .blocking_pick_file();
+
+    let Some(path) = file_path else {
+        return Err("Import cancelled".to_string());
+    };
+    let path_str = path.to_string();
+    let content =
+        fs::read_to_string(&path_str).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let (rows, skipped) = mdc_import::parse_chart_csv(&content)?;
+    let label = Path::new(&path_str)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported chart".to_string());
+
+    let pool = db::pool(&app).await?;
+    mdc_import::import_snapshot(&pool, &label, &path_str, rows, skipped).await
+}
+
+#[tauri::command]
+pub async fn list_mdc_snapshots(app: tauri::AppHandle) -> Result<Vec<MdcSnapshot>, String> {
+    let pool = db::pool(&app).await?;
+    mdc_import::list_snapshots(&pool).await
+}
+
+/// Clear the "chart row changed" flag once the user has reviewed a recipe.
+#[tauri::command]
+pub async fn acknowledge_mdc_change(app: tauri::AppHandle, recipe_id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query("UPDATE recipes SET mdc_source_changed = 0 WHERE id = ?")
+        .bind(&recipe_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to update recipe: {}", e))?;
+    Ok(())
+}
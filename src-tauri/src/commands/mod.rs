@@ -1,2 +1,14 @@
 pub mod wifi;
 pub mod export;
+pub mod sessions;
+pub mod inventory;
+pub mod mdc;
+pub mod chemistry;
+pub mod templates;
+pub mod catalog;
+pub mod bundle;
+pub mod devices;
+pub mod collections;
+pub mod deploy;
+pub mod device_session;
+pub mod discovery;
@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::commands::inventory::{self, ChemicalUsage, InventoryWarning};
+use crate::db;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DevSession {
+    pub id: String,
+    pub recipe_id: Option<String>,
+    pub recipe_revision: String,
+    pub film_format: String,
+    pub roll_count: i64,
+    pub measured_temperature: Option<f64>,
+    pub developer_batch: String,
+    pub ago_filename: String,
+    pub rating: Option<i64>,
+    pub notes: String,
+    pub processed_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDevSession {
+    pub recipe_id: String,
+    /// Defaults to the recipe's current `updated_at`, which is what the editor bumps on every change.
+    #[serde(default)]
+    pub recipe_revision: Option<String>,
+    #[serde(default)]
+    pub film_format: Option<String>,
+    #[serde(default)]
+    pub roll_count: Option<i64>,
+    #[serde(default)]
+    pub measured_temperature: Option<f64>,
+    #[serde(default)]
+    pub developer_batch: String,
+    #[serde(default)]
+    pub ago_filename: String,
+    #[serde(default)]
+    pub rating: Option<i64>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub processed_at: Option<String>,
+    /// Bottles used for this run; stock is decremented when the session is logged.
+    #[serde(default)]
+    pub chemicals: Vec<ChemicalUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedDevSession {
+    pub session: DevSession,
+    pub warnings: Vec<InventoryWarning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingPoint {
+    pub processed_at: String,
+    pub rating: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub recipe_id: String,
+    pub run_count: i64,
+    pub total_rolls: i64,
+    pub average_rating: Option<f64>,
+    /// Average of the last few rated runs, to compare against the overall average.
+    pub recent_average_rating: Option<f64>,
+    pub average_temperature: Option<f64>,
+    pub first_processed_at: Option<String>,
+    pub last_processed_at: Option<String>,
+    pub rating_trend: Vec<RatingPoint>,
+}
+
+const RECENT_RATING_WINDOW: usize = 5;
+
+const SESSION_COLUMNS: &str = "id, recipe_id, recipe_revision, film_format, roll_count, measured_temperature, developer_batch, ago_filename, rating, notes, processed_at";
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(((sum / count as f64) * 100.0).round() / 100.0)
+    }
+}
+
+#[tauri::command]
+pub async fn log_dev_session(
+    app: tauri::AppHandle,
+    session: NewDevSession,
+) -> Result<LoggedDevSession, String> {
+    let pool = db::pool(&app).await?;
+    insert_session(&pool, session).await
+}
+
+/// Record a session and take its chemistry out of stock.
+pub(crate) async fn insert_session(
+    pool: &Pool<Sqlite>,
+    session: NewDevSession,
+) -> Result<LoggedDevSession, String> {
+    if let Some(rating) = session.rating {
+        if !(1..=5).contains(&rating) {
+            return Err(format!("Rating must be between 1 and 5, got {}", rating));
+        }
+    }
+    let roll_count = session.roll_count.unwrap_or(1);
+    if roll_count < 1 {
+        return Err("Roll count must be at least 1".to_string());
+    }
+
+    let current_revision: Option<String> =
+        sqlx::query_scalar("SELECT updated_at FROM recipes WHERE id = ?")
+            .bind(&session.recipe_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load recipe: {}", e))?;
+    let Some(current_revision) = current_revision else {
+        return Err(format!("Recipe {} not found", session.recipe_id));
+    };
+
+    let film_format = session
+        .film_format
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| "35mm".to_string());
+
+    let entry = DevSession {
+        id: db::new_id(),
+        recipe_id: Some(session.recipe_id),
+        recipe_revision: session.recipe_revision.unwrap_or(current_revision),
+        film_format,
+        roll_count,
+        measured_temperature: session.measured_temperature,
+        developer_batch: session.developer_batch.trim().to_string(),
+        ago_filename: session.ago_filename.trim().to_string(),
+        rating: session.rating,
+        notes: session.notes,
+        processed_at: session.processed_at.unwrap_or_else(db::now_iso),
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    sqlx::query(&format!(
+        "INSERT INTO sessions ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        SESSION_COLUMNS
+    ))
+    .bind(&entry.id)
+    .bind(&entry.recipe_id)
+    .bind(&entry.recipe_revision)
+    .bind(&entry.film_format)
+    .bind(entry.roll_count)
+    .bind(entry.measured_temperature)
+    .bind(&entry.developer_batch)
+    .bind(&entry.ago_filename)
+    .bind(entry.rating)
+    .bind(&entry.notes)
+    .bind(&entry.processed_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to log session: {}", e))?;
+
+    let today = inventory::today();
+    let mut warnings = Vec::new();
+    for usage in &inventory::merge_usages(&session.chemicals) {
+        let (chemical, deducted) = inventory::apply_usage(&mut tx, usage, entry.roll_count).await?;
+
+        sqlx::query(
+            "INSERT INTO session_chemicals (session_id, chemical_id, volume_ml, volume_deducted_ml) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&entry.id)
+        .bind(&chemical.id)
+        .bind(usage.volume_ml.unwrap_or(0.0).max(0.0))
+        .bind(deducted)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record chemical usage: {}", e))?;
+
+        warnings.extend(inventory::warnings_for(&chemical, usage.volume_ml, today));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to log session: {}", e))?;
+
+    Ok(LoggedDevSession {
+        session: entry,
+        warnings,
+    })
+}
+
+#[tauri::command]
+pub async fn list_dev_sessions(
+    app: tauri::AppHandle,
+    recipe_id: String,
+) -> Result<Vec<DevSession>, String> {
+    let pool = db::pool(&app).await?;
+
+    sqlx::query_as::<_, DevSession>(&format!(
+        "SELECT {} FROM sessions WHERE recipe_id = ? ORDER BY processed_at DESC",
+        SESSION_COLUMNS
+    ))
+    .bind(&recipe_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load sessions: {}", e))
+}
+
+#[tauri::command]
+pub async fn delete_dev_session(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    remove_session(&pool, &id).await
+}
+
+/// Delete a session and put the chemistry it used back into stock.
+pub(crate) async fn remove_session(pool: &Pool<Sqlite>, id: &str) -> Result<(), String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let roll_count: Option<i64> = sqlx::query_scalar("SELECT roll_count FROM sessions WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to load session: {}", e))?;
+    let Some(roll_count) = roll_count else {
+        return Ok(());
+    };
+
+    // A session logged by mistake should not leave the inventory short.
+    let usages: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT chemical_id, volume_deducted_ml FROM session_chemicals WHERE session_id = ?",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to load chemical usage: {}", e))?;
+    for (chemical_id, deducted_ml) in usages {
+        inventory::revert_usage(&mut tx, &chemical_id, deducted_ml, roll_count).await?;
+    }
+
+    sqlx::query("DELETE FROM sessions WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to delete session: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to delete session: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_recipe_session_summary(
+    app: tauri::AppHandle,
+    recipe_id: String,
+) -> Result<SessionSummary, String> {
+    let sessions = list_dev_sessions(app, recipe_id.clone()).await?;
+
+    // `list_dev_sessions` is newest first; the trend reads oldest to newest.
+    let rating_trend = sessions
+        .iter()
+        .rev()
+        .filter_map(|s| {
+            s.rating.map(|rating| RatingPoint {
+                processed_at: s.processed_at.clone(),
+                rating,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let recent_start = rating_trend.len().saturating_sub(RECENT_RATING_WINDOW);
+
+    Ok(SessionSummary {
+        recipe_id,
+        run_count: sessions.len() as i64,
+        total_rolls: sessions.iter().map(|s| s.roll_count).sum(),
+        average_rating: average(rating_trend.iter().map(|p| p.rating as f64)),
+        recent_average_rating: average(rating_trend[recent_start..].iter().map(|p| p.rating as f64)),
+        average_temperature: average(sessions.iter().filter_map(|s| s.measured_temperature)),
+        first_processed_at: sessions.last().map(|s| s.processed_at.clone()),
+        last_processed_at: sessions.first().map(|s| s.processed_at.clone()),
+        rating_trend,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    async fn inventory_pool() -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for migration in crate::migrations::get_migrations() {
+            sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        }
+        sqlx::query("INSERT INTO recipes (id, name, updated_at) VALUES ('r1', 'HP5+ Rodinal', '2024-01-01')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, remaining, one_shot, rolls) in [
+            ("rodinal", 0.3, true, 0),
+            ("fixer", 1000.0, false, 7),
+            ("stop", 250.0, true, 0),
+        ] {
+            sqlx::query(
+                "INSERT INTO chemicals (id, name, volume_remaining_ml, volume_initial_ml, one_shot, rolls_processed) VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(remaining)
+            .bind(remaining)
+            .bind(one_shot)
+            .bind(rolls)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        pool
+    }
+
+    async fn stock(pool: &Pool<Sqlite>) -> Vec<(String, f64, i64)> {
+        sqlx::query_as("SELECT id, volume_remaining_ml, rolls_processed FROM chemicals ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    fn usage(chemical_id: &str, volume_ml: Option<f64>) -> ChemicalUsage {
+        ChemicalUsage {
+            chemical_id: chemical_id.to_string(),
+            volume_ml,
+        }
+    }
+
+    #[test]
+    fn deleting_a_session_restores_what_logging_deducted() {
+        block_on(async {
+            let pool = inventory_pool().await;
+            let before = stock(&pool).await;
+
+            let logged = insert_session(
+                &pool,
+                NewDevSession {
+                    recipe_id: "r1".to_string(),
+                    recipe_revision: None,
+                    film_format: None,
+                    roll_count: Some(2),
+                    measured_temperature: Some(20.1),
+                    developer_batch: String::new(),
+                    ago_filename: String::new(),
+                    rating: Some(4),
+                    notes: String::new(),
+                    processed_at: None,
+                    // The developer runs dry, and the stop bath is listed twice.
+                    chemicals: vec![
+                        usage("rodinal", Some(0.5)),
+                        usage("fixer", Some(12.5)),
+                        usage("stop", Some(100.0)),
+                        usage("stop", Some(100.0)),
+                    ],
+                },
+            )
+            .await
+            .unwrap();
+
+            let deducted: Vec<(String, f64)> = sqlx::query_as(
+                "SELECT chemical_id, volume_deducted_ml FROM session_chemicals ORDER BY chemical_id",
+            )
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+            assert_eq!(
+                deducted,
+                [
+                    ("fixer".to_string(), 12.5),
+                    ("rodinal".to_string(), 0.3),
+                    ("stop".to_string(), 200.0),
+                ]
+            );
+            assert_eq!(
+                stock(&pool).await,
+                [
+                    ("fixer".to_string(), 987.5, 9),
+                    ("rodinal".to_string(), 0.0, 0),
+                    ("stop".to_string(), 50.0, 0),
+                ]
+            );
+
+            remove_session(&pool, &logged.session.id).await.unwrap();
+            assert_eq!(stock(&pool).await, before);
+            let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM session_chemicals")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(left, 0);
+
+            // Deleting again changes nothing.
+            remove_session(&pool, &logged.session.id).await.unwrap();
+            assert_eq!(stock(&pool).await, before);
+        });
+    }
+}
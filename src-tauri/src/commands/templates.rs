@@ -0,0 +1,145 @@
+use crate::db;
+use crate::recipe::{self, Recipe};
+use crate::template::{self, NewTemplate, Template};
+
+#[tauri::command]
+pub async fn list_templates(app: tauri::AppHandle) -> Result<Vec<Template>, String> {
+    let pool = db::pool(&app).await?;
+    template::load_all_templates(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_template(app: tauri::AppHandle, id: String) -> Result<Template, String> {
+    let pool = db::pool(&app).await?;
+    template::load_template(&pool, &id).await
+}
+
+#[tauri::command]
+pub async fn create_template(
+    app: tauri::AppHandle,
+    template: NewTemplate,
+) -> Result<Template, String> {
+    let pool = db::pool(&app).await?;
+    template::validate(&template.name, &template.steps)?;
+
+    let now = db::now_iso();
+    let entry = Template {
+        id: db::new_id(),
+        name: template.name.trim().to_string(),
+        category: template
+            .category
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| "BW".to_string()),
+        description: template.description,
+        builtin: false,
+        created_at: now.clone(),
+        updated_at: now,
+        steps: template.steps,
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    template::insert_template(&mut tx, &entry).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save template: {}", e))?;
+
+    template::load_template(&pool, &entry.id).await
+}
+
+/// Save a template's fields and replace its steps.
+#[tauri::command]
+pub async fn update_template(app: tauri::AppHandle, template: Template) -> Result<Template, String> {
+    let pool = db::pool(&app).await?;
+    template::validate(&template.name, &template.steps)?;
+    template::ensure_editable(&pool, &template.id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let result = sqlx::query(
+        "UPDATE templates SET name = ?, category = ?, description = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(template.name.trim())
+    .bind(template.category.trim())
+    .bind(&template.description)
+    .bind(db::now_iso())
+    .bind(&template.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update template: {}", e))?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Template {} not found", template.id));
+    }
+
+    template::replace_template_steps(&mut tx, &template.id, &template.steps).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save template: {}", e))?;
+
+    template::load_template(&pool, &template.id).await
+}
+
+#[tauri::command]
+pub async fn delete_template(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    template::ensure_editable(&pool, &id).await?;
+
+    sqlx::query("DELETE FROM templates WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete template: {}", e))?;
+    Ok(())
+}
+
+/// Copy a template, built-in or not, as a new user template.
+#[tauri::command]
+pub async fn duplicate_template(
+    app: tauri::AppHandle,
+    id: String,
+    name: Option<String>,
+) -> Result<Template, String> {
+    let pool = db::pool(&app).await?;
+    let source = template::load_template(&pool, &id).await?;
+    let copy = template::duplicate(source, name);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    template::insert_template(&mut tx, &copy).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save template: {}", e))?;
+
+    template::load_template(&pool, &copy.id).await
+}
+
+/// Create and store a recipe whose steps are copies of the template's.
+#[tauri::command]
+pub async fn create_recipe_from_template(
+    app: tauri::AppHandle,
+    template_id: String,
+    name: Option<String>,
+) -> Result<Recipe, String> {
+    let pool = db::pool(&app).await?;
+    let template = template::load_template(&pool, &template_id).await?;
+    let mut recipe = template::recipe_from_template(&template, name);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    recipe::insert_recipe(&mut tx, &mut recipe).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save recipe: {}", e))?;
+
+    Ok(recipe)
+}
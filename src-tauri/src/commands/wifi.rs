@@ -1,8 +1,20 @@
 use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use crate::db;
+use crate::device::{self, Device};
+use crate::discovery;
+use crate::probe::{self, DeviceProbe};
+use crate::secrets;
+use crate::supervisor::{self, ConnectionState};
+use crate::wifi_scan::{self, WifiNetwork};
 
 #[tauri::command]
 pub async fn wifi_get_interface() -> Result<String, String> {
+    wifi_interface()
+}
+
+pub(crate) fn wifi_interface() -> Result<String, String> {
     let output = Command::new("networksetup")
         .arg("-listallhardwareports")
         .output()
@@ -30,10 +42,81 @@ pub async fn wifi_get_interface() -> Result<String, String> {
     Err("Could not find Wi-Fi interface".to_string())
 }
 
+/// Networks in range with signal and security, AGO-like SSIDs first. Saved
+/// devices are matched by SSID. Scans on `interface`, or every interface.
+#[tauri::command]
+pub async fn wifi_scan_networks(
+    app: tauri::AppHandle,
+    interface: Option<String>,
+) -> Result<Vec<WifiNetwork>, String> {
+    let interface = interface.unwrap_or_default();
+    let mut networks = tokio::task::spawn_blocking(move || scan_networks(interface.trim()))
+        .await
+        .map_err(|e| format!("Wi-Fi scan failed: {}", e))??;
+
+    let pool = db::pool(&app).await?;
+    let devices = device::load_all_devices(&pool).await?;
+    for network in &mut networks {
+        if let Some(device) = devices.iter().find(|d| d.ssid == network.ssid) {
+            network.device_id = Some(device.id.clone());
+            network.ago_like = true;
+        }
+    }
+    Ok(wifi_scan::merge(networks))
+}
+
+fn scan_networks(interface: &str) -> Result<Vec<WifiNetwork>, String> {
+    if cfg!(target_os = "macos") {
+        let output = Command::new("system_profiler")
+            .args(["SPAirPortDataType", "-json"])
+            .output()
+            .map_err(|e| format!("Failed to run system_profiler: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to scan for networks: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        wifi_scan::parse_system_profiler(&String::from_utf8_lossy(&output.stdout), interface)
+    } else if cfg!(target_os = "linux") {
+        let mut args = vec![
+            "-t",
+            "-f",
+            "IN-USE,SSID,SIGNAL,SECURITY",
+            "device",
+            "wifi",
+            "list",
+            "--rescan",
+            "yes",
+        ];
+        if !interface.is_empty() {
+            args.extend(["ifname", interface]);
+        }
+        let output = Command::new("nmcli")
+            .args(&args)
+            .output()
+            .map_err(|e| format!("Failed to run nmcli: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to scan for networks: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(wifi_scan::parse_nmcli(&String::from_utf8_lossy(&output.stdout)))
+    } else {
+        Err("Scanning for Wi-Fi networks is only supported on macOS and Linux".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn wifi_get_current_network(interface: String) -> Result<String, String> {
+    current_network(&interface)
+}
+
+/// SSID the interface is joined to, or an empty string when it is not associated.
+pub(crate) fn current_network(interface: &str) -> Result<String, String> {
     let output = Command::new("networksetup")
-        .args(["-getairportnetwork", &interface])
+        .args(["-getairportnetwork", interface])
         .output()
         .map_err(|e| format!("Failed to get current network: {}", e))?;
 
@@ -66,15 +149,14 @@ pub async fn wifi_get_current_network(interface: String) -> Result<String, Strin
     }
 }
 
-#[tauri::command]
-pub async fn wifi_connect(interface: String, ssid: String, password: String) -> Result<(), String> {
-    let mut args = vec!["-setairportnetwork".to_string(), interface.clone(), ssid.clone()];
+pub(crate) fn connect(interface: &str, ssid: &str, password: &str) -> Result<(), String> {
+    let mut args = vec!["-setairportnetwork", interface, ssid];
     if !password.is_empty() {
-        args.push(password.clone());
+        args.push(password);
     }
 
     let first = Command::new("networksetup")
-        .args(args.iter().map(String::as_str))
+        .args(&args)
         .output()
         .map_err(|e| format!("Failed to connect to WiFi: {}", e))?;
 
@@ -92,7 +174,7 @@ pub async fn wifi_connect(interface: String, ssid: String, password: String) ->
     // Retry without password for networks already stored in Keychain.
     if !password.is_empty() {
         let second = Command::new("networksetup")
-            .args(["-setairportnetwork", &interface, &ssid])
+            .args(["-setairportnetwork", interface, ssid])
             .output()
             .map_err(|e| format!("Failed to connect to WiFi: {}", e))?;
 
@@ -115,35 +197,198 @@ pub async fn wifi_connect(interface: String, ssid: String, password: String) ->
     ))
 }
 
+/// Join a device's network with the password stored for it, if any.
 #[tauri::command]
-pub async fn wifi_reconnect(interface: String, ssid: String, password: String) -> Result<(), String> {
-    // Reconnect to a previous network. If password is empty, try without it (saved networks).
-    if password.is_empty() {
-        let output = Command::new("networksetup")
-            .args(["-setairportnetwork", &interface, &ssid])
-            .output()
-            .map_err(|e| format!("Failed to reconnect: {}", e))?;
+pub async fn wifi_connect(
+    app: tauri::AppHandle,
+    interface: String,
+    device_id: String,
+) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    if let Ok(ssid) = current_network(&interface) {
+        supervisor::remember_network(&app, &ssid, &device.ssid);
+    }
+    let password = device.wifi_password()?.unwrap_or_default();
+    connect(&interface, &device.ssid, &password)
+}
+
+#[tauri::command]
+pub async fn wifi_reconnect(interface: String, ssid: String) -> Result<(), String> {
+    reconnect(&interface, &ssid)
+}
 
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(format!("Failed to reconnect to {}", ssid))
+/// Reconnect to a previous network. Without a stored password, rely on the
+/// one the system saved for it.
+pub(crate) fn reconnect(interface: &str, ssid: &str) -> Result<(), String> {
+    let store = secrets::default_store()?;
+    match secrets::wifi_password(store.as_ref(), ssid)? {
+        Some(password) => connect(interface, ssid, &password),
+        None => {
+            let output = Command::new("networksetup")
+                .args(["-setairportnetwork", interface, ssid])
+                .output()
+                .map_err(|e| format!("Failed to reconnect: {}", e))?;
+
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(format!("Failed to reconnect to {}", ssid))
+            }
         }
+    }
+}
+
+/// Check the active device now instead of waiting for the next poll.
+#[tauri::command]
+pub async fn get_connection_state(app: tauri::AppHandle) -> Result<ConnectionState, String> {
+    supervisor::check(&app).await
+}
+
+/// Leave the AGO for the network in use before joining it. Returns the SSID
+/// rejoined, or nothing when no earlier network is known.
+#[tauri::command]
+pub async fn restore_previous_network(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    supervisor::restore(&app)
+}
+
+/// Store a device's Wi-Fi password in the secret store; an empty password removes it.
+#[tauri::command]
+pub async fn wifi_set_password(
+    app: tauri::AppHandle,
+    device_id: String,
+    password: String,
+) -> Result<(), String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let store = secrets::default_store()?;
+    if password.is_empty() {
+        store.delete(&device.credential_account())
     } else {
-        wifi_connect(interface, ssid, password).await
+        store.set(&device.credential_account(), &password)
     }
 }
 
 #[tauri::command]
-pub async fn wifi_probe_ago(ip: String) -> Result<bool, String> {
-    let url = format!("http://{}", ip);
+pub async fn wifi_has_password(app: tauri::AppHandle, device_id: String) -> Result<bool, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    Ok(device.wifi_password()?.is_some())
+}
+
+/// Whether the device answers like an AGO; any other web server counts as unreachable.
+#[tauri::command]
+pub async fn wifi_probe_ago(app: tauri::AppHandle, device_id: String) -> Result<bool, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    is_reachable(&device).await
+}
+
+/// Probe a device's web UI and programs API and cache the result for uploads.
+#[tauri::command]
+pub async fn probe_device(app: tauri::AppHandle, device_id: String) -> Result<DeviceProbe, String> {
+    let pool = db::pool(&app).await?;
+    let device = device::load_device(&pool, &device_id).await?;
+    let _hold = supervisor::hold(&app);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .map_err(|e| format!("Failed to create probe client: {}", e))?;
+
+    let mut result = run_probe(&client, &device.base_url()).await;
+    probe::save_probe(&pool, &device.id, &mut result).await?;
+    Ok(result)
+}
+
+/// The last probe cached for a device, if any.
+#[tauri::command]
+pub async fn get_device_probe(
+    app: tauri::AppHandle,
+    device_id: String,
+) -> Result<Option<DeviceProbe>, String> {
+    let pool = db::pool(&app).await?;
+    probe::load_probe(&pool, &device_id).await
+}
+
+/// Whether the device's custom programs API answers.
+pub(crate) async fn is_reachable(device: &Device) -> Result<bool, String> {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(2))
         .build()
         .map_err(|e| format!("Failed to create probe client: {}", e))?;
 
-    match client.get(url).send().await {
-        Ok(resp) => Ok(resp.status().is_success()),
+    match client
+        .get(format!("{}{}", device.base_url(), device::CUSTOM_PROGRAMS_PATH))
+        .header("Accept", "application/json, text/plain, */*")
+        .send()
+        .await
+    {
+        Ok(resp) => {
+            let status_ok = resp.status().is_success();
+            let body = resp.text().await.unwrap_or_default();
+            Ok(discovery::fingerprint(status_ok, &body).is_some())
+        }
         Err(_) => Ok(false),
     }
 }
+
+fn header_text(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> String {
+    resp.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Check the programs API, the methods it advertises for writing and the web
+/// UI's version strings. Latency is the programs API's response time.
+pub(crate) async fn run_probe(client: &reqwest::Client, base_url: &str) -> DeviceProbe {
+    let mut result = DeviceProbe {
+        probed_at: db::now_iso(),
+        ..Default::default()
+    };
+    let programs_url = format!("{}{}", base_url, device::CUSTOM_PROGRAMS_PATH);
+
+    let started = Instant::now();
+    if let Ok(resp) = client
+        .get(&programs_url)
+        .header("Accept", "application/json, text/plain, */*")
+        .send()
+        .await
+    {
+        result.reachable = true;
+        result.latency_ms = Some(started.elapsed().as_millis() as u64);
+        let status_ok = resp.status().is_success();
+        let body = resp.text().await.unwrap_or_default();
+        result.program_count = discovery::fingerprint(status_ok, &body);
+        result.programs_api = result.program_count.is_some();
+    } else {
+        // No connection at all; the web UI would not answer either.
+        return result;
+    }
+
+    if result.programs_api {
+        if let Ok(resp) = client
+            .request(reqwest::Method::OPTIONS, &programs_url)
+            .send()
+            .await
+        {
+            let allow = header_text(&resp, reqwest::header::ALLOW);
+            let cors = header_text(&resp, reqwest::header::ACCESS_CONTROL_ALLOW_METHODS);
+            result.advertised_methods = probe::allowed_write_methods(&format!("{},{}", allow, cors));
+        }
+    }
+
+    if let Ok(resp) = client.get(format!("{}/", base_url)).send().await {
+        let server = header_text(&resp, reqwest::header::SERVER);
+        let page = resp.text().await.unwrap_or_default();
+        result.firmware_hints = discovery::firmware_hints(&page);
+        for hint in discovery::firmware_hints(&server) {
+            if !result.firmware_hints.contains(&hint) {
+                result.firmware_hints.push(hint);
+            }
+        }
+    }
+
+    result
+}
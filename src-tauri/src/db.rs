@@ -0,0 +1,49 @@
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_sql::DbInstances;
+
+pub const DB_URL: &str = "sqlite:ago_recipes.db";
+
+/// Borrow the SQLite pool opened by the SQL plugin. The database is preloaded in
+/// `tauri.conf.json`, so migrations have already run by the time commands execute.
+pub async fn pool(app: &AppHandle) -> Result<Pool<Sqlite>, String> {
+    let instances = app
+        .try_state::<DbInstances>()
+        .ok_or_else(|| "Database is not loaded".to_string())?;
+    let instances = instances.0.read().await;
+
+    instances
+        .get(DB_URL)
+        .and_then(|db| db.sqlite())
+        .cloned()
+        .ok_or_else(|| format!("Database {} is not loaded", DB_URL))
+}
+
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Timestamp in the same shape the frontend writes (`Date.toISOString()`).
+pub fn now_iso() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+pub async fn setting(pool: &Pool<Sqlite>, key: &str) -> Result<Option<String>, String> {
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))
+}
+
+pub async fn set_setting(pool: &Pool<Sqlite>, key: &str, value: &str) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO settings (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
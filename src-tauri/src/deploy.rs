@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ago_format::AgoDeviceProgram;
+
+/// A collection recipe turned into the payload the device should hold.
+#[derive(Debug, Clone)]
+pub struct DesiredProgram {
+    pub recipe_id: String,
+    pub recipe_name: String,
+    pub program: AgoDeviceProgram,
+}
+
+/// A custom program file found on the device. `program` is `None` when the
+/// file could not be read or parsed.
+#[derive(Debug, Clone)]
+pub struct InstalledProgram {
+    pub filename: String,
+    pub program: Option<AgoDeviceProgram>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    /// Not on the device yet.
+    Upload,
+    /// On the device under the same label with different steps; uploaded,
+    /// then the old files are deleted.
+    Replace,
+    /// Already on the device as it should be.
+    Keep,
+    /// A duplicate of a collection program, or not in the collection when pruning.
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub action: SyncAction,
+    /// Program name and title as the AGO shows them.
+    pub label: String,
+    pub recipe_id: Option<String>,
+    /// Device files kept, replaced or removed by this change.
+    pub filenames: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPlan {
+    pub changes: Vec<PlannedChange>,
+}
+
+fn display_label(program: &AgoDeviceProgram) -> String {
+    format!(
+        "{}{}",
+        program.name.trim(),
+        program.expanded_title.trim_end()
+    )
+}
+
+/// The device tells programs apart by label only, so two recipes that share
+/// one would keep replacing each other. Names every such clash.
+pub fn check_labels(desired: &[DesiredProgram]) -> Result<(), String> {
+    let mut by_label: BTreeMap<String, Vec<&DesiredProgram>> = BTreeMap::new();
+    for want in desired {
+        by_label
+            .entry(want.program.label_key())
+            .or_default()
+            .push(want);
+    }
+    let clashes = by_label
+        .values()
+        .filter(|wants| wants.len() > 1)
+        .map(|wants| {
+            let names = wants
+                .iter()
+                .map(|w| w.recipe_name.as_str())
+                .collect::<Vec<_>>();
+            format!(
+                "{} all show as \"{}\" on the AGO",
+                names.join(", "),
+                display_label(&wants[0].program)
+            )
+        })
+        .collect::<Vec<_>>();
+    if clashes.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}; give them a different film, developer or dilution",
+            clashes.join("; ")
+        ))
+    }
+}
+
+/// Work out what to change so the device carries exactly one copy of each
+/// desired program. Programs outside the collection are left alone unless
+/// `prune` is set; unreadable files are never touched.
+pub fn plan(
+    desired: &[DesiredProgram],
+    installed: &[InstalledProgram],
+    prune: bool,
+) -> Result<SyncPlan, String> {
+    check_labels(desired)?;
+
+    let mut by_label: HashMap<String, Vec<(&str, String)>> = HashMap::new();
+    for file in installed {
+        if let Some(program) = &file.program {
+            by_label
+                .entry(program.label_key())
+                .or_default()
+                .push((file.filename.as_str(), program.fingerprint()));
+        }
+    }
+
+    let mut changes = Vec::new();
+    for want in desired {
+        let label = display_label(&want.program);
+        let fingerprint = want.program.fingerprint();
+        let existing = by_label
+            .remove(&want.program.label_key())
+            .unwrap_or_default();
+
+        match existing.iter().position(|(_, f)| *f == fingerprint) {
+            Some(index) => {
+                changes.push(PlannedChange {
+                    action: SyncAction::Keep,
+                    label: label.clone(),
+                    recipe_id: Some(want.recipe_id.clone()),
+                    filenames: vec![existing[index].0.to_string()],
+                });
+                let duplicates = existing
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != index)
+                    .map(|(_, (filename, _))| filename.to_string())
+                    .collect::<Vec<_>>();
+                if !duplicates.is_empty() {
+                    changes.push(PlannedChange {
+                        action: SyncAction::Remove,
+                        label,
+                        recipe_id: Some(want.recipe_id.clone()),
+                        filenames: duplicates,
+                    });
+                }
+            }
+            None => changes.push(PlannedChange {
+                action: if existing.is_empty() {
+                    SyncAction::Upload
+                } else {
+                    SyncAction::Replace
+                },
+                label,
+                recipe_id: Some(want.recipe_id.clone()),
+                filenames: existing.iter().map(|(f, _)| f.to_string()).collect(),
+            }),
+        }
+    }
+
+    if prune {
+        let mut leftovers = installed
+            .iter()
+            .filter_map(|file| {
+                let program = file.program.as_ref()?;
+                by_label
+                    .contains_key(&program.label_key())
+                    .then(|| (display_label(program), file.filename.clone()))
+            })
+            .collect::<Vec<_>>();
+        leftovers.sort();
+        for (label, filename) in leftovers {
+            changes.push(PlannedChange {
+                action: SyncAction::Remove,
+                label,
+                recipe_id: None,
+                filenames: vec![filename],
+            });
+        }
+    }
+
+    Ok(SyncPlan { changes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ago_format::AgoDeviceStep;
+
+    fn program(film: &str, title: &str, dev_seconds: i64) -> AgoDeviceProgram {
+        AgoDeviceProgram {
+            name: film.to_string(),
+            designator: "C2".to_string(),
+            category: "BW".to_string(),
+            expanded_title: title.to_string(),
+            steps: vec![AgoDeviceStep {
+                name: "DEV".to_string(),
+                time: dev_seconds,
+                agitation: "Roll".to_string(),
+                compensation: "Off".to_string(),
+                formula_designator: None,
+                min_temperature: None,
+                max_temperature: None,
+            }],
+        }
+    }
+
+    fn want(id: &str, program: AgoDeviceProgram) -> DesiredProgram {
+        DesiredProgram {
+            recipe_id: id.to_string(),
+            recipe_name: format!("Recipe {}", id),
+            program,
+        }
+    }
+
+    fn file(filename: &str, program: Option<AgoDeviceProgram>) -> InstalledProgram {
+        InstalledProgram {
+            filename: filename.to_string(),
+            program,
+        }
+    }
+
+    fn summary(plan: &SyncPlan) -> Vec<(SyncAction, Option<&str>, Vec<&str>)> {
+        plan.changes
+            .iter()
+            .map(|c| {
+                (
+                    c.action,
+                    c.recipe_id.as_deref(),
+                    c.filenames.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_matching_programs_and_uploads_missing_ones() {
+        let desired = [
+            want("a", program("HP5+", " - Rodinal 1+50", 660)),
+            want("b", program("Tri-X", " - D-76 1+1", 585)),
+        ];
+        // The device may trim the title; the label still matches.
+        let installed = [file(
+            "_P_C0_1.txt",
+            Some(program("HP5+", "- Rodinal 1+50", 660)),
+        )];
+
+        let plan = plan(&desired, &installed, false).unwrap();
+        assert_eq!(
+            summary(&plan),
+            [
+                (SyncAction::Keep, Some("a"), vec!["_P_C0_1.txt"]),
+                (SyncAction::Upload, Some("b"), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaces_programs_whose_steps_changed() {
+        let desired = [want("a", program("HP5+", " - Rodinal 1+50", 660))];
+        let installed = [
+            file("_P_C0_1.txt", Some(program("HP5+", " - Rodinal 1+50", 600))),
+            file("_P_C0_2.txt", Some(program("hp5+", " - rodinal 1+50", 540))),
+        ];
+
+        let plan = plan(&desired, &installed, false).unwrap();
+        assert_eq!(
+            summary(&plan),
+            [(
+                SyncAction::Replace,
+                Some("a"),
+                vec!["_P_C0_1.txt", "_P_C0_2.txt"]
+            )]
+        );
+    }
+
+    #[test]
+    fn removes_duplicate_copies_of_a_kept_program() {
+        let desired = [want("a", program("HP5+", " - Rodinal 1+50", 660))];
+        let installed = [
+            file("_P_C0_1.txt", Some(program("HP5+", " - Rodinal 1+50", 600))),
+            file("_P_C0_2.txt", Some(program("HP5+", " - Rodinal 1+50", 660))),
+            file("_P_C0_3.txt", Some(program("HP5+", " - Rodinal 1+50", 660))),
+        ];
+
+        let plan = plan(&desired, &installed, false).unwrap();
+        assert_eq!(
+            summary(&plan),
+            [
+                (SyncAction::Keep, Some("a"), vec!["_P_C0_2.txt"]),
+                (
+                    SyncAction::Remove,
+                    Some("a"),
+                    vec!["_P_C0_1.txt", "_P_C0_3.txt"]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn prunes_only_readable_programs_outside_the_collection() {
+        let desired = [want("a", program("HP5+", " - Rodinal 1+50", 660))];
+        let installed = [
+            file("_P_C0_1.txt", Some(program("HP5+", " - Rodinal 1+50", 660))),
+            file("_P_C0_2.txt", Some(program("Pan F", " - Perceptol", 720))),
+            file("_P_C0_3.txt", None),
+        ];
+
+        let kept = plan(&desired, &installed, false).unwrap();
+        assert_eq!(
+            summary(&kept),
+            [(SyncAction::Keep, Some("a"), vec!["_P_C0_1.txt"])]
+        );
+
+        let pruned = plan(&desired, &installed, true).unwrap();
+        assert_eq!(
+            summary(&pruned),
+            [
+                (SyncAction::Keep, Some("a"), vec!["_P_C0_1.txt"]),
+                (SyncAction::Remove, None, vec!["_P_C0_2.txt"]),
+            ]
+        );
+        assert!(pruned
+            .changes
+            .iter()
+            .all(|c| !c.filenames.contains(&"_P_C0_3.txt".to_string())));
+    }
+
+    #[test]
+    fn rejects_recipes_that_share_a_label() {
+        let desired = [
+            want("a", program("HP5+", " - Rodinal 1+50", 660)),
+            want("b", program("Tri-X", " - D-76 1+1", 585)),
+            want("c", program("HP5+", "- rodinal  1+50", 720)),
+        ];
+        let error = plan(&desired, &[], false).unwrap_err();
+        assert!(
+            error.starts_with("Recipe a, Recipe c all show as \"HP5+ - Rodinal 1+50\""),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::secrets;
+
+/// The device migration 14 creates from the old single-device settings.
+pub const DEFAULT_DEVICE_ID: &str = "default";
+
+/// Where the AGO serves custom program files.
+pub const CUSTOM_PROGRAMS_PATH: &str = "/api/files/programs/custom";
+
+pub const DEVICE_COLUMNS: &str = "id, name, ip, ssid, credential_ref, firmware_notes, upload_endpoint, upload_field, created_at, updated_at";
+
+/// One AGO unit. The Wi-Fi password is not stored here; `credential_ref` names
+/// the secret store entry holding it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Device {
+    pub id: String,
+    pub name: String,
+    pub ip: String,
+    pub ssid: String,
+    pub credential_ref: String,
+    pub firmware_notes: String,
+    /// Compatibility upload endpoint tried after the programs API; empty for none.
+    pub upload_endpoint: String,
+    pub upload_field: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Device {
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.ip.trim())
+    }
+
+    pub fn program_url(&self, filename: &str) -> String {
+        format!("{}{}/{}", self.base_url(), CUSTOM_PROGRAMS_PATH, filename)
+    }
+
+    /// Secret store account for the Wi-Fi password, defaulting to the SSID's.
+    pub fn credential_account(&self) -> String {
+        if self.credential_ref.trim().is_empty() {
+            secrets::wifi_account(&self.ssid)
+        } else {
+            self.credential_ref.trim().to_string()
+        }
+    }
+
+    pub fn wifi_password(&self) -> Result<Option<String>, String> {
+        secrets::default_store()?.get(&self.credential_account())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDevice {
+    pub name: String,
+    pub ip: String,
+    pub ssid: String,
+    #[serde(default)]
+    pub credential_ref: String,
+    #[serde(default)]
+    pub firmware_notes: String,
+    #[serde(default)]
+    pub upload_endpoint: String,
+    #[serde(default)]
+    pub upload_field: String,
+}
+
+/// Check a device's name and address before saving.
+pub fn validate(name: &str, ip: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Device name is required".to_string());
+    }
+    let ip = ip.trim();
+    if ip.is_empty() {
+        return Err("Device IP address is required".to_string());
+    }
+    if ip.contains("://") || ip.contains('/') || ip.contains(char::is_whitespace) {
+        return Err(format!(
+            "'{}' is not a host name or IP address; leave out http:// and paths",
+            ip
+        ));
+    }
+    Ok(())
+}
+
+pub async fn load_device(pool: &Pool<Sqlite>, id: &str) -> Result<Device, String> {
+    sqlx::query_as::<_, Device>(&format!(
+        "SELECT {} FROM devices WHERE id = ?",
+        DEVICE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load device: {}", e))?
+    .ok_or_else(|| format!("Device {} not found", id))
+}
+
+pub async fn load_all_devices(pool: &Pool<Sqlite>) -> Result<Vec<Device>, String> {
+    sqlx::query_as::<_, Device>(&format!(
+        "SELECT {} FROM devices ORDER BY name COLLATE NOCASE",
+        DEVICE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load devices: {}", e))
+}
@@ -0,0 +1,194 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::net::Ipv4Addr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::ago_format;
+
+/// Address the AGO uses for itself when it runs its own access point.
+pub const ACCESS_POINT_IP: Ipv4Addr = Ipv4Addr::new(10, 10, 10, 1);
+
+/// mDNS service types browsed for candidates; every hit is still fingerprinted.
+pub const MDNS_SERVICE_TYPES: [&str; 2] = ["_http._tcp.local.", "_arduino._tcp.local."];
+
+/// Subnets larger than this are only scanned in the /24 around our own address.
+const MIN_SCAN_PREFIX: u8 = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoverySource {
+    Scan,
+    Mdns,
+}
+
+/// An address whose custom programs endpoint answered like an AGO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredDevice {
+    pub ip: String,
+    pub latency_ms: u64,
+    pub source: DiscoverySource,
+    /// mDNS host name, when found that way.
+    pub hostname: Option<String>,
+    pub program_count: usize,
+    /// Version-looking strings from the web UI and response headers.
+    pub firmware_hints: Vec<String>,
+    /// Saved device profile already using this address.
+    pub device_id: Option<String>,
+}
+
+/// Whether a response from `/api/files/programs/custom` has the AGO's shape:
+/// a listing that names `_P_C*.txt` files, or `[]` for an empty custom folder.
+/// Routers and captive portals answer every path with a page or an error
+/// object, neither of which names a program. Returns the number of custom
+/// programs listed.
+pub fn fingerprint(status_ok: bool, body: &str) -> Option<usize> {
+    if !status_ok {
+        return None;
+    }
+    let filenames = ago_format::custom_program_filenames(body);
+    if !filenames.is_empty() {
+        return Some(filenames.len());
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(Value::Array(items)) if items.is_empty() => Some(0),
+        _ => None,
+    }
+}
+
+fn version_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:firmware|fw|version|ver|build)\b[\s:=v._-]*([0-9]+(?:\.[0-9]+){1,3}[a-z0-9-]*)|\bv([0-9]+\.[0-9]+(?:\.[0-9]+)?[a-z0-9-]*)\b")
+            .expect("valid version pattern")
+    })
+}
+
+/// Version strings found in a page or header, e.g. "Firmware 1.4.2" or "v2.0".
+pub fn firmware_hints(text: &str) -> Vec<String> {
+    let mut hints = BTreeSet::new();
+    for captures in version_pattern().captures_iter(text) {
+        let whole = captures.get(0).map(|m| m.as_str()).unwrap_or_default();
+        if captures.get(1).is_some() || captures.get(2).is_some() {
+            hints.insert(whole.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+    }
+    hints.into_iter().take(8).collect()
+}
+
+/// Addresses to try: the access point address, then every host on each local
+/// IPv4 network. Networks wider than /22 are narrowed to our own /24.
+pub fn scan_targets(networks: &[(Ipv4Addr, u8)]) -> Vec<Ipv4Addr> {
+    let mut targets = vec![ACCESS_POINT_IP];
+    let mut seen = BTreeSet::from([ACCESS_POINT_IP]);
+    for &(ip, prefix) in networks {
+        if ip.is_loopback() || ip.is_link_local() || prefix >= 31 {
+            continue;
+        }
+        let prefix = if prefix < MIN_SCAN_PREFIX { 24 } else { prefix };
+        let mask = u32::MAX << (32 - prefix as u32);
+        let network = u32::from(ip) & mask;
+        let broadcast = network | !mask;
+        for host in (network + 1)..broadcast {
+            let host = Ipv4Addr::from(host);
+            if host != ip && seen.insert(host) {
+                targets.push(host);
+            }
+        }
+    }
+    targets
+}
+
+/// IPv4 addresses and prefix lengths of this machine's network interfaces.
+pub fn local_networks() -> Result<Vec<(Ipv4Addr, u8)>, String> {
+    let interfaces = if_addrs::get_if_addrs()
+        .map_err(|e| format!("Failed to list network interfaces: {}", e))?;
+    Ok(interfaces
+        .into_iter()
+        .filter(|i| !i.is_loopback())
+        .filter_map(|i| match i.addr {
+            if_addrs::IfAddr::V4(v4) => Some((v4.ip, v4.prefixlen)),
+            if_addrs::IfAddr::V6(_) => None,
+        })
+        .collect())
+}
+
+/// Browse mDNS for `wait` and return resolved IPv4 addresses with host names.
+/// Blocks; run it off the async runtime.
+pub fn mdns_candidates(wait: Duration) -> Result<Vec<(Ipv4Addr, String)>, String> {
+    let daemon = mdns_sd::ServiceDaemon::new()
+        .map_err(|e| format!("Failed to start mDNS browser: {}", e))?;
+    let receivers = MDNS_SERVICE_TYPES
+        .iter()
+        .map(|ty| {
+            daemon
+                .browse(ty)
+                .map_err(|e| format!("Failed to browse {}: {}", ty, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let deadline = Instant::now() + wait;
+    let mut found = Vec::new();
+    let mut seen = BTreeSet::new();
+    while Instant::now() < deadline {
+        for receiver in &receivers {
+            while let Ok(event) = receiver.try_recv() {
+                if let mdns_sd::ServiceEvent::ServiceResolved(info) = event {
+                    let hostname = info.get_hostname().trim_end_matches('.').to_string();
+                    for ip in info.get_addresses_v4() {
+                        if seen.insert(*ip) {
+                            found.push((*ip, hostname.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let _ = daemon.shutdown();
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_counts_listed_programs() {
+        assert_eq!(fingerprint(true, "[]"), Some(0));
+        assert_eq!(fingerprint(true, " [ ]\n"), Some(0));
+        assert_eq!(
+            fingerprint(true, r#"["_P_C0_ab.txt", "_P_C0_cd.txt"]"#),
+            Some(2)
+        );
+        assert_eq!(
+            fingerprint(true, r#"{"files":[{"name":"/custom/_P_C0_ab.txt"}]}"#),
+            Some(1)
+        );
+        assert_eq!(
+            fingerprint(
+                true,
+                "<html><a href=\"_P_C0_ab.txt\">_P_C0_ab.txt</a></html>"
+            ),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn fingerprint_rejects_routers() {
+        assert_eq!(fingerprint(false, "[]"), None);
+        assert_eq!(
+            fingerprint(true, r#"{"error":"Not Found","code":404}"#),
+            None
+        );
+        assert_eq!(fingerprint(true, "{}"), None);
+        assert_eq!(fingerprint(true, r#"["index.html", "style.css"]"#), None);
+        let catch_all = "<!DOCTYPE html>\n<html><head><title>Router Login</title></head>\
+            <body><form action=\"/login\"><input name=\"password\"></form></body></html>";
+        assert_eq!(fingerprint(true, catch_all), None);
+        assert_eq!(fingerprint(true, ""), None);
+        assert_eq!(fingerprint(true, "42"), None);
+    }
+}
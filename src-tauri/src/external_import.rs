@@ -0,0 +1,726 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::mdc::{self, normalize_name};
+use crate::mdc_import;
+use crate::process::Process;
+use crate::recipe::{self, Recipe, Step, TemperatureDefaults};
+
+/// Keys that carry no recipe data and are dropped without being reported.
+const IGNORED_KEYS: [&str; 8] = [
+    "id",
+    "uuid",
+    "createdat",
+    "updatedat",
+    "modifiedat",
+    "version",
+    "order",
+    "index",
+];
+
+/// Keys a JSON export may put its recipe list under.
+const LIST_KEYS: [&str; 7] = [
+    "recipes",
+    "developments",
+    "presets",
+    "items",
+    "entries",
+    "data",
+    "records",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalFormat {
+    /// One recipe per row with film, developer, dilution and time columns.
+    Csv,
+    /// Generic JSON: a recipe object, a list of them, or a list under
+    /// `recipes`, `presets` and similar keys, with or without a `steps` list.
+    ///
+    /// The key names are common spellings, not any one app's schema; no timer
+    /// app's export has been checked against it. `testdata/external` holds the
+    /// shapes that are known to import.
+    Json,
+}
+
+impl ExternalFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExternalFormat::Csv),
+            "json" => Ok(ExternalFormat::Json),
+            other => Err(format!(
+                "Unknown import format '{}', expected csv or json",
+                other
+            )),
+        }
+    }
+
+    /// Pick the format from the file extension, falling back to the content.
+    pub fn detect(path: &str, content: &str) -> Self {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".json") {
+            ExternalFormat::Json
+        } else if lower.ends_with(".csv") || lower.ends_with(".tsv") {
+            ExternalFormat::Csv
+        } else if content
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .starts_with(['{', '['])
+        {
+            ExternalFormat::Json
+        } else {
+            ExternalFormat::Csv
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalIssue {
+    /// "row 3" for CSV, "recipe 2" for JSON.
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedRecipe {
+    pub source: String,
+    pub recipe_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalImportReport {
+    pub format: ExternalFormat,
+    pub imported: Vec<ImportedRecipe>,
+    pub skipped: Vec<ExternalIssue>,
+    /// Columns or keys that had no recipe field; their values are kept in the notes.
+    pub unmapped_fields: Vec<String>,
+}
+
+/// Recipes read from a file, not yet saved.
+#[derive(Debug, Clone, Default)]
+pub struct ExternalRecipes {
+    pub recipes: Vec<(String, Recipe)>,
+    pub skipped: Vec<ExternalIssue>,
+    pub unmapped_fields: BTreeSet<String>,
+}
+
+/// The app's step name for another app's step or phase label.
+pub fn standard_step_name(label: &str) -> Option<&'static str> {
+    let key = normalize_name(label);
+    let name = match key.as_str() {
+        "dev" | "develop" | "developer" | "developing" | "development" | "firstdeveloper"
+        | "colordeveloper" | "colourdeveloper" => "DEV",
+        "stop" | "stopbath" | "stopper" => "STOP",
+        "fix" | "fixer" | "fixing" | "fixative" => "FIX",
+        "rinse" | "wash" | "washing" | "finalwash" | "water" => "RINSE",
+        "pre" | "prewash" | "presoak" | "prerinse" => "PRE",
+        "blix" | "bleachfix" => "BLIX",
+        "stab" | "stabilizer" | "stabiliser" | "finalrinse" => "STAB",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// "Roll", "stick", "stand" and "none"/"off"; anything else is not mapped.
+fn standard_agitation(raw: &str) -> Option<&'static str> {
+    match normalize_name(raw).as_str() {
+        "roll" | "rotary" | "rotation" | "continuous" => Some("Roll"),
+        "stick" | "inversion" | "inversions" | "invert" => Some("Stick"),
+        "stand" | "semistand" => Some("Stand"),
+        "off" | "none" => Some("Off"),
+        _ => None,
+    }
+}
+
+/// Header or key in the form used for matching: lower case letters and digits.
+fn field_key(raw: &str) -> String {
+    raw.to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect()
+}
+
+/// Recipe fields gathered from one CSV row or JSON object before the steps are built.
+#[derive(Debug, Default)]
+struct Fields {
+    name: String,
+    film: String,
+    developer: String,
+    dilution: String,
+    category: String,
+    notes: Vec<String>,
+    dev_time: Option<mdc::DevTime>,
+    temperature: Option<f64>,
+    agitation: Option<&'static str>,
+    /// Times for STOP, FIX, RINSE, BLIX and STAB given as their own columns.
+    step_seconds: Vec<(&'static str, i64)>,
+}
+
+impl Fields {
+    /// Store `value` under the recipe field `key` names. Returns false when the
+    /// key is not a recipe field.
+    fn set(&mut self, key: &str, value: &str) -> bool {
+        let value = value.trim();
+        match key {
+            "name" | "recipe" | "recipename" | "title" | "label" => self.name = value.to_string(),
+            "film" | "filmstock" | "filmname" | "emulsion" | "stock" => {
+                self.film = value.to_string()
+            }
+            "developer" | "developername" | "dev" | "chemistry" => {
+                self.developer = value.to_string()
+            }
+            "dilution" | "ratio" => self.dilution = value.replace(':', "+").replace(' ', ""),
+            "process" | "category" | "type" => self.category = value.to_string(),
+            "notes" | "note" | "comment" | "comments" | "description" => {
+                if !value.is_empty() {
+                    self.notes.push(value.to_string());
+                }
+            }
+            "iso" | "ei" | "asa" | "asaiso" => {
+                if !value.is_empty() {
+                    self.notes.push(format!("EI {}", value));
+                }
+            }
+            "time" | "devtime" | "developmenttime" | "developingtime" | "duration" | "minutes" => {
+                self.dev_time = mdc::parse_dev_time(value)
+            }
+            "seconds" | "devtimeseconds" | "durationseconds" => {
+                self.dev_time = value.parse::<f64>().ok().map(|s| mdc::DevTime {
+                    baths: vec![s.round() as i64],
+                    note: None,
+                })
+            }
+            "temp" | "temperature" | "tempc" | "temperaturec" | "devtemp" => {
+                self.temperature = mdc::parse_temp_c(value)
+            }
+            "tempf" | "temperaturef" => {
+                let degrees = value.trim_end_matches(['F', 'f', '°']);
+                self.temperature = mdc::parse_temp_c(&format!("{}F", degrees))
+            }
+            "agitation" => self.agitation = standard_agitation(value),
+            "stop" | "stoptime" | "fix" | "fixtime" | "fixer" | "rinse" | "rinsetime" | "wash"
+            | "washtime" | "blix" | "blixtime" | "bleachfix" | "stab" | "stabtime"
+            | "stabilizer" | "stabiliser" => {
+                let step = standard_step_name(key.trim_end_matches("time")).unwrap_or("RINSE");
+                if let Some(time) = mdc::parse_dev_time(value) {
+                    self.step_seconds.push((step, time.baths.iter().sum()));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn unmapped(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() {
+            self.notes.push(format!("{}: {}", key.trim(), value));
+        }
+    }
+
+    /// A DEV/STOP/FIX/RINSE recipe, or the colour process's steps, with the
+    /// developer time and temperature filled in.
+    fn standard_steps(&self, temps: TemperatureDefaults) -> Vec<Step> {
+        let process = Process::from_category(&self.category);
+        if process.is_colour() {
+            return self.colour_steps(process);
+        }
+        let dev_temps = self.temperature.map_or(temps, |t| temps.rated_at(t));
+        let baths = self
+            .dev_time
+            .as_ref()
+            .map(|t| t.baths.clone())
+            .unwrap_or_else(|| vec![0]);
+
+        let mut steps = Vec::new();
+        for seconds in baths {
+            let mut dev = recipe::default_step("DEV", steps.len() as i64, dev_temps);
+            dev.set_total_seconds(seconds);
+            if let Some(agitation) = self.agitation {
+                dev.agitation = agitation.to_string();
+            }
+            steps.push(dev);
+        }
+        for name in ["STOP", "FIX", "RINSE"] {
+            let mut step = recipe::default_step(name, steps.len() as i64, temps);
+            if let Some((_, seconds)) = self.step_seconds.iter().find(|(n, _)| *n == name) {
+                step.set_total_seconds(*seconds);
+            }
+            steps.push(step);
+        }
+        steps
+    }
+
+    /// Colour programs keep the process temperatures, which the AGO checks
+    /// against the chemistry's tolerance; the time given is the first
+    /// developer's and a wash time goes to the final wash.
+    fn colour_steps(&self, process: Process) -> Vec<Step> {
+        let mut steps = recipe::colour_steps(process);
+        if let Some(dev) = steps.iter_mut().find(|s| s.name == "DEV") {
+            if let Some(time) = &self.dev_time {
+                dev.set_total_seconds(time.baths.iter().sum());
+            }
+            if let Some(agitation) = self.agitation {
+                dev.agitation = agitation.to_string();
+            }
+        }
+        for (name, seconds) in &self.step_seconds {
+            let name = if *name == "RINSE" { "WASH" } else { *name };
+            if let Some(step) = steps.iter_mut().rev().find(|s| s.name == name) {
+                step.set_total_seconds(*seconds);
+            }
+        }
+        steps
+    }
+
+    fn into_recipe(self, steps: Vec<Step>, fallback_name: &str) -> Result<Recipe, String> {
+        if steps.is_empty() {
+            return Err("no steps or development time".to_string());
+        }
+        if self.film.is_empty() && self.developer.is_empty() && self.name.is_empty() {
+            return Err("no name, film or developer".to_string());
+        }
+
+        let name = if !self.name.is_empty() {
+            self.name.clone()
+        } else {
+            let parts = [&self.film, &self.developer, &self.dilution];
+            let joined = parts
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| p.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if joined.is_empty() {
+                fallback_name.to_string()
+            } else {
+                joined
+            }
+        };
+
+        let mut recipe = recipe::new_recipe(&name);
+        recipe.film_stock = self.film;
+        recipe.developer = self.developer;
+        recipe.dilution = self.dilution;
+        if !self.category.is_empty() {
+            recipe.category = Process::from_category(&self.category)
+                .category()
+                .to_string();
+        }
+        let mut notes = self.notes;
+        if let Some(note) = self.dev_time.and_then(|t| t.note) {
+            notes.push(note);
+        }
+        recipe.notes = notes.join("\n");
+        recipe.steps = steps;
+        for (index, step) in recipe.steps.iter_mut().enumerate() {
+            step.sort_order = index as i64;
+        }
+        Ok(recipe)
+    }
+}
+
+fn detect_delimiter(header: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(',')
+}
+
+/// Read one recipe per CSV row. Comma, semicolon and tab separated files are
+/// accepted; the header decides which column is which.
+pub fn parse_csv(text: &str, temps: TemperatureDefaults) -> Result<ExternalRecipes, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let delimiter = detect_delimiter(text.lines().next().unwrap_or_default());
+    let mut records = mdc_import::parse_csv(text, delimiter).into_iter();
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| "CSV file is empty".to_string())?;
+
+    let keys = header.iter().map(|h| field_key(h)).collect::<Vec<_>>();
+    let mut probe = Fields::default();
+    let mapped = keys.iter().map(|k| probe.set(k, "")).collect::<Vec<_>>();
+    if !mapped.contains(&true) {
+        return Err("CSV header has no recognised recipe columns".to_string());
+    }
+
+    let mut out = ExternalRecipes::default();
+    for (column, mapped) in header.iter().zip(&mapped) {
+        if !mapped {
+            out.unmapped_fields.insert(column.trim().to_string());
+        }
+    }
+
+    for (line, row) in records {
+        let source = format!("row {}", line);
+        if row.len() > header.len() {
+            out.skipped.push(ExternalIssue {
+                source,
+                reason: format!("expected {} columns, found {}", header.len(), row.len()),
+            });
+            continue;
+        }
+
+        let mut fields = Fields::default();
+        for ((column, key), value) in header.iter().zip(&keys).zip(&row) {
+            if !fields.set(key, value) {
+                fields.unmapped(column, value);
+            }
+        }
+
+        let steps = fields.standard_steps(temps);
+        match fields.into_recipe(steps, &source) {
+            Ok(recipe) => out.recipes.push((source, recipe)),
+            Err(reason) => out.skipped.push(ExternalIssue { source, reason }),
+        }
+    }
+    Ok(out)
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Film and developer may be objects such as `{"brand": "Kodak", "name": "Tri-X"}`.
+fn name_text(value: &Value) -> Option<String> {
+    if let Some(text) = scalar_text(value) {
+        return Some(text);
+    }
+    let object = value.as_object()?;
+    let part = |key: &str| object.get(key).and_then(scalar_text).unwrap_or_default();
+    let joined = [part("brand"), part("manufacturer"), part("name")]
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(joined).filter(|j| !j.is_empty())
+}
+
+/// Step duration in seconds. Numbers are seconds unless the key says otherwise;
+/// strings may be "7:30" or decimal minutes.
+fn step_seconds(key: &str, value: &Value) -> Option<i64> {
+    let seconds = match value {
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            match key {
+                "durationms" | "milliseconds" | "timems" => n / 1000.0,
+                "minutes" | "durationminutes" | "timeminutes" => n * 60.0,
+                _ => n,
+            }
+        }
+        Value::String(s) => return mdc::parse_dev_time(s).map(|t| t.baths.iter().sum()),
+        _ => return None,
+    };
+    Some(seconds.round() as i64).filter(|s| *s >= 0)
+}
+
+/// One step of a recipe's step list. `dev_index` counts the DEV steps before
+/// this one, for the colour developer windows.
+fn json_step(
+    value: &Value,
+    index: usize,
+    dev_index: usize,
+    fields: &Fields,
+    temps: TemperatureDefaults,
+    unmapped: &mut BTreeSet<String>,
+) -> Result<Step, String> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("step {} is not an object", index + 1))?;
+
+    let label = ["name", "title", "label", "type", "step", "kind", "phase"]
+        .iter()
+        .find_map(|k| object.iter().find(|(key, _)| field_key(key) == *k))
+        .and_then(|(_, v)| scalar_text(v))
+        .unwrap_or_default();
+    if label.is_empty() {
+        return Err(format!("step {} has no name", index + 1));
+    }
+    let name = match standard_step_name(&label) {
+        Some(name) => name.to_string(),
+        None => {
+            unmapped.insert(format!("step name '{}'", label));
+            label.to_uppercase()
+        }
+    };
+
+    let process = Process::from_category(&fields.category);
+    let mut step = if process.is_colour() {
+        recipe::colour_step(&name, index as i64, process, dev_index)
+    } else {
+        let step_temps = match fields.temperature {
+            Some(t) if name == "DEV" => temps.rated_at(t),
+            _ => temps,
+        };
+        recipe::default_step(&name, index as i64, step_temps)
+    };
+    for (raw_key, value) in object {
+        let key = field_key(raw_key);
+        match key.as_str() {
+            "name" | "title" | "label" | "type" | "step" | "kind" | "phase" => {}
+            "duration" | "time" | "seconds" | "length" | "durationseconds" | "durationms"
+            | "milliseconds" | "timems" | "minutes" | "durationminutes" | "timeminutes" => {
+                if let Some(seconds) = step_seconds(&key, value) {
+                    step.set_total_seconds(seconds);
+                }
+            }
+            "temp" | "temperature" | "tempc" if process.is_colour() => {}
+            "temp" | "temperature" | "tempc" => {
+                if let Some(t) = scalar_text(value).and_then(|t| mdc::parse_temp_c(&t)) {
+                    let window = temps.rated_at(t);
+                    step.min_temperature = window.min;
+                    step.rated_temperature = window.rated;
+                    step.max_temperature = window.max;
+                }
+            }
+            "agitation" | "agitationtype" => {
+                match scalar_text(value).as_deref().and_then(standard_agitation) {
+                    Some(agitation) => step.agitation = agitation.to_string(),
+                    None => {
+                        unmapped.insert(format!("steps[].{}", raw_key));
+                    }
+                }
+            }
+            k if IGNORED_KEYS.contains(&k) => {}
+            _ => {
+                unmapped.insert(format!("steps[].{}", raw_key));
+            }
+        }
+    }
+    Ok(step)
+}
+
+fn json_recipe(
+    object: &Map<String, Value>,
+    source: &str,
+    temps: TemperatureDefaults,
+    unmapped: &mut BTreeSet<String>,
+) -> Result<Recipe, String> {
+    let mut fields = Fields::default();
+    let mut step_values = None;
+
+    for (raw_key, value) in object {
+        let key = field_key(raw_key);
+        match key.as_str() {
+            "steps" | "phases" | "stages" | "processsteps" | "timers" => {
+                step_values = value.as_array();
+                continue;
+            }
+            k if IGNORED_KEYS.contains(&k) => continue,
+            // Plain numbers are read as seconds, unlike the chart's minutes.
+            "time" | "devtime" | "developmenttime" | "duration" | "durationseconds"
+            | "durationms" | "minutes"
+                if value.is_number() =>
+            {
+                fields.dev_time = step_seconds(&key, value).map(|seconds| mdc::DevTime {
+                    baths: vec![seconds],
+                    note: None,
+                });
+                continue;
+            }
+            "film" | "filmstock" | "developer" => {
+                if let Some(text) = name_text(value) {
+                    fields.set(&key, &text);
+                    continue;
+                }
+            }
+            _ => {
+                if let Some(text) = scalar_text(value) {
+                    if fields.set(&key, &text) {
+                        continue;
+                    }
+                    fields.unmapped(raw_key, &text);
+                }
+            }
+        }
+        unmapped.insert(raw_key.to_string());
+    }
+
+    let steps = match step_values {
+        Some(values) if !values.is_empty() => {
+            let mut steps = Vec::with_capacity(values.len());
+            let mut dev_index = 0;
+            for (index, value) in values.iter().enumerate() {
+                let step = json_step(value, index, dev_index, &fields, temps, unmapped)?;
+                if step.name == "DEV" {
+                    dev_index += 1;
+                }
+                steps.push(step);
+            }
+            steps
+        }
+        _ => fields.standard_steps(temps),
+    };
+    fields.into_recipe(steps, source)
+}
+
+/// Read a generic JSON export: a recipe object, a list of them, or a list
+/// under a key such as `recipes` or `presets`. Recipes with a `steps` (or
+/// `phases`) list keep those steps; others get DEV/STOP/FIX/RINSE, or their
+/// colour process's steps, with the development time.
+pub fn parse_json(text: &str, temps: TemperatureDefaults) -> Result<ExternalRecipes, String> {
+    let value: Value = serde_json::from_str(text.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let items = match &value {
+        Value::Array(items) => items.clone(),
+        Value::Object(object) => object
+            .iter()
+            .find(|(key, value)| LIST_KEYS.contains(&field_key(key).as_str()) && value.is_array())
+            .and_then(|(_, value)| value.as_array().cloned())
+            .unwrap_or_else(|| vec![value.clone()]),
+        _ => return Err("JSON is not an object or a list".to_string()),
+    };
+
+    let mut out = ExternalRecipes::default();
+    for (index, item) in items.iter().enumerate() {
+        let source = format!("recipe {}", index + 1);
+        let result = match item.as_object() {
+            Some(object) => json_recipe(object, &source, temps, &mut out.unmapped_fields),
+            None => Err("not an object".to_string()),
+        };
+        match result {
+            Ok(recipe) => out.recipes.push((source, recipe)),
+            Err(reason) => out.skipped.push(ExternalIssue { source, reason }),
+        }
+    }
+    Ok(out)
+}
+
+pub fn parse(
+    format: ExternalFormat,
+    text: &str,
+    temps: TemperatureDefaults,
+) -> Result<ExternalRecipes, String> {
+    match format {
+        ExternalFormat::Csv => parse_csv(text, temps),
+        ExternalFormat::Json => parse_json(text, temps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ago_format::AgoProgramFile;
+
+    fn names(steps: &[Step]) -> Vec<&str> {
+        steps.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn sample_csv_maps_columns_and_reports_the_rest() {
+        let text = include_str!("../testdata/external/recipes.csv");
+        let out = parse_csv(text, TemperatureDefaults::default()).unwrap();
+
+        assert_eq!(out.unmapped_fields.into_iter().collect::<Vec<_>>(), ["Lab"]);
+        assert_eq!(out.skipped.len(), 1);
+        assert_eq!(out.skipped[0].source, "row 4");
+        assert_eq!(out.skipped[0].reason, "no name, film or developer");
+        assert_eq!(out.recipes.len(), 2);
+
+        let (source, tri_x) = &out.recipes[0];
+        assert_eq!(source, "row 2");
+        assert_eq!(tri_x.name, "Tri-X 400 D-76 1+1");
+        assert_eq!(tri_x.film_stock, "Tri-X 400");
+        assert_eq!(tri_x.developer, "D-76");
+        assert_eq!(tri_x.dilution, "1+1");
+        assert_eq!(tri_x.notes, "EI 400\nLab: Home");
+        assert_eq!(names(&tri_x.steps), ["DEV", "STOP", "FIX", "RINSE"]);
+        assert_eq!(tri_x.steps[0].total_seconds(), 585);
+        assert_eq!(tri_x.steps[0].agitation, "Stick");
+        assert_eq!(tri_x.steps[0].rated_temperature, 20.0);
+        assert_eq!(tri_x.steps[2].total_seconds(), 300);
+
+        let (_, hp5) = &out.recipes[1];
+        assert_eq!(hp5.steps[0].total_seconds(), 660);
+        assert_eq!(hp5.steps[0].agitation, "Stand");
+        assert_eq!(hp5.steps[0].rated_temperature, 21.0);
+        assert_eq!(hp5.notes, "EI 400\nLab: Club");
+    }
+
+    #[test]
+    fn sample_json_maps_steps_and_reports_the_rest() {
+        let text = include_str!("../testdata/external/recipes.json");
+        let out = parse_json(text, TemperatureDefaults::default()).unwrap();
+
+        assert_eq!(
+            out.unmapped_fields.into_iter().collect::<Vec<_>>(),
+            ["rating", "step name 'Hypo clear'", "steps[].color"]
+        );
+        assert_eq!(out.skipped.len(), 1);
+        assert_eq!(out.skipped[0].source, "recipe 3");
+        assert_eq!(out.recipes.len(), 2);
+
+        let (_, delta) = &out.recipes[0];
+        assert_eq!(delta.name, "Delta 100 in DDX");
+        assert_eq!(delta.film_stock, "Ilford Delta 100");
+        assert_eq!(delta.developer, "Ilfotec DDX");
+        assert_eq!(delta.dilution, "1+4");
+        assert_eq!(delta.notes, "rating: 5");
+        assert_eq!(
+            names(&delta.steps),
+            ["DEV", "STOP", "FIX", "HYPO CLEAR", "RINSE"]
+        );
+        let seconds = delta
+            .steps
+            .iter()
+            .map(Step::total_seconds)
+            .collect::<Vec<_>>();
+        assert_eq!(seconds, [600, 60, 300, 120, 600]);
+        assert_eq!(delta.steps[0].agitation, "Stick");
+        assert_eq!(delta.steps[2].rated_temperature, 21.0);
+
+        let (_, portra) = &out.recipes[1];
+        assert_eq!(portra.name, "Kodak Portra 400");
+        assert_eq!(portra.category, "C-41");
+        assert_eq!(names(&portra.steps), ["PRE", "DEV", "BLIX", "WASH", "STAB"]);
+        let dev = &portra.steps[1];
+        assert_eq!(dev.total_seconds(), 195);
+        assert_eq!(dev.rated_temperature, 37.8);
+        assert_eq!(dev.logo_text, "C-41 DEV");
+        assert!(dev.formula_designator.is_empty());
+        let program = AgoProgramFile::from_recipe(portra)
+            .to_device_program("_P_C0_test.txt", &portra.film_stock, "", "")
+            .unwrap();
+        assert_eq!(program.designator, "C41");
+    }
+
+    #[test]
+    fn colour_rows_keep_blix_and_stab_times() {
+        let text = "Film,Process,Time,Blix,Stab,Wash\n\
+                    Ektachrome E100,E-6,7,5:30,1,6\n\
+                    Portra 400,C-41,3:30,,0:45,\n";
+        let out = parse_csv(text, TemperatureDefaults::default()).unwrap();
+        assert!(out.skipped.is_empty());
+
+        let (_, ektachrome) = &out.recipes[0];
+        assert_eq!(
+            names(&ektachrome.steps),
+            ["PRE", "DEV", "WASH", "DEV", "BLIX", "WASH", "STAB"]
+        );
+        let seconds = ektachrome
+            .steps
+            .iter()
+            .map(Step::total_seconds)
+            .collect::<Vec<_>>();
+        assert_eq!(seconds, [300, 420, 150, 360, 330, 360, 60]);
+        assert_eq!(ektachrome.steps[3].max_temperature, 39.1);
+
+        let (_, portra) = &out.recipes[1];
+        assert_eq!(portra.steps[1].total_seconds(), 210);
+        assert_eq!(portra.steps[4].total_seconds(), 45);
+
+        for (_, recipe) in &out.recipes {
+            AgoProgramFile::from_recipe(recipe)
+                .to_device_program("_P_C0_test.txt", &recipe.film_stock, "", "")
+                .unwrap();
+        }
+    }
+}
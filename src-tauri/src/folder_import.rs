@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ago_format::{self, AgoProgramFile};
+use crate::catalog::{self, Catalog};
+use crate::recipe::Recipe;
+
+/// Folders nested deeper than this are not searched.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Imported,
+    Duplicate,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileImportResult {
+    /// Path relative to the imported folder.
+    pub path: String,
+    pub status: ImportStatus,
+    /// Id of the new recipe, or of the recipe it duplicates.
+    pub recipe_id: Option<String>,
+    pub recipe_name: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderImportReport {
+    pub folder: String,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub files: Vec<FileImportResult>,
+}
+
+impl FolderImportReport {
+    pub fn new(folder: &str, files: Vec<FileImportResult>) -> Self {
+        let count = |status| files.iter().filter(|f| f.status == status).count();
+        Self {
+            folder: folder.to_string(),
+            imported: count(ImportStatus::Imported),
+            duplicates: count(ImportStatus::Duplicate),
+            invalid: count(ImportStatus::Invalid),
+            files,
+        }
+    }
+}
+
+/// Exported `.json` program files and raw `_P_C*.txt` device programs.
+pub fn is_program_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let lower = name.to_lowercase();
+    lower.ends_with(".json") || (name.starts_with("_P_C") && lower.ends_with(".txt"))
+}
+
+/// Program files under `folder`, sorted by path. Hidden entries and symlinked
+/// folders are skipped.
+pub fn find_program_files(folder: &Path) -> Result<Vec<PathBuf>, String> {
+    fn walk(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read folder {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if hidden {
+                continue;
+            }
+            if file_type.is_dir() {
+                if depth < MAX_DEPTH {
+                    // Unreadable subfolders should not fail the whole import.
+                    let _ = walk(&path, depth + 1, out);
+                }
+            } else if is_program_file(&path) {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    if !folder.is_dir() {
+        return Err(format!("{} is not a folder", folder.display()));
+    }
+    let mut files = Vec::new();
+    walk(folder, 0, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Content hash used to spot a recipe that is already in the library.
+pub fn recipe_hash(recipe: &Recipe) -> String {
+    AgoProgramFile::from_recipe(recipe).content_hash()
+}
+
+/// Read one program file into an unsaved recipe, with the title split against
+/// the catalog. Programs with neither a name nor a title are named after the file.
+pub fn read_program_file(path: &Path, catalog: &Catalog) -> Result<Recipe, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let program = ago_format::parse_lenient(&content)?;
+    if program.steps.is_empty() {
+        return Err("Program has no steps".to_string());
+    }
+
+    let title = catalog.parse_title(&catalog::program_title_text(
+        &program.name,
+        &program.expanded_title,
+    ));
+    let mut recipe = program.to_recipe(title.best.parts());
+    if recipe.name.is_empty() {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        recipe.name = ago_format::sanitize_name_from_filename(stem);
+    }
+    Ok(recipe)
+}
+
+/// Hashes of the recipes already stored, mapped to their id and name.
+pub fn existing_hashes(recipes: &[Recipe]) -> HashMap<String, (String, String)> {
+    recipes
+        .iter()
+        .map(|r| (recipe_hash(r), (r.id.clone(), r.name.clone())))
+        .collect()
+}
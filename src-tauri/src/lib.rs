@@ -1,5 +1,29 @@
+mod ago_format;
+mod bundle;
+mod catalog;
+mod chemistry;
+mod collection;
 mod commands;
+mod db;
+mod deploy;
+mod device;
+mod discovery;
+mod external_import;
+mod folder_import;
+mod mdc;
+mod mdc_import;
 mod migrations;
+mod pdf;
+mod probe;
+mod process;
+mod recipe;
+mod recipe_card;
+mod recipe_export;
+mod secrets;
+mod share_code;
+mod supervisor;
+mod template;
+mod wifi_scan;
 
 use tauri::Manager;
 
@@ -8,7 +32,7 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(
             tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:ago_recipes.db", migrations::get_migrations())
+                .add_migrations(db::DB_URL, migrations::get_migrations())
                 .build(),
         )
         .plugin(tauri_plugin_dialog::init())
@@ -23,22 +47,115 @@ pub fn run() {
                 let window = app.get_webview_window("main").unwrap();
                 window.open_devtools();
             }
+
+            app.manage(supervisor::Supervisor::default());
+            tauri::async_runtime::spawn(supervisor::run(app.handle().clone()));
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let pool = match db::pool(&handle).await {
+                    Ok(pool) => pool,
+                    Err(e) => {
+                        log::error!("Failed to open database: {}", e);
+                        return;
+                    }
+                };
+                let moved = match secrets::default_store() {
+                    Ok(store) => secrets::migrate_plaintext_password(&pool, store.as_ref()).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = moved {
+                    log::error!("Failed to move the WiFi password to the secret store: {}", e);
+                }
+                if let Err(e) = mdc::ensure_seeded(&pool).await {
+                    log::error!("Failed to seed Massive Dev Chart: {}", e);
+                }
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::wifi::wifi_get_interface,
             commands::wifi::wifi_get_current_network,
+            commands::wifi::wifi_scan_networks,
             commands::wifi::wifi_connect,
             commands::wifi::wifi_reconnect,
+            commands::wifi::get_connection_state,
+            commands::wifi::restore_previous_network,
+            commands::wifi::wifi_set_password,
+            commands::wifi::wifi_has_password,
             commands::wifi::wifi_probe_ago,
+            commands::wifi::probe_device,
+            commands::wifi::get_device_probe,
+            commands::devices::list_devices,
+            commands::devices::get_device,
+            commands::devices::create_device,
+            commands::devices::update_device,
+            commands::devices::delete_device,
+            commands::discovery::discover_devices,
+            commands::collections::list_collections,
+            commands::collections::create_collection,
+            commands::collections::update_collection,
+            commands::collections::delete_collection,
+            commands::deploy::deploy_collection,
+            commands::device_session::run_device_session,
+            commands::export::recipe_to_ago_json,
+            commands::export::parse_ago_json,
             commands::export::export_recipe_file,
+            commands::export::export_recipe_pdf,
+            commands::export::export_recipe_qr,
+            commands::export::recipe_share_code,
             commands::export::import_recipe_file,
+            commands::export::import_recipe_folder,
+            commands::export::import_share_code,
+            commands::bundle::bundle_public_key,
+            commands::bundle::export_recipe_bundle,
+            commands::bundle::import_recipe_bundle,
+            commands::bundle::trust_bundle_key,
+            commands::bundle::untrust_bundle_key,
+            commands::export::import_external_recipes,
             commands::export::upload_recipe_file,
             commands::export::delete_ago_program,
             commands::export::list_ago_programs,
             commands::export::get_upload_debug_log,
             commands::export::clear_upload_debug_log,
+            commands::sessions::log_dev_session,
+            commands::sessions::list_dev_sessions,
+            commands::sessions::delete_dev_session,
+            commands::sessions::get_recipe_session_summary,
+            commands::inventory::list_chemicals,
+            commands::inventory::add_chemical,
+            commands::inventory::update_chemical,
+            commands::inventory::delete_chemical,
+            commands::inventory::check_inventory,
+            commands::mdc::search_mdc,
+            commands::mdc::get_mdc_entry,
+            commands::mdc::list_mdc_films,
+            commands::mdc::list_mdc_developers,
+            commands::mdc::create_recipe_from_mdc,
+            commands::mdc::import_mdc_csv,
+            commands::mdc::list_mdc_snapshots,
+            commands::mdc::acknowledge_mdc_change,
+            commands::chemistry::parse_dilution,
+            commands::chemistry::calculate_chemistry,
+            commands::chemistry::list_tank_volumes,
+            commands::chemistry::validate_recipe_chemistry,
+            commands::templates::list_templates,
+            commands::templates::get_template,
+            commands::templates::create_template,
+            commands::templates::update_template,
+            commands::templates::delete_template,
+            commands::templates::duplicate_template,
+            commands::templates::create_recipe_from_template,
+            commands::catalog::parse_program_title,
+            commands::catalog::list_catalog_names,
+            commands::catalog::add_catalog_name,
+            commands::catalog::delete_catalog_name,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                supervisor::restore_on_exit(app);
+            }
+        });
 }
@@ -0,0 +1,997 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, QueryBuilder, Sqlite};
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+
+use crate::catalog::{bigrams, dice};
+use crate::recipe::{self, Recipe, Step, TemperatureDefaults};
+
+/// The bundled chart, used to seed an empty `mdc_entries` table. The frontend
+/// reads the chart only through the search commands.
+const BUNDLED_CHART: &str = include_str!("../../src/data/massive_dev_chart.json");
+
+static SEED_LOCK: Mutex<()> = Mutex::const_new(());
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Lowest [`name_similarity`] for a chart name to count as a fuzzy match.
+const FUZZY_MIN_SIMILARITY: f64 = 0.6;
+/// Fuzzy matches searched per name, most similar first.
+const FUZZY_MAX_NAMES: usize = 20;
+
+/// One row of the chart as it appears in the source data.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MdcRow {
+    pub film: String,
+    pub developer: String,
+    pub dilution: String,
+    pub iso: String,
+    pub time_35mm: String,
+    pub time_120: String,
+    pub time_sheet: String,
+    pub temp_c: String,
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MdcEntry {
+    pub id: i64,
+    pub film: String,
+    pub developer: String,
+    pub dilution: String,
+    pub iso: String,
+    pub time_35mm: String,
+    pub time_120: String,
+    pub time_sheet: String,
+    pub temp_c: String,
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MdcQuery {
+    #[serde(default)]
+    pub film: Option<String>,
+    #[serde(default)]
+    pub developer: Option<String>,
+    #[serde(default)]
+    pub dilution: Option<String>,
+    #[serde(default)]
+    pub iso: Option<i64>,
+    /// Development temperature in °C.
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Zero-based page index.
+    #[serde(default)]
+    pub page: Option<i64>,
+    #[serde(default)]
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MdcPage {
+    pub entries: Vec<MdcEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+pub const ENTRY_COLUMNS: &str =
+    "id, film, developer, dilution, iso, time_35mm, time_120, time_sheet, temp_c, notes";
+
+/// Reduce a film or developer name to a comparison key, so "HP5+", "HP5 Plus" and
+/// "hp5plus" all compare equal, as do "510-Pyro" and "510 Pyro".
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace("plus", "+")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '+')
+        .collect()
+}
+
+/// "1:1", "1 + 1" and "1+1" share a key; "Stock" becomes "stock".
+pub fn normalize_dilution(dilution: &str) -> String {
+    dilution
+        .to_lowercase()
+        .replace(':', "+")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Parse the chart's temperature column ("20C", "20.5C", "68F", "20") into °C.
+pub fn parse_temp_c(raw: &str) -> Option<f64> {
+    let trimmed = raw.trim().trim_end_matches('°');
+    let upper = trimmed.to_uppercase();
+    if let Some(f) = upper.strip_suffix('F') {
+        let f = f.trim().trim_end_matches('°').parse::<f64>().ok()?;
+        return Some(((f - 32.0) * 5.0 / 9.0 * 10.0).round() / 10.0);
+    }
+    upper
+        .strip_suffix('C')
+        .unwrap_or(&upper)
+        .trim()
+        .trim_end_matches('°')
+        .parse::<f64>()
+        .ok()
+}
+
+/// Leading box speed of the ISO column; pushed/pulled ratings like "50(25)" use the first value.
+pub fn parse_iso(raw: &str) -> Option<i64> {
+    let digits = raw
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+/// Identifies a chart row across snapshots: film, developer, dilution and rated speed.
+pub fn row_key(film: &str, developer: &str, dilution: &str, iso: &str) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        normalize_name(film),
+        normalize_name(developer),
+        normalize_dilution(dilution),
+        iso.trim()
+    )
+}
+
+/// A chart time converted to seconds. Two-bath developers ("3+3") have one entry per bath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevTime {
+    pub baths: Vec<i64>,
+    pub note: Option<String>,
+}
+
+fn minutes_to_seconds(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    if let Some((min, sec)) = raw.split_once(':') {
+        let min = min.trim().parse::<i64>().ok()?;
+        let sec = sec.trim().parse::<i64>().ok()?;
+        return Some(min * 60 + sec);
+    }
+    let minutes = raw.parse::<f64>().ok().filter(|m| *m > 0.0)?;
+    Some((minutes * 60.0).round() as i64)
+}
+
+/// Parse a chart time column: "11.75" and "8.5" are decimal minutes, "7:42" is
+/// min:sec, "7-9" is a range (the midpoint is used), "3+3" is a two-bath time.
+/// Footnote markers ("34*") are ignored and "#" means no time is given.
+pub fn parse_dev_time(raw: &str) -> Option<DevTime> {
+    let cleaned = raw.trim().trim_end_matches('*').trim().replace(',', ".");
+    if cleaned.is_empty() || cleaned == "#" {
+        return None;
+    }
+
+    if cleaned.contains('+') {
+        let baths = cleaned
+            .split('+')
+            .map(minutes_to_seconds)
+            .collect::<Option<Vec<_>>>()?;
+        return Some(DevTime {
+            baths,
+            note: Some(format!("Two-bath time {} min", cleaned)),
+        });
+    }
+
+    if let Some((low, high)) = cleaned.split_once('-') {
+        let low = minutes_to_seconds(low)?;
+        let high = minutes_to_seconds(high)?;
+        return Some(DevTime {
+            baths: vec![(low + high) / 2],
+            note: Some(format!("Chart gives {} min; using the midpoint", cleaned)),
+        });
+    }
+
+    Some(DevTime {
+        baths: vec![minutes_to_seconds(&cleaned)?],
+        note: None,
+    })
+}
+
+/// Build a recipe from a chart entry for one film format ("35mm", "120" or "sheet"),
+/// starting from `skeleton`. The skeleton's DEV steps take the chart's baths in
+/// order; the last DEV step repeats for any baths left over.
+pub fn recipe_from_entry(
+    entry: &MdcEntry,
+    format: &str,
+    skeleton: &[Step],
+) -> Result<Recipe, String> {
+    let columns = [
+        ("35mm", entry.time_35mm.as_str()),
+        ("120", entry.time_120.as_str()),
+        ("sheet", entry.time_sheet.as_str()),
+    ];
+    let format = format.trim().to_lowercase();
+    let Some(&(_, requested)) = columns.iter().find(|(name, _)| *name == format) else {
+        return Err(format!(
+            "Unknown film format '{}', expected 35mm, 120 or sheet",
+            format
+        ));
+    };
+
+    let mut notes = Vec::new();
+    let time = match parse_dev_time(requested) {
+        Some(time) => time,
+        None => {
+            // The chart often lists only one format; fall back to the first one it has.
+            let (fallback_format, time) = columns
+                .iter()
+                .find_map(|(name, raw)| parse_dev_time(raw).map(|t| (*name, t)))
+                .ok_or_else(|| {
+                    format!(
+                        "No development time in the chart for {} in {}",
+                        entry.film, entry.developer
+                    )
+                })?;
+            notes.push(format!(
+                "No {} time in the chart; using the {} time",
+                format, fallback_format
+            ));
+            time
+        }
+    };
+    notes.extend(time.note.clone());
+
+    let chart_temp = parse_temp_c(&entry.temp_c);
+    let dev_slots = skeleton.iter().filter(|s| s.name == "DEV").count();
+
+    let mut steps = Vec::new();
+    let mut dev_index = 0;
+    for template_step in skeleton {
+        if template_step.name != "DEV" {
+            steps.push(template_step.clone());
+            continue;
+        }
+
+        let mut dev_step = template_step.clone();
+        if let Some(rated) = chart_temp {
+            let window = TemperatureDefaults {
+                min: dev_step.min_temperature,
+                rated: dev_step.rated_temperature,
+                max: dev_step.max_temperature,
+            }
+            .rated_at(rated);
+            dev_step.min_temperature = window.min;
+            dev_step.rated_temperature = window.rated;
+            dev_step.max_temperature = window.max;
+        }
+
+        let end = if dev_index + 1 == dev_slots {
+            time.baths.len()
+        } else {
+            dev_index + 1
+        };
+        let baths = time.baths.get(dev_index..end).unwrap_or_default();
+        if baths.is_empty() {
+            steps.push(dev_step.clone());
+        }
+        for seconds in baths {
+            let mut step = dev_step.clone();
+            step.set_total_seconds(*seconds);
+            steps.push(step);
+        }
+        dev_index += 1;
+    }
+    if !steps.iter().any(|s| s.name == "DEV") {
+        return Err("Template has no DEV step to carry the chart time".to_string());
+    }
+
+    let mut header = format!("Massive Dev Chart: {} @ ISO {}", format, entry.iso);
+    if !entry.notes.trim().is_empty() && entry.notes.trim() != "[notes]" {
+        header.push_str(&format!(" ({})", entry.notes.trim()));
+    }
+    notes.insert(0, header);
+
+    let name = format!("{} {} {}", entry.film, entry.developer, entry.dilution);
+    let mut recipe = recipe::new_recipe(name.trim());
+    recipe.film_stock = entry.film.clone();
+    recipe.developer = entry.developer.clone();
+    recipe.dilution = entry.dilution.clone();
+    recipe.notes = notes.join("\n");
+    recipe.steps = steps;
+    Ok(recipe)
+}
+
+fn name_tokens(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(normalize_name)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+pub fn bundled_rows() -> Result<Vec<MdcRow>, String> {
+    serde_json::from_str(BUNDLED_CHART).map_err(|e| format!("Bundled chart is invalid: {}", e))
+}
+
+/// Only the newest snapshot is searched; older ones are kept for change reports.
+const CURRENT_SNAPSHOT: &str = "snapshot_id = (SELECT MAX(id) FROM mdc_snapshots)";
+
+/// Create a snapshot record and insert its rows with their search keys.
+pub async fn insert_snapshot(
+    conn: &mut sqlx::SqliteConnection,
+    label: &str,
+    source: &str,
+    rows: &[MdcRow],
+) -> Result<i64, String> {
+    let snapshot_id = sqlx::query(
+        "INSERT INTO mdc_snapshots (label, source, row_count, imported_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(label)
+    .bind(source)
+    .bind(rows.len() as i64)
+    .bind(crate::db::now_iso())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to create chart snapshot: {}", e))?
+    .last_insert_rowid();
+
+    for row in rows {
+        sqlx::query(
+            "INSERT INTO mdc_entries (snapshot_id, row_key, film, developer, dilution, iso, time_35mm, time_120, time_sheet, temp_c, notes, film_key, developer_key, dilution_key, iso_value, temp_value)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(snapshot_id)
+        .bind(row_key(&row.film, &row.developer, &row.dilution, &row.iso))
+        .bind(&row.film)
+        .bind(&row.developer)
+        .bind(&row.dilution)
+        .bind(&row.iso)
+        .bind(&row.time_35mm)
+        .bind(&row.time_120)
+        .bind(&row.time_sheet)
+        .bind(&row.temp_c)
+        .bind(&row.notes)
+        .bind(normalize_name(&row.film))
+        .bind(normalize_name(&row.developer))
+        .bind(normalize_dilution(&row.dilution))
+        .bind(parse_iso(&row.iso))
+        .bind(parse_temp_c(&row.temp_c))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to insert chart row: {}", e))?;
+    }
+    Ok(snapshot_id)
+}
+
+/// Load the bundled chart into SQLite the first time it is needed.
+pub async fn ensure_seeded(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let _guard = SEED_LOCK.lock().await;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mdc_snapshots")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to count chart snapshots: {}", e))?;
+    if count > 0 {
+        return Ok(());
+    }
+
+    let rows = bundled_rows()?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    insert_snapshot(&mut tx, "Bundled chart", "bundled", &rows).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to seed chart: {}", e))?;
+
+    log::info!("Seeded Massive Dev Chart with {} rows", rows.len());
+    Ok(())
+}
+
+/// Bigram Dice similarity of two normalized words; equal words score 1.0 even
+/// when they are too short to have bigrams ("x", "5").
+fn word_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        1.0
+    } else {
+        dice(&bigrams(a), &bigrams(b))
+    }
+}
+
+/// Token-level similarity of a query to a chart name, both as [`name_tokens`].
+/// Each word scores against its closest word on the other side, and the
+/// query's words weigh most: a typo ("Deltta 100") still finds "Ilford Delta
+/// 100 Pro", and a maker the chart leaves out ("Kodak Xtol") still finds "Xtol".
+fn name_similarity(query: &[String], name: &[String]) -> f64 {
+    if query.is_empty() || name.is_empty() {
+        return 0.0;
+    }
+    let coverage = |words: &[String], others: &[String]| {
+        words
+            .iter()
+            .map(|word| {
+                others
+                    .iter()
+                    .map(|other| word_similarity(word, other))
+                    .fold(0.0, f64::max)
+            })
+            .sum::<f64>()
+            / words.len() as f64
+    };
+    0.7 * coverage(query, name) + 0.3 * coverage(name, query)
+}
+
+/// Keys of the chart names most similar to `text`, best first. `None` when
+/// `text` is empty, when some name already contains every word of it (the
+/// substring filter finds those), or when no name is similar enough.
+fn fuzzy_keys(names: &[String], text: &str) -> Option<Vec<String>> {
+    let words = name_tokens(text);
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut scored = Vec::new();
+    for name in names {
+        let key = normalize_name(name);
+        if words.iter().all(|word| key.contains(word.as_str())) {
+            return None;
+        }
+        let score = name_similarity(&words, &name_tokens(name));
+        if score >= FUZZY_MIN_SIMILARITY {
+            scored.push((score, key));
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut seen = HashSet::new();
+    let keys = scored
+        .into_iter()
+        .filter(|(_, key)| seen.insert(key.clone()))
+        .map(|(_, key)| key)
+        .take(FUZZY_MAX_NAMES)
+        .collect::<Vec<_>>();
+    (!keys.is_empty()).then_some(keys)
+}
+
+/// Film and developer keys picked by [`fuzzy_keys`], used in place of the
+/// substring filter when it matches nothing.
+#[derive(Debug, Default)]
+struct FuzzyNames {
+    film: Option<Vec<String>>,
+    developer: Option<Vec<String>>,
+}
+
+fn push_name_filter(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    column: &str,
+    text: Option<&str>,
+    fuzzy: Option<&Vec<String>>,
+) {
+    match fuzzy {
+        Some(keys) => {
+            builder.push(format!(" AND {} IN (", column));
+            let mut separated = builder.separated(", ");
+            for key in keys {
+                separated.push_bind(key.clone());
+            }
+            builder.push(")");
+        }
+        None => {
+            for token in name_tokens(text.unwrap_or_default()) {
+                builder
+                    .push(format!(" AND {} LIKE ", column))
+                    .push_bind(format!("%{}%", token));
+            }
+        }
+    }
+}
+
+/// Order by how well `column` matches: fuzzy matches by similarity, otherwise
+/// exact names above prefix matches above substring matches.
+fn push_name_rank(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    column: &str,
+    text: Option<&str>,
+    fuzzy: Option<&Vec<String>>,
+) {
+    match fuzzy {
+        Some(keys) => {
+            builder.push(format!("CASE {}", column));
+            for (rank, key) in keys.iter().enumerate() {
+                builder
+                    .push(" WHEN ")
+                    .push_bind(key.clone())
+                    .push(format!(" THEN {}", rank));
+            }
+            builder.push(" END, ");
+        }
+        None => {
+            let key = text.map(normalize_name).unwrap_or_default();
+            builder
+                .push(format!("({} = ", column))
+                .push_bind(key.clone())
+                .push(format!(") DESC, ({} LIKE ", column))
+                .push_bind(format!("{}%", key))
+                .push(") DESC, ");
+        }
+    }
+}
+
+fn push_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &MdcQuery, fuzzy: &FuzzyNames) {
+    builder.push(" WHERE ").push(CURRENT_SNAPSHOT);
+
+    push_name_filter(
+        builder,
+        "film_key",
+        query.film.as_deref(),
+        fuzzy.film.as_ref(),
+    );
+    push_name_filter(
+        builder,
+        "developer_key",
+        query.developer.as_deref(),
+        fuzzy.developer.as_ref(),
+    );
+    if let Some(dilution) = query
+        .dilution
+        .as_deref()
+        .map(normalize_dilution)
+        .filter(|d| !d.is_empty())
+    {
+        builder.push(" AND dilution_key = ").push_bind(dilution);
+    }
+    if let Some(iso) = query.iso {
+        builder.push(" AND iso_value = ").push_bind(iso);
+    }
+    if let Some(temperature) = query.temperature {
+        // Chart temperatures are whole or half degrees; allow for rounding.
+        builder
+            .push(" AND ABS(temp_value - ")
+            .push_bind(temperature)
+            .push(") < 0.26");
+    }
+}
+
+async fn count_matches(
+    pool: &Pool<Sqlite>,
+    query: &MdcQuery,
+    fuzzy: &FuzzyNames,
+) -> Result<i64, String> {
+    let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM mdc_entries");
+    push_filters(&mut count, query, fuzzy);
+    count
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to search chart: {}", e))
+}
+
+/// Search the current snapshot one page at a time.
+///
+/// Film and developer queries are split on whitespace, each word goes through
+/// [`normalize_name`], and every word must appear in the row's normalized name.
+/// That covers case, punctuation and "Plus"/"+" ("HP5 Plus" finds "HP5+",
+/// "trix" finds "Tri-X"). When that finds nothing, names whose words resemble
+/// the query's are searched instead ([`name_similarity`]), which covers typos
+/// ("Deltta") and extra words the chart leaves out ("Kodak Xtol" finds "Xtol").
+pub async fn search(pool: &Pool<Sqlite>, query: &MdcQuery) -> Result<MdcPage, String> {
+    ensure_seeded(pool).await?;
+
+    let page = query.page.unwrap_or(0).max(0);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let mut fuzzy = FuzzyNames::default();
+    let mut total = count_matches(pool, query, &fuzzy).await?;
+    if total == 0 {
+        let film = query.film.as_deref().unwrap_or_default();
+        let developer = query.developer.as_deref().unwrap_or_default();
+        fuzzy.film = fuzzy_keys(&all_names(pool, "film").await?, film);
+        fuzzy.developer = fuzzy_keys(&all_names(pool, "developer").await?, developer);
+        if fuzzy.film.is_some() || fuzzy.developer.is_some() {
+            total = count_matches(pool, query, &fuzzy).await?;
+        }
+    }
+
+    let mut select = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM mdc_entries", ENTRY_COLUMNS));
+    push_filters(&mut select, query, &fuzzy);
+    select.push(" ORDER BY ");
+    push_name_rank(
+        &mut select,
+        "film_key",
+        query.film.as_deref(),
+        fuzzy.film.as_ref(),
+    );
+    push_name_rank(
+        &mut select,
+        "developer_key",
+        query.developer.as_deref(),
+        fuzzy.developer.as_ref(),
+    );
+    select
+        .push("film, developer, dilution_key, iso_value LIMIT ")
+        .push_bind(page_size)
+        .push(" OFFSET ")
+        .push_bind(page * page_size);
+
+    let entries = select
+        .build_query_as::<MdcEntry>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to search chart: {}", e))?;
+
+    Ok(MdcPage {
+        entries,
+        total,
+        page,
+        page_size,
+    })
+}
+
+pub async fn get_entry(pool: &Pool<Sqlite>, id: i64) -> Result<MdcEntry, String> {
+    ensure_seeded(pool).await?;
+
+    sqlx::query_as::<_, MdcEntry>(&format!(
+        "SELECT {} FROM mdc_entries WHERE id = ?",
+        ENTRY_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load chart entry: {}", e))?
+    .ok_or_else(|| format!("Chart entry {} not found", id))
+}
+
+/// Distinct film or developer names matching `query`, for autocomplete.
+pub async fn distinct_names(
+    pool: &Pool<Sqlite>,
+    column: &str,
+    query: &str,
+    film: Option<&str>,
+    limit: i64,
+) -> Result<Vec<String>, String> {
+    ensure_seeded(pool).await?;
+
+    let key_column = match column {
+        "film" => "film_key",
+        "developer" => "developer_key",
+        _ => return Err(format!("Unknown chart column {}", column)),
+    };
+
+    let mut builder = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT DISTINCT {} FROM mdc_entries WHERE {}",
+        column, CURRENT_SNAPSHOT
+    ));
+    for token in name_tokens(query) {
+        builder
+            .push(format!(" AND {} LIKE ", key_column))
+            .push_bind(format!("%{}%", token));
+    }
+    if let Some(film) = film.filter(|f| !f.trim().is_empty()) {
+        builder.push(" AND film = ").push_bind(film.to_string());
+    }
+    builder
+        .push(format!(" ORDER BY {} LIMIT ", column))
+        .push_bind(limit.clamp(1, MAX_PAGE_SIZE));
+
+    builder
+        .build_query_scalar()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list chart names: {}", e))
+}
+
+/// Every film or developer name in the current snapshot, for the title catalog.
+pub async fn all_names(pool: &Pool<Sqlite>, column: &str) -> Result<Vec<String>, String> {
+    ensure_seeded(pool).await?;
+
+    if column != "film" && column != "developer" {
+        return Err(format!("Unknown chart column {}", column));
+    }
+    sqlx::query_scalar(&format!(
+        "SELECT DISTINCT {} FROM mdc_entries WHERE {}",
+        column, CURRENT_SNAPSHOT
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list chart names: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time_35mm: &str, time_120: &str, time_sheet: &str) -> MdcEntry {
+        MdcEntry {
+            id: 1,
+            film: "Tri-X 400".to_string(),
+            developer: "D-76".to_string(),
+            dilution: "1+1".to_string(),
+            iso: "400".to_string(),
+            time_35mm: time_35mm.to_string(),
+            time_120: time_120.to_string(),
+            time_sheet: time_sheet.to_string(),
+            temp_c: "20C".to_string(),
+            notes: "[notes]".to_string(),
+        }
+    }
+
+    fn skeleton() -> Vec<Step> {
+        let temps = TemperatureDefaults::default();
+        recipe::DEFAULT_TEMPLATE_STEPS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| recipe::default_step(name, i as i64, temps))
+            .collect()
+    }
+
+    fn dev_seconds(recipe: &Recipe) -> Vec<i64> {
+        recipe
+            .steps
+            .iter()
+            .filter(|s| s.name == "DEV")
+            .map(Step::total_seconds)
+            .collect()
+    }
+
+    #[test]
+    fn parses_chart_times() {
+        let time = |raw: &str| parse_dev_time(raw).map(|t| t.baths);
+        assert_eq!(time("11.75"), Some(vec![705]));
+        assert_eq!(time("8,5"), Some(vec![510]));
+        assert_eq!(time("7:42"), Some(vec![462]));
+        assert_eq!(time("34*"), Some(vec![2040]));
+        assert_eq!(time("#"), None);
+        assert_eq!(time(""), None);
+        assert_eq!(time("n/a"), None);
+
+        assert_eq!(
+            parse_dev_time("7-9"),
+            Some(DevTime {
+                baths: vec![480],
+                note: Some("Chart gives 7-9 min; using the midpoint".to_string()),
+            })
+        );
+        assert_eq!(time("6.5-7"), Some(vec![405]));
+        assert_eq!(
+            parse_dev_time("3+3"),
+            Some(DevTime {
+                baths: vec![180, 180],
+                note: Some("Two-bath time 3+3 min".to_string()),
+            })
+        );
+        assert_eq!(time("4+x"), None);
+    }
+
+    #[test]
+    fn recipe_takes_the_requested_format() {
+        let recipe = recipe_from_entry(&entry("9.75", "11", "#"), "120", &skeleton()).unwrap();
+        assert_eq!(recipe.name, "Tri-X 400 D-76 1+1");
+        assert_eq!(dev_seconds(&recipe), [660]);
+        assert_eq!(recipe.notes, "Massive Dev Chart: 120 @ ISO 400");
+        assert_eq!(recipe.steps[0].rated_temperature, 20.0);
+    }
+
+    #[test]
+    fn missing_format_falls_back_to_the_first_time_given() {
+        let recipe = recipe_from_entry(&entry("9.75", "", "#"), "sheet", &skeleton()).unwrap();
+        assert_eq!(dev_seconds(&recipe), [585]);
+        assert_eq!(
+            recipe.notes,
+            "Massive Dev Chart: sheet @ ISO 400\nNo sheet time in the chart; using the 35mm time"
+        );
+
+        let recipe = recipe_from_entry(&entry("", "", "12-14"), "120", &skeleton()).unwrap();
+        assert_eq!(dev_seconds(&recipe), [780]);
+
+        let error = recipe_from_entry(&entry("#", "", ""), "35mm", &skeleton()).unwrap_err();
+        assert_eq!(
+            error,
+            "No development time in the chart for Tri-X 400 in D-76"
+        );
+        assert!(recipe_from_entry(&entry("9", "", ""), "110", &skeleton()).is_err());
+    }
+
+    #[test]
+    fn two_bath_times_fill_the_dev_steps() {
+        let mut chart = entry("3+3", "", "");
+        chart.temp_c = "24C".to_string();
+
+        // One DEV slot: the last DEV step repeats for the second bath.
+        let recipe = recipe_from_entry(&chart, "35mm", &skeleton()).unwrap();
+        let names = recipe
+            .steps
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["DEV", "DEV", "STOP", "FIX", "RINSE"]);
+        assert_eq!(dev_seconds(&recipe), [180, 180]);
+        assert_eq!(recipe.steps[1].rated_temperature, 24.0);
+        assert_eq!(recipe.steps[1].max_temperature, 28.0);
+        assert_eq!(recipe.steps[2].rated_temperature, 20.0);
+        assert!(recipe.notes.ends_with("Two-bath time 3+3 min"));
+
+        let no_dev = skeleton().into_iter().skip(1).collect::<Vec<_>>();
+        assert!(recipe_from_entry(&chart, "35mm", &no_dev).is_err());
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// An in-memory database holding `rows` as the current chart snapshot.
+    async fn chart_pool(rows: &[(&str, &str, &str)]) -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for migration in crate::migrations::get_migrations() {
+            sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        }
+        let rows = rows
+            .iter()
+            .map(|(film, developer, dilution)| MdcRow {
+                film: film.to_string(),
+                developer: developer.to_string(),
+                dilution: dilution.to_string(),
+                iso: "400".to_string(),
+                time_35mm: "10".to_string(),
+                time_120: String::new(),
+                time_sheet: String::new(),
+                temp_c: "20C".to_string(),
+                notes: String::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut conn = pool.acquire().await.unwrap();
+        insert_snapshot(&mut conn, "Test chart", "test", &rows)
+            .await
+            .unwrap();
+        drop(conn);
+        pool
+    }
+
+    fn names(page: &MdcPage) -> Vec<(&str, &str)> {
+        page.entries
+            .iter()
+            .map(|e| (e.film.as_str(), e.developer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_names() {
+        for name in ["HP5+", "HP5 Plus", "hp5plus", " Hp5-PLUS "] {
+            assert_eq!(normalize_name(name), "hp5+", "{}", name);
+        }
+        assert_eq!(normalize_name("510-Pyro"), normalize_name("510 Pyro"));
+        assert_eq!(normalize_name("Kodak Tri-X 400"), "kodaktrix400");
+        assert_eq!(normalize_name("Fomapan 100 Classic"), "fomapan100classic");
+    }
+
+    #[test]
+    fn fuzzy_keys_cover_typos_and_extra_words() {
+        let rows = bundled_rows().unwrap();
+        let films = rows.iter().map(|r| r.film.clone()).collect::<Vec<_>>();
+        let developers = rows.iter().map(|r| r.developer.clone()).collect::<Vec<_>>();
+        let best = |names: &[String], text: &str| fuzzy_keys(names, text).map(|k| k[0].clone());
+
+        // Words that already match as substrings stay with the plain filter.
+        assert_eq!(fuzzy_keys(&films, "HP5 Plus"), None);
+        assert_eq!(fuzzy_keys(&developers, "rodinal"), None);
+        assert_eq!(fuzzy_keys(&films, " "), None);
+
+        assert_eq!(
+            best(&films, "Deltta 100").as_deref(),
+            Some("ilforddelta100pro")
+        );
+        assert_eq!(best(&developers, "Kodak Xtol").as_deref(), Some("xtol"));
+        assert_eq!(best(&developers, "Rodinall").as_deref(), Some("rodinal"));
+        assert_eq!(fuzzy_keys(&developers, "Qwzv"), None);
+    }
+
+    #[test]
+    fn name_similarity_weighs_the_query_words() {
+        let tokens = |text: &str| name_tokens(text);
+        let score = |query: &str, name: &str| name_similarity(&tokens(query), &tokens(name));
+
+        assert_eq!(score("Tri-X 400", "Tri-X 400"), 1.0);
+        assert!(score("Kodak Xtol", "Xtol") >= FUZZY_MIN_SIMILARITY);
+        assert!(score("Kodak Xtol", "Kodak D-76") < FUZZY_MIN_SIMILARITY);
+        assert!(score("Rodinall", "Rodinal") > score("Rodinall", "Parodinal"));
+        assert_eq!(score("", "Xtol"), 0.0);
+    }
+
+    #[test]
+    fn search_ranks_exact_then_prefix_then_substring_matches() {
+        block_on(async {
+            let pool = chart_pool(&[
+                ("Ilford HP5+", "Parodinal", "1+50"),
+                ("Kodak Tri-X 400", "Rodinal", "1+50"),
+                ("Ilford HP5+", "ID-11", "Stock"),
+                ("Ilford HP5+", "Rodinal Special", "1+25"),
+                ("Ilford HP5+", "Rodinal", "1:50"),
+                ("Ilford HP5+", "Xtol", "Stock"),
+            ])
+            .await;
+
+            let query = MdcQuery {
+                developer: Some("rodinal".to_string()),
+                ..Default::default()
+            };
+            let page = search(&pool, &query).await.unwrap();
+            assert_eq!(page.total, 4);
+            assert_eq!(
+                names(&page),
+                [
+                    ("Ilford HP5+", "Rodinal"),
+                    ("Kodak Tri-X 400", "Rodinal"),
+                    ("Ilford HP5+", "Rodinal Special"),
+                    ("Ilford HP5+", "Parodinal"),
+                ]
+            );
+
+            let query = MdcQuery {
+                film: Some("HP5 Plus".to_string()),
+                developer: Some("Rodinal".to_string()),
+                dilution: Some("1+50".to_string()),
+                ..Default::default()
+            };
+            let page = search(&pool, &query).await.unwrap();
+            assert_eq!(
+                names(&page),
+                [("Ilford HP5+", "Rodinal"), ("Ilford HP5+", "Parodinal")]
+            );
+
+            // Pages share the total and continue the same order.
+            let query = MdcQuery {
+                developer: Some("rodinal".to_string()),
+                page: Some(1),
+                page_size: Some(3),
+                ..Default::default()
+            };
+            let page = search(&pool, &query).await.unwrap();
+            assert_eq!((page.total, page.page, page.page_size), (4, 1, 3));
+            assert_eq!(names(&page), [("Ilford HP5+", "Parodinal")]);
+        });
+    }
+
+    #[test]
+    fn search_falls_back_to_similar_names() {
+        block_on(async {
+            let pool = chart_pool(&[
+                ("Ilford HP5+", "Rodinal", "1+50"),
+                ("Ilford HP5+", "Parodinal", "1+50"),
+                ("Ilford HP5+", "Xtol", "Stock"),
+                ("Ilford Delta 100 Pro", "Xtol", "Stock"),
+            ])
+            .await;
+
+            let query = MdcQuery {
+                developer: Some("Rodinall".to_string()),
+                ..Default::default()
+            };
+            let page = search(&pool, &query).await.unwrap();
+            assert_eq!(
+                names(&page),
+                [("Ilford HP5+", "Rodinal"), ("Ilford HP5+", "Parodinal")]
+            );
+
+            let query = MdcQuery {
+                film: Some("Ilford Deltta 100".to_string()),
+                developer: Some("Kodak Xtol".to_string()),
+                ..Default::default()
+            };
+            let page = search(&pool, &query).await.unwrap();
+            assert_eq!(page.total, 1);
+            assert_eq!(names(&page), [("Ilford Delta 100 Pro", "Xtol")]);
+
+            // Other filters still apply to fuzzy matches.
+            let query = MdcQuery {
+                developer: Some("Rodinall".to_string()),
+                dilution: Some("1+25".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(search(&pool, &query).await.unwrap().total, 0);
+        });
+    }
+}
@@ -0,0 +1,490 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::mdc::{self, MdcRow};
+
+/// Header of the chart's CSV export, which is also the layout of `src/data/massive_dev_chart.csv`.
+const EXPECTED_HEADER: [&str; 9] = [
+    "Film", "Developer", "Dilution", "ASA/ISO", "35mm", "120", "Sheet", "Temp", "Notes",
+];
+
+/// Snapshots kept after an import: the new one plus the previous ones for change reports.
+const SNAPSHOTS_TO_KEEP: i64 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MdcRowIssue {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MdcDiffEntry {
+    pub row_key: String,
+    pub film: String,
+    pub developer: String,
+    pub dilution: String,
+    pub iso: String,
+    /// Field-level differences for changed rows, e.g. "35mm: 11 -> 12".
+    pub changes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MdcImportReport {
+    pub snapshot_id: i64,
+    pub previous_snapshot_id: Option<i64>,
+    pub row_count: usize,
+    pub added: Vec<MdcDiffEntry>,
+    pub changed: Vec<MdcDiffEntry>,
+    pub removed: Vec<MdcDiffEntry>,
+    pub skipped: Vec<MdcRowIssue>,
+    /// Recipes created from a row that has since changed or been removed.
+    pub flagged_recipes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MdcSnapshot {
+    pub id: i64,
+    pub label: String,
+    pub source: String,
+    pub row_count: i64,
+    pub imported_at: String,
+}
+
+/// Split CSV text into records, honouring quoted fields with embedded delimiters,
+/// doubled quotes and line breaks. Each record carries its starting line number.
+pub fn parse_csv(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+
+    records
+        .into_iter()
+        .filter(|(_, r)| r.iter().any(|f| !f.trim().is_empty()))
+        .collect()
+}
+
+fn clean(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalize temperatures to the chart's "20C" form; anything unparseable is kept as written.
+fn normalize_temp(raw: &str) -> String {
+    let raw = clean(raw);
+    let upper = raw.to_uppercase();
+    if upper.ends_with('F') {
+        return raw;
+    }
+    match mdc::parse_temp_c(&raw) {
+        Some(c) => format!("{}C", c),
+        None => raw,
+    }
+}
+
+fn normalize_row(fields: &[String]) -> Result<MdcRow, String> {
+    if fields.len() != EXPECTED_HEADER.len() {
+        return Err(format!(
+            "expected {} columns, found {}",
+            EXPECTED_HEADER.len(),
+            fields.len()
+        ));
+    }
+
+    let row = MdcRow {
+        film: clean(&fields[0]),
+        developer: clean(&fields[1]),
+        dilution: clean(&fields[2]).replace(':', "+").replace(" + ", "+"),
+        iso: clean(&fields[3]),
+        time_35mm: clean(&fields[4]),
+        time_120: clean(&fields[5]),
+        time_sheet: clean(&fields[6]),
+        temp_c: normalize_temp(&fields[7]),
+        notes: clean(&fields[8]),
+    };
+
+    if row.film.is_empty() {
+        return Err("film is empty".to_string());
+    }
+    if row.developer.is_empty() {
+        return Err("developer is empty".to_string());
+    }
+    Ok(row)
+}
+
+/// Parse a chart CSV export into validated rows. Rows that can't be used are
+/// reported rather than failing the whole import; a wrong header fails it.
+pub fn parse_chart_csv(text: &str) -> Result<(Vec<MdcRow>, Vec<MdcRowIssue>), String> {
+    let mut records = parse_csv(text, ',').into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err("CSV file is empty".to_string());
+    };
+
+    let header_matches = header.len() == EXPECTED_HEADER.len()
+        && header
+            .iter()
+            .zip(EXPECTED_HEADER)
+            .all(|(found, expected)| found.trim().eq_ignore_ascii_case(expected));
+    if !header_matches {
+        return Err(format!(
+            "Unexpected CSV header '{}', expected '{}'",
+            header.join(","),
+            EXPECTED_HEADER.join(",")
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    for (line, fields) in records {
+        match normalize_row(&fields) {
+            Ok(row) => {
+                let key = mdc::row_key(&row.film, &row.developer, &row.dilution, &row.iso);
+                if seen.insert(key) {
+                    rows.push(row);
+                } else {
+                    issues.push(MdcRowIssue {
+                        line,
+                        reason: format!(
+                            "duplicate of an earlier {} / {} / {} / {} row",
+                            row.film, row.developer, row.dilution, row.iso
+                        ),
+                    });
+                }
+            }
+            Err(reason) => issues.push(MdcRowIssue { line, reason }),
+        }
+    }
+
+    if rows.is_empty() {
+        return Err("CSV file has no usable rows".to_string());
+    }
+    Ok((rows, issues))
+}
+
+fn diff_entry(key: &str, row: &MdcRow, changes: Vec<String>) -> MdcDiffEntry {
+    MdcDiffEntry {
+        row_key: key.to_string(),
+        film: row.film.clone(),
+        developer: row.developer.clone(),
+        dilution: row.dilution.clone(),
+        iso: row.iso.clone(),
+        changes,
+    }
+}
+
+fn row_changes(old: &MdcRow, new: &MdcRow) -> Vec<String> {
+    let mut changes = [
+        ("35mm", &old.time_35mm, &new.time_35mm),
+        ("120", &old.time_120, &new.time_120),
+        ("Sheet", &old.time_sheet, &new.time_sheet),
+        ("Notes", &old.notes, &new.notes),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| format!("{}: {} -> {}", field, before, after))
+    .collect::<Vec<_>>();
+
+    // "20" and "20C" are the same temperature; only report real changes.
+    let same_temp = match (mdc::parse_temp_c(&old.temp_c), mdc::parse_temp_c(&new.temp_c)) {
+        (Some(before), Some(after)) => (before - after).abs() < f64::EPSILON,
+        _ => old.temp_c == new.temp_c,
+    };
+    if !same_temp {
+        changes.push(format!("Temp: {} -> {}", old.temp_c, new.temp_c));
+    }
+    changes
+}
+
+/// Rows added, changed and removed since the `previous` snapshot, each in
+/// `row_key` order.
+fn diff_rows(
+    previous: &BTreeMap<String, MdcRow>,
+    rows: &[MdcRow],
+) -> (Vec<MdcDiffEntry>, Vec<MdcDiffEntry>, Vec<MdcDiffEntry>) {
+    let current = rows
+        .iter()
+        .map(|row| {
+            (
+                mdc::row_key(&row.film, &row.developer, &row.dilution, &row.iso),
+                row,
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (key, row) in &current {
+        match previous.get(key) {
+            None => added.push(diff_entry(key, row, Vec::new())),
+            Some(old) => {
+                let changes = row_changes(old, row);
+                if !changes.is_empty() {
+                    changed.push(diff_entry(key, row, changes));
+                }
+            }
+        }
+    }
+    let removed = previous
+        .iter()
+        .filter(|(key, _)| !current.contains_key(*key))
+        .map(|(key, row)| diff_entry(key, row, Vec::new()))
+        .collect::<Vec<_>>();
+    (added, changed, removed)
+}
+
+#[derive(sqlx::FromRow)]
+struct KeyedRow {
+    row_key: String,
+    #[sqlx(flatten)]
+    row: MdcRow,
+}
+
+async fn snapshot_rows(
+    pool: &Pool<Sqlite>,
+    snapshot_id: i64,
+) -> Result<BTreeMap<String, MdcRow>, String> {
+    let rows = sqlx::query_as::<_, KeyedRow>(
+        "SELECT row_key, film, developer, dilution, iso, time_35mm, time_120, time_sheet, temp_c, notes FROM mdc_entries WHERE snapshot_id = ?",
+    )
+    .bind(snapshot_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load chart snapshot: {}", e))?;
+
+    Ok(rows.into_iter().map(|keyed| (keyed.row_key, keyed.row)).collect())
+}
+
+/// Store `rows` as the newest chart snapshot and report how it differs from the
+/// previous one. Recipes generated from rows that changed or disappeared are flagged.
+pub async fn import_snapshot(
+    pool: &Pool<Sqlite>,
+    label: &str,
+    source: &str,
+    rows: Vec<MdcRow>,
+    skipped: Vec<MdcRowIssue>,
+) -> Result<MdcImportReport, String> {
+    mdc::ensure_seeded(pool).await?;
+
+    let previous_snapshot_id: Option<i64> = sqlx::query_scalar("SELECT MAX(id) FROM mdc_snapshots")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to load chart snapshots: {}", e))?;
+    let previous = match previous_snapshot_id {
+        Some(id) => snapshot_rows(pool, id).await?,
+        None => BTreeMap::new(),
+    };
+
+    let (added, changed, removed) = diff_rows(&previous, &rows);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let snapshot_id = mdc::insert_snapshot(&mut tx, label, source, &rows).await?;
+
+    let mut flagged_recipes = Vec::new();
+    for entry in changed.iter().chain(removed.iter()) {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "UPDATE recipes SET mdc_source_changed = 1 WHERE mdc_source = ? RETURNING id",
+        )
+        .bind(&entry.row_key)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to flag recipes: {}", e))?;
+        flagged_recipes.extend(ids);
+    }
+
+    let newest_pruned = snapshot_id - SNAPSHOTS_TO_KEEP;
+    sqlx::query("DELETE FROM mdc_entries WHERE snapshot_id <= ?")
+        .bind(newest_pruned)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to prune old chart snapshots: {}", e))?;
+    sqlx::query("DELETE FROM mdc_snapshots WHERE id <= ?")
+        .bind(newest_pruned)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to prune old chart snapshots: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to save chart snapshot: {}", e))?;
+
+    Ok(MdcImportReport {
+        snapshot_id,
+        previous_snapshot_id,
+        row_count: rows.len(),
+        added,
+        changed,
+        removed,
+        skipped,
+        flagged_recipes,
+    })
+}
+
+pub async fn list_snapshots(pool: &Pool<Sqlite>) -> Result<Vec<MdcSnapshot>, String> {
+    mdc::ensure_seeded(pool).await?;
+
+    sqlx::query_as::<_, MdcSnapshot>(
+        "SELECT id, label, source, row_count, imported_at FROM mdc_snapshots ORDER BY id DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load chart snapshots: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Film,Developer,Dilution,ASA/ISO,35mm,120,Sheet,Temp,Notes\n";
+
+    fn row(film: &str, developer: &str, dilution: &str, time_35mm: &str, temp_c: &str) -> MdcRow {
+        MdcRow {
+            film: film.to_string(),
+            developer: developer.to_string(),
+            dilution: dilution.to_string(),
+            iso: "400".to_string(),
+            time_35mm: time_35mm.to_string(),
+            time_120: String::new(),
+            time_sheet: String::new(),
+            temp_c: temp_c.to_string(),
+            notes: String::new(),
+        }
+    }
+
+    fn keyed(rows: &[MdcRow]) -> BTreeMap<String, MdcRow> {
+        rows.iter()
+            .map(|r| {
+                (
+                    mdc::row_key(&r.film, &r.developer, &r.dilution, &r.iso),
+                    r.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_quoted_fields_and_line_breaks() {
+        let records = parse_csv("a,\"b, \"\"c\"\"\"\r\n\"multi\nline\",d\n\n,\n", ',');
+        assert_eq!(
+            records,
+            [
+                (1, vec!["a".to_string(), "b, \"c\"".to_string()]),
+                (2, vec!["multi\nline".to_string(), "d".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_must_match_the_chart_export() {
+        let rows = format!("{}Tri-X 400,D-76,1:1,400,9.75,11,,20,\n", HEADER);
+        let (rows, issues) = parse_chart_csv(&rows).unwrap();
+        assert!(issues.is_empty());
+        assert_eq!(rows[0].dilution, "1+1");
+        assert_eq!(rows[0].temp_c, "20C");
+
+        let lower = " film,DEVELOPER,dilution,asa/iso,35MM,120,sheet,temp,notes\nHP5+,ID-11,stock,400,7.5,,,20C,\n";
+        assert!(parse_chart_csv(lower).is_ok());
+
+        let error = parse_chart_csv("Film,Developer,Time\nTri-X,D-76,9\n").unwrap_err();
+        assert!(
+            error.starts_with("Unexpected CSV header 'Film,Developer,Time'"),
+            "{}",
+            error
+        );
+        assert_eq!(parse_chart_csv("").unwrap_err(), "CSV file is empty");
+        assert_eq!(
+            parse_chart_csv(HEADER).unwrap_err(),
+            "CSV file has no usable rows"
+        );
+    }
+
+    #[test]
+    fn duplicate_and_broken_rows_are_reported() {
+        let text = format!(
+            "{}Tri-X 400,D-76,1+1,400,9.75,,,20C,\n\
+             Tri-X  400,D-76,1 : 1,400,10,,,20C,later\n\
+             ,D-76,1+1,400,9,,,20C,\n\
+             HP5+,,stock,400,7.5,,,20C,\n\
+             HP5+,ID-11,stock,400\n\
+             HP5+,ID-11,stock,400,7.5,,,20C,\n",
+            HEADER
+        );
+        let (rows, issues) = parse_chart_csv(&text).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].time_35mm, "9.75");
+        let issues = issues
+            .iter()
+            .map(|i| (i.line, i.reason.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            [
+                (
+                    3,
+                    "duplicate of an earlier Tri-X 400 / D-76 / 1+1 / 400 row"
+                ),
+                (4, "film is empty"),
+                (5, "developer is empty"),
+                (6, "expected 9 columns, found 4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_reports_added_changed_and_removed_rows() {
+        let previous = keyed(&[
+            row("Tri-X 400", "D-76", "1+1", "9.75", "20C"),
+            row("HP5+", "ID-11", "stock", "7.5", "20C"),
+            row("FP4+", "Perceptol", "stock", "11", "20C"),
+        ]);
+        let rows = [
+            // Same temperature written differently: not a change.
+            row("Tri-X 400", "D-76", "1+1", "9.75", "20"),
+            row("HP5+", "ID-11", "stock", "8", "21C"),
+            row("Delta 100", "DD-X", "1+4", "12", "20C"),
+        ];
+        let (added, changed, removed) = diff_rows(&previous, &rows);
+
+        let films =
+            |entries: &[MdcDiffEntry]| entries.iter().map(|e| e.film.clone()).collect::<Vec<_>>();
+        assert_eq!(films(&added), ["Delta 100"]);
+        assert_eq!(films(&changed), ["HP5+"]);
+        assert_eq!(changed[0].changes, ["35mm: 7.5 -> 8", "Temp: 20C -> 21C"]);
+        assert_eq!(films(&removed), ["FP4+"]);
+        assert_eq!(removed[0].row_key, "fp4+|perceptol|stock|400");
+    }
+}
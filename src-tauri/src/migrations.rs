@@ -85,5 +85,300 @@ pub fn get_migrations() -> Vec<Migration> {
         "#,
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 5,
+            description: "log development sessions",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id                      TEXT PRIMARY KEY,
+                recipe_id               TEXT REFERENCES recipes(id) ON DELETE SET NULL,
+                recipe_revision         TEXT NOT NULL DEFAULT '',
+                film_format             TEXT NOT NULL DEFAULT '35mm',
+                roll_count              INTEGER NOT NULL DEFAULT 1,
+                measured_temperature    REAL,
+                developer_batch         TEXT NOT NULL DEFAULT '',
+                ago_filename            TEXT NOT NULL DEFAULT '',
+                rating                  INTEGER,
+                notes                   TEXT NOT NULL DEFAULT '',
+                processed_at            TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_sessions_recipe ON sessions(recipe_id, processed_at);
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "track chemistry inventory",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS chemicals (
+                id                          TEXT PRIMARY KEY,
+                name                        TEXT NOT NULL,
+                kind                        TEXT NOT NULL DEFAULT 'developer',
+                opened_at                   TEXT,
+                volume_remaining_ml         REAL NOT NULL DEFAULT 0,
+                one_shot                    INTEGER NOT NULL DEFAULT 1,
+                capacity_rolls_per_litre    REAL NOT NULL DEFAULT 0,
+                rolls_processed             INTEGER NOT NULL DEFAULT 0,
+                expires_at                  TEXT,
+                notes                       TEXT NOT NULL DEFAULT '',
+                created_at                  TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS session_chemicals (
+                session_id      TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+                chemical_id     TEXT NOT NULL REFERENCES chemicals(id) ON DELETE CASCADE,
+                volume_ml       REAL NOT NULL DEFAULT 0,
+                PRIMARY KEY (session_id, chemical_id)
+            );
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "store massive dev chart for backend search",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS mdc_entries (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                film            TEXT NOT NULL,
+                developer       TEXT NOT NULL,
+                dilution        TEXT NOT NULL DEFAULT '',
+                iso             TEXT NOT NULL DEFAULT '',
+                time_35mm       TEXT NOT NULL DEFAULT '',
+                time_120        TEXT NOT NULL DEFAULT '',
+                time_sheet      TEXT NOT NULL DEFAULT '',
+                temp_c          TEXT NOT NULL DEFAULT '',
+                notes           TEXT NOT NULL DEFAULT '',
+                film_key        TEXT NOT NULL,
+                developer_key   TEXT NOT NULL,
+                dilution_key    TEXT NOT NULL DEFAULT '',
+                iso_value       INTEGER,
+                temp_value      REAL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_mdc_film ON mdc_entries(film_key, developer_key);
+            CREATE INDEX IF NOT EXISTS idx_mdc_developer ON mdc_entries(developer_key);
+            CREATE INDEX IF NOT EXISTS idx_mdc_iso_temp ON mdc_entries(iso_value, temp_value);
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "remember the chart row a recipe was created from",
+            sql: r#"
+            ALTER TABLE recipes ADD COLUMN mdc_source TEXT NOT NULL DEFAULT '';
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "version massive dev chart snapshots",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS mdc_snapshots (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                label           TEXT NOT NULL,
+                source          TEXT NOT NULL DEFAULT '',
+                row_count       INTEGER NOT NULL DEFAULT 0,
+                imported_at     TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            ALTER TABLE mdc_entries ADD COLUMN snapshot_id INTEGER REFERENCES mdc_snapshots(id) ON DELETE CASCADE;
+            ALTER TABLE mdc_entries ADD COLUMN row_key TEXT NOT NULL DEFAULT '';
+
+            INSERT INTO mdc_snapshots (label, source, row_count)
+                SELECT 'Bundled chart', 'bundled', COUNT(*) FROM mdc_entries HAVING COUNT(*) > 0;
+            UPDATE mdc_entries SET
+                snapshot_id = (SELECT MAX(id) FROM mdc_snapshots),
+                row_key = film_key || '|' || developer_key || '|' || dilution_key || '|' || trim(iso);
+
+            CREATE INDEX IF NOT EXISTS idx_mdc_snapshot ON mdc_entries(snapshot_id, row_key);
+
+            ALTER TABLE recipes ADD COLUMN mdc_source_changed INTEGER NOT NULL DEFAULT 0;
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "add editable step templates",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS templates (
+                id              TEXT PRIMARY KEY,
+                name            TEXT NOT NULL,
+                category        TEXT NOT NULL DEFAULT 'BW',
+                description     TEXT NOT NULL DEFAULT '',
+                builtin         INTEGER NOT NULL DEFAULT 0,
+                created_at      TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at      TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS template_steps (
+                id                  TEXT PRIMARY KEY,
+                template_id         TEXT NOT NULL REFERENCES templates(id) ON DELETE CASCADE,
+                sort_order          INTEGER NOT NULL DEFAULT 0,
+                name                TEXT NOT NULL DEFAULT 'DEV',
+                time_min            INTEGER NOT NULL DEFAULT 0,
+                time_sec            INTEGER NOT NULL DEFAULT 0,
+                agitation           TEXT NOT NULL DEFAULT 'Roll',
+                compensation        TEXT NOT NULL DEFAULT 'Off',
+                min_temperature     REAL NOT NULL DEFAULT 18,
+                rated_temperature   REAL NOT NULL DEFAULT 20,
+                max_temperature     REAL NOT NULL DEFAULT 24,
+                formula_designator  TEXT NOT NULL DEFAULT '',
+                logo_text           TEXT NOT NULL DEFAULT ''
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_template_steps ON template_steps(template_id, sort_order);
+
+            INSERT OR IGNORE INTO templates (id, name, category, description, builtin) VALUES
+                ('builtin-bw', 'B&W standard', 'BW', 'Develop, stop, fix and rinse', 1),
+                ('builtin-bw-prewash', 'B&W with pre-wash', 'BW', 'Pre-wash before the standard B&W sequence', 1),
+                ('builtin-two-bath', 'Two-bath B&W', 'BW', 'Bath A and bath B developers, no stop', 1),
+                ('builtin-c41', 'C-41', 'C-41', 'Colour negative: pre-heat, develop, blix, wash', 1),
+                ('builtin-e6', 'E-6', 'E-6', 'Colour reversal: first developer, colour developer, blix', 1);
+
+            INSERT OR IGNORE INTO template_steps (id, template_id, sort_order, name, time_min, time_sec, agitation, compensation, min_temperature, rated_temperature, max_temperature, formula_designator, logo_text) VALUES
+                ('builtin-bw-0', 'builtin-bw', 0, 'DEV', 0, 0, 'Roll', 'On', 18, 20, 24, '1.1.1', 'B&W DEV'),
+                ('builtin-bw-1', 'builtin-bw', 1, 'STOP', 1, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-2', 'builtin-bw', 2, 'FIX', 5, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-3', 'builtin-bw', 3, 'RINSE', 10, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-prewash-0', 'builtin-bw-prewash', 0, 'PRE', 1, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-prewash-1', 'builtin-bw-prewash', 1, 'DEV', 0, 0, 'Roll', 'On', 18, 20, 24, '1.1.1', 'B&W DEV'),
+                ('builtin-bw-prewash-2', 'builtin-bw-prewash', 2, 'STOP', 1, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-prewash-3', 'builtin-bw-prewash', 3, 'FIX', 5, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-bw-prewash-4', 'builtin-bw-prewash', 4, 'RINSE', 10, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-two-bath-0', 'builtin-two-bath', 0, 'DEV', 3, 0, 'Roll', 'On', 18, 20, 24, '1.1.1', 'BATH A'),
+                ('builtin-two-bath-1', 'builtin-two-bath', 1, 'DEV', 3, 0, 'Stand', 'Off', 18, 20, 24, '1.1.1', 'BATH B'),
+                ('builtin-two-bath-2', 'builtin-two-bath', 2, 'FIX', 5, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-two-bath-3', 'builtin-two-bath', 3, 'RINSE', 10, 0, 'Roll', 'Off', 18, 20, 24, '', ''),
+                ('builtin-c41-0', 'builtin-c41', 0, 'PRE', 5, 0, 'Roll', 'Off', 37, 37.8, 39, '', ''),
+                ('builtin-c41-1', 'builtin-c41', 1, 'DEV', 3, 15, 'Roll', 'Off', 37.65, 37.8, 37.95, '', 'C-41 DEV'),
+                ('builtin-c41-2', 'builtin-c41', 2, 'BLIX', 6, 30, 'Roll', 'Off', 33, 37.8, 39, '', ''),
+                ('builtin-c41-3', 'builtin-c41', 3, 'WASH', 3, 0, 'Roll', 'Off', 33, 37.8, 39, '', ''),
+                ('builtin-e6-0', 'builtin-e6', 0, 'PRE', 5, 0, 'Roll', 'Off', 37, 38, 39, '', ''),
+                ('builtin-e6-1', 'builtin-e6', 1, 'DEV', 6, 30, 'Roll', 'Off', 37.7, 38, 38.3, '', 'FIRST DEV'),
+                ('builtin-e6-2', 'builtin-e6', 2, 'WASH', 2, 30, 'Roll', 'Off', 36, 38, 40, '', ''),
+                ('builtin-e6-3', 'builtin-e6', 3, 'DEV', 6, 0, 'Roll', 'Off', 37, 38, 39, '', 'COLOR DEV'),
+                ('builtin-e6-4', 'builtin-e6', 4, 'BLIX', 6, 0, 'Roll', 'Off', 36, 38, 40, '', ''),
+                ('builtin-e6-5', 'builtin-e6', 5, 'WASH', 4, 0, 'Roll', 'Off', 36, 38, 40, '', '');
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "finish colour templates with a stabiliser",
+            sql: r#"
+            INSERT OR IGNORE INTO template_steps (id, template_id, sort_order, name, time_min, time_sec, agitation, compensation, min_temperature, rated_temperature, max_temperature, formula_designator, logo_text)
+                SELECT 'builtin-c41-4', id, 4, 'STAB', 1, 0, 'Roll', 'Off', 24, 37.8, 41, '', '' FROM templates WHERE id = 'builtin-c41';
+            INSERT OR IGNORE INTO template_steps (id, template_id, sort_order, name, time_min, time_sec, agitation, compensation, min_temperature, rated_temperature, max_temperature, formula_designator, logo_text)
+                SELECT 'builtin-e6-6', id, 6, 'STAB', 1, 0, 'Roll', 'Off', 24, 38, 40, '', '' FROM templates WHERE id = 'builtin-e6';
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "user additions to the film and developer catalog",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS catalog_names (
+                kind            TEXT NOT NULL,
+                name            TEXT NOT NULL,
+                created_at      TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (kind, name)
+            );
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "trusted keys for signed recipe bundles",
+            sql: r#"
+            INSERT OR IGNORE INTO settings (key, value) VALUES
+                ('bundle_publisher', ''),
+                ('trusted_bundle_keys', '[]'),
+                ('untrusted_bundle_policy', 'warn');
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "device profiles for several AGO units",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS devices (
+                id              TEXT PRIMARY KEY,
+                name            TEXT NOT NULL,
+                ip              TEXT NOT NULL DEFAULT '10.10.10.1',
+                ssid            TEXT NOT NULL DEFAULT 'AGO',
+                credential_ref  TEXT NOT NULL DEFAULT '',
+                firmware_notes  TEXT NOT NULL DEFAULT '',
+                upload_endpoint TEXT NOT NULL DEFAULT '',
+                upload_field    TEXT NOT NULL DEFAULT '',
+                created_at      TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at      TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT OR IGNORE INTO devices (id, name, ip, ssid, credential_ref, upload_endpoint, upload_field)
+                SELECT 'default', 'AGO',
+                    COALESCE(NULLIF((SELECT value FROM settings WHERE key = 'ago_ip'), ''), '10.10.10.1'),
+                    COALESCE(NULLIF((SELECT value FROM settings WHERE key = 'ago_ssid'), ''), 'AGO'),
+                    'wifi:' || COALESCE(NULLIF((SELECT value FROM settings WHERE key = 'ago_ssid'), ''), 'AGO'),
+                    COALESCE((SELECT value FROM settings WHERE key = 'ago_upload_endpoint'), ''),
+                    COALESCE((SELECT value FROM settings WHERE key = 'ago_upload_field'), '');
+
+            DELETE FROM settings WHERE key IN ('ago_ip', 'ago_ssid', 'ago_upload_endpoint', 'ago_upload_field');
+            INSERT OR IGNORE INTO settings (key, value) VALUES ('active_device_id', 'default');
+
+            ALTER TABLE ago_uploads ADD COLUMN device_id TEXT REFERENCES devices(id) ON DELETE SET NULL;
+            UPDATE ago_uploads SET device_id = 'default';
+            CREATE INDEX IF NOT EXISTS idx_ago_uploads_device ON ago_uploads(device_id, filename);
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "named recipe collections for fleet deploys",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS collections (
+                id              TEXT PRIMARY KEY,
+                name            TEXT NOT NULL,
+                description     TEXT NOT NULL DEFAULT '',
+                created_at      TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at      TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS collection_recipes (
+                collection_id   TEXT NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+                recipe_id       TEXT NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+                sort_order      INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (collection_id, recipe_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_collection_recipes ON collection_recipes(collection_id, sort_order);
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "cached device probe results",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS device_probes (
+                device_id       TEXT PRIMARY KEY REFERENCES devices(id) ON DELETE CASCADE,
+                probe           TEXT NOT NULL,
+                updated_at      TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+        "#,
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "rate chemical capacity on starting volume and record deducted stock",
+            sql: r#"
+            ALTER TABLE chemicals ADD COLUMN volume_initial_ml REAL NOT NULL DEFAULT 0;
+            UPDATE chemicals SET volume_initial_ml = volume_remaining_ml + COALESCE(
+                (SELECT SUM(volume_ml) FROM session_chemicals WHERE chemical_id = chemicals.id), 0);
+
+            ALTER TABLE session_chemicals ADD COLUMN volume_deducted_ml REAL NOT NULL DEFAULT 0;
+            UPDATE session_chemicals SET volume_deducted_ml = volume_ml;
+        "#,
+            kind: MigrationKind::Up,
+        },
     ]
 }
@@ -0,0 +1,373 @@
+use std::fmt::Write;
+
+pub const PAGE_WIDTH: f64 = 595.0;
+pub const PAGE_HEIGHT: f64 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Glyph widths in 1/1000 em for ASCII 32..=126, from the Helvetica AFM files.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// WinAnsiEncoding byte for `c`; characters outside it become '?'.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '\u{2022}' => 0x95,
+        '\u{2013}' => 0x96,
+        '\u{2014}' => 0x97,
+        '\u{2018}' => 0x91,
+        '\u{2019}' => 0x92,
+        '\u{201c}' => 0x93,
+        '\u{201d}' => 0x94,
+        '\u{2026}' => 0x85,
+        '\u{20ac}' => 0x80,
+        _ => b'?',
+    }
+}
+
+fn char_width(c: char, font: Font) -> f64 {
+    let widths = match font {
+        Font::Regular => &HELVETICA_WIDTHS,
+        Font::Bold => &HELVETICA_BOLD_WIDTHS,
+    };
+    let width = match win_ansi(c) {
+        b @ 32..=126 => widths[(b - 32) as usize],
+        0xb0 => 400,
+        0xb1 => 584,
+        0xd7 => 584,
+        0x95 => 350,
+        0x96 => 556,
+        0x97 => 1000,
+        _ => 556,
+    };
+    width as f64 / 1000.0
+}
+
+pub fn text_width(text: &str, font: Font, size: f64) -> f64 {
+    text.chars().map(|c| char_width(c, font)).sum::<f64>() * size
+}
+
+/// Break `text` into lines no wider than `max_width`, keeping explicit line
+/// breaks. Words longer than a line are split.
+pub fn wrap(text: &str, font: Font, size: f64, max_width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, font, size) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                if !line.is_empty() && text_width(&format!("{}{}", line, c), font, size) > max_width
+                {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.chars().map(win_ansi) {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            32..=126 => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "\\{:03o}", byte);
+            }
+        }
+    }
+    out
+}
+
+fn text_op(x: f64, y: f64, size: f64, font: Font, text: &str) -> String {
+    format!(
+        "BT /{} {:.1} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+        font.resource(),
+        size,
+        x,
+        PAGE_HEIGHT - y,
+        escape(text)
+    )
+}
+
+/// Builds an A4 document page by page from text, lines and filled boxes, using the
+/// standard Helvetica fonts so nothing is embedded. Coordinates are in points
+/// from the top-left corner of the page.
+#[derive(Debug, Default)]
+pub struct PdfWriter {
+    pages: Vec<String>,
+}
+
+impl PdfWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new page; drawing goes to the last page started.
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Move `other`'s pages to the end of this document.
+    pub fn append(&mut self, other: PdfWriter) {
+        self.pages.extend(other.pages);
+    }
+
+    /// Draw text on an earlier page, e.g. a footer once the page count is known.
+    pub fn text_on(&mut self, page: usize, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        if let Some(content) = self.pages.get_mut(page) {
+            content.push_str(&text_op(x, y, size, font, text));
+        }
+    }
+
+    fn content(&mut self) -> &mut String {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+        self.pages.last_mut().expect("a page was just added")
+    }
+
+    pub fn text(&mut self, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        let line = text_op(x, y, size, font, text);
+        self.content().push_str(&line);
+    }
+
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, width: f64) {
+        let line = format!(
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S\n",
+            width,
+            x1,
+            PAGE_HEIGHT - y1,
+            x2,
+            PAGE_HEIGHT - y2
+        );
+        self.content().push_str(&line);
+    }
+
+    /// A rectangle filled with `gray` (0 black, 1 white).
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, gray: f64) {
+        let rect = format!(
+            "q {:.2} g {:.2} {:.2} {:.2} {:.2} re f Q\n",
+            gray,
+            x,
+            PAGE_HEIGHT - y - height,
+            width,
+            height
+        );
+        self.content().push_str(&rect);
+    }
+
+    /// Serialize the document with `title` in its info dictionary.
+    pub fn finish(mut self, title: &str) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+
+        // Objects 1-5 are fixed; each page then takes a page and a content object.
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            String::new(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            format!(
+                "<< /Title ({}) /Producer (AGO Recipe Manager) >>",
+                escape(title)
+            ),
+        ];
+        let mut kids = Vec::new();
+        for content in &self.pages {
+            let page_id = objects.len() + 1;
+            kids.push(format!("{} 0 R", page_id));
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                page_id + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            self.pages.len()
+        );
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+        }
+
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        out.extend_from_slice(trailer.as_bytes());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(pages: usize) -> Vec<u8> {
+        let mut pdf = PdfWriter::new();
+        for page in 0..pages {
+            pdf.new_page();
+            pdf.text(48.0, 60.0, 12.0, Font::Bold, &format!("Page {}", page + 1));
+            pdf.line(48.0, 70.0, 300.0, 70.0, 0.5);
+            pdf.fill_rect(48.0, 80.0, 200.0, 20.0, 0.9);
+        }
+        pdf.finish("Cards")
+    }
+
+    /// Offsets from the cross-reference table that `startxref` points at.
+    fn xref_offsets(bytes: &[u8]) -> Vec<usize> {
+        let text = String::from_utf8_lossy(bytes);
+        let start = text.rsplit("startxref\n").next().unwrap();
+        let start: usize = start.lines().next().unwrap().parse().unwrap();
+        let table = std::str::from_utf8(&bytes[start..]).unwrap();
+        assert!(table.starts_with("xref\n"), "{}", table);
+        table
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn xref_points_at_each_object() {
+        let bytes = document(3);
+        let offsets = xref_offsets(&bytes);
+        // Catalog, page tree, two fonts and info, then a page and a content stream per page.
+        assert_eq!(offsets.len(), 5 + 2 * 3);
+        for (index, offset) in offsets.iter().enumerate() {
+            let header = format!("{} 0 obj\n", index + 1);
+            assert!(
+                bytes[*offset..].starts_with(header.as_bytes()),
+                "object {}",
+                index + 1
+            );
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains(&format!("/Size {} ", offsets.len() + 1)));
+    }
+
+    #[test]
+    fn page_tree_counts_every_page() {
+        // An empty document still gets one blank page.
+        for (pages, expected) in [(0, 1), (1, 1), (4, 4)] {
+            let text = String::from_utf8_lossy(&document(pages)).into_owned();
+            assert!(
+                text.contains(&format!("/Count {} >>", expected)),
+                "{}",
+                pages
+            );
+            assert_eq!(text.matches("/Type /Page /Parent").count(), expected);
+        }
+    }
+
+    #[test]
+    fn text_is_escaped_for_pdf_strings() {
+        assert_eq!(escape(r"f(x) = a\b"), r"f\(x\) = a\\b");
+        assert_eq!(
+            escape("38.0 °C ± 0.15 – café"),
+            r"38.0 \260C \261 0.15 \226 caf\351"
+        );
+        assert_eq!(escape("日本"), "??");
+
+        let mut pdf = PdfWriter::new();
+        pdf.text(48.0, 60.0, 10.0, Font::Regular, "(Tri-X)");
+        let text = String::from_utf8_lossy(&pdf.finish(r"HP5+ (box) \ é")).into_owned();
+        assert!(text.contains(r"(\(Tri-X\)) Tj"), "{}", text);
+        assert!(text.contains(r"/Title (HP5+ \(box\) \\ \351)"), "{}", text);
+    }
+
+    #[test]
+    fn wrap_breaks_at_words_and_keeps_line_breaks() {
+        let width = text_width("Agitate gently", Font::Regular, 10.0);
+        assert_eq!(
+            wrap("Agitate gently every minute", Font::Regular, 10.0, width),
+            ["Agitate gently", "every minute"]
+        );
+        assert_eq!(wrap("a\n\nb", Font::Regular, 10.0, 100.0), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn wrap_splits_words_longer_than_a_line() {
+        let word = "Xtol-replenished-stock-solution";
+        let max_width = text_width("Xtol-repl", Font::Bold, 12.0);
+        let lines = wrap(&format!("Use {}", word), Font::Bold, 12.0, max_width);
+        assert_eq!(lines[0], "Use");
+        assert!(lines.len() > 2, "{:?}", lines);
+        assert_eq!(lines[1..].concat(), word);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(line, Font::Bold, 12.0) <= max_width));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::db;
+
+/// HTTP methods the custom programs API may accept for writing a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WriteMethod {
+    Post,
+    Put,
+}
+
+impl WriteMethod {
+    pub const ALL: [WriteMethod; 2] = [WriteMethod::Post, WriteMethod::Put];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriteMethod::Post => "POST",
+            WriteMethod::Put => "PUT",
+        }
+    }
+}
+
+/// What a device answered when last probed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceProbe {
+    /// The web server answered at all.
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    /// `/api/files/programs/custom` answered like an AGO.
+    pub programs_api: bool,
+    pub program_count: Option<usize>,
+    /// Methods the custom programs API advertises for writing.
+    pub advertised_methods: Vec<WriteMethod>,
+    /// Method that last wrote a program, kept across probes.
+    pub last_write_method: Option<WriteMethod>,
+    /// Version-looking strings from the web UI and response headers.
+    pub firmware_hints: Vec<String>,
+    pub probed_at: String,
+}
+
+impl DeviceProbe {
+    /// Upload order: the method that last worked, then advertised methods,
+    /// then the rest.
+    pub fn upload_order(&self) -> Vec<WriteMethod> {
+        let mut order = self.last_write_method.into_iter().collect::<Vec<_>>();
+        for method in self
+            .advertised_methods
+            .iter()
+            .copied()
+            .chain(WriteMethod::ALL)
+        {
+            if !order.contains(&method) {
+                order.push(method);
+            }
+        }
+        order
+    }
+}
+
+/// Write methods named in an `Allow` or `Access-Control-Allow-Methods` header.
+pub fn allowed_write_methods(header: &str) -> Vec<WriteMethod> {
+    let named = header
+        .split(',')
+        .map(|m| m.trim().to_uppercase())
+        .collect::<Vec<_>>();
+    WriteMethod::ALL
+        .into_iter()
+        .filter(|m| named.iter().any(|n| n == m.as_str()))
+        .collect()
+}
+
+pub async fn load_probe(
+    pool: &Pool<Sqlite>,
+    device_id: &str,
+) -> Result<Option<DeviceProbe>, String> {
+    let json: Option<String> =
+        sqlx::query_scalar("SELECT probe FROM device_probes WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load device probe: {}", e))?;
+    // A cache written by another version is just dropped.
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Cache a fresh probe, keeping the write method learned from earlier uploads.
+pub async fn save_probe(
+    pool: &Pool<Sqlite>,
+    device_id: &str,
+    probe: &mut DeviceProbe,
+) -> Result<(), String> {
+    if let Some(previous) = load_probe(pool, device_id).await? {
+        probe.last_write_method = previous.last_write_method;
+    }
+    store(pool, device_id, probe).await
+}
+
+/// Remember the method that just wrote a program.
+pub async fn record_write_method(
+    pool: &Pool<Sqlite>,
+    device_id: &str,
+    method: WriteMethod,
+) -> Result<(), String> {
+    let mut probe = load_probe(pool, device_id).await?.unwrap_or_default();
+    if probe.last_write_method == Some(method) {
+        return Ok(());
+    }
+    probe.last_write_method = Some(method);
+    store(pool, device_id, &probe).await
+}
+
+async fn store(pool: &Pool<Sqlite>, device_id: &str, probe: &DeviceProbe) -> Result<(), String> {
+    let json =
+        serde_json::to_string(probe).map_err(|e| format!("Failed to save device probe: {}", e))?;
+    sqlx::query(
+        "INSERT INTO device_probes (device_id, probe, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(device_id) DO UPDATE SET probe = excluded.probe, updated_at = excluded.updated_at",
+    )
+    .bind(device_id)
+    .bind(json)
+    .bind(db::now_iso())
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to save device probe: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_order_tries_last_worked_method_first() {
+        let mut probe = DeviceProbe::default();
+        assert_eq!(probe.upload_order(), [WriteMethod::Post, WriteMethod::Put]);
+        probe.advertised_methods = vec![WriteMethod::Put];
+        assert_eq!(probe.upload_order(), [WriteMethod::Put, WriteMethod::Post]);
+        probe.last_write_method = Some(WriteMethod::Post);
+        assert_eq!(probe.upload_order(), [WriteMethod::Post, WriteMethod::Put]);
+        assert_eq!(probe.advertised_methods, [WriteMethod::Put]);
+    }
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+use crate::mdc::normalize_name;
+
+/// Slack for temperatures that went through a float round trip.
+const TEMP_EPSILON: f64 = 1e-6;
+
+/// The chemistry a recipe runs, taken from its `category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Process {
+    #[serde(rename = "BW")]
+    BlackAndWhite,
+    #[serde(rename = "C-41")]
+    C41,
+    #[serde(rename = "E-6")]
+    E6,
+    #[serde(rename = "ECN-2")]
+    Ecn2,
+}
+
+/// Allowed developer temperature: `rated` ± `tolerance` °C.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TempSpec {
+    pub rated: f64,
+    pub tolerance: f64,
+}
+
+impl TempSpec {
+    pub fn min(&self) -> f64 {
+        self.rated - self.tolerance
+    }
+
+    pub fn max(&self) -> f64 {
+        self.rated + self.tolerance
+    }
+}
+
+/// Temperatures of one program step, as written to the AGO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepTemperatures {
+    pub name: String,
+    pub min: f64,
+    pub rated: f64,
+    pub max: f64,
+}
+
+impl Process {
+    /// Recognises "BW", "B&W", "C-41", "C41", "E-6", "E6", "ECN-2" and "ECN2";
+    /// anything else is treated as black and white, as before colour support.
+    pub fn from_category(category: &str) -> Self {
+        match normalize_name(category).as_str() {
+            "c41" => Process::C41,
+            "e6" => Process::E6,
+            "ecn2" => Process::Ecn2,
+            _ => Process::BlackAndWhite,
+        }
+    }
+
+    pub fn category(&self) -> &'static str {
+        match self {
+            Process::BlackAndWhite => "BW",
+            Process::C41 => "C-41",
+            Process::E6 => "E-6",
+            Process::Ecn2 => "ECN-2",
+        }
+    }
+
+    /// Program designator sent in the custom program payload.
+    pub fn designator(&self) -> &'static str {
+        match self {
+            Process::BlackAndWhite => "C2",
+            Process::C41 => "C41",
+            Process::E6 => "E6",
+            Process::Ecn2 => "ECN2",
+        }
+    }
+
+    pub fn is_colour(&self) -> bool {
+        *self != Process::BlackAndWhite
+    }
+
+    /// Developer tolerances in program order: E-6 has a first and a colour developer.
+    fn developer_specs(&self) -> &'static [TempSpec] {
+        match self {
+            Process::BlackAndWhite => &[],
+            Process::C41 => &[TempSpec {
+                rated: 37.8,
+                tolerance: 0.15,
+            }],
+            Process::E6 => &[
+                TempSpec {
+                    rated: 38.0,
+                    tolerance: 0.3,
+                },
+                TempSpec {
+                    rated: 38.0,
+                    tolerance: 1.1,
+                },
+            ],
+            Process::Ecn2 => &[TempSpec {
+                rated: 41.1,
+                tolerance: 0.1,
+            }],
+        }
+    }
+
+    /// Spec for the `index`-th DEV step; extra DEV steps share the last spec.
+    pub fn developer_spec(&self, index: usize) -> Option<TempSpec> {
+        let specs = self.developer_specs();
+        specs.get(index).or_else(|| specs.last()).copied()
+    }
+
+    /// Range for blix, stabiliser, wash and other non-developer colour steps.
+    pub fn auxiliary_range(&self) -> Option<(f64, f64)> {
+        match self {
+            Process::BlackAndWhite => None,
+            Process::C41 => Some((24.0, 41.0)),
+            Process::E6 => Some((24.0, 40.0)),
+            Process::Ecn2 => Some((24.0, 43.0)),
+        }
+    }
+}
+
+/// Check a program's steps against the process: colour developer windows must
+/// sit inside the tolerance, other colour steps inside the process range, a BLIX
+/// must follow a developer and a stabiliser must be the last step.
+pub fn validate_steps(process: Process, steps: &[StepTemperatures]) -> Result<(), String> {
+    let mut problems = Vec::new();
+    let mut dev_index = 0;
+    let mut seen_dev = false;
+
+    for (index, step) in steps.iter().enumerate() {
+        let label = format!("Step {} ({})", index + 1, step.name);
+
+        match step.name.as_str() {
+            "BLIX" if !seen_dev => problems.push(format!("{} must come after a DEV step", label)),
+            "STAB" if index + 1 != steps.len() => {
+                problems.push(format!("{} must be the last step", label))
+            }
+            _ => {}
+        }
+
+        if !process.is_colour() {
+            seen_dev |= step.name == "DEV";
+            continue;
+        }
+
+        if step.name == "DEV" {
+            let spec = process
+                .developer_spec(dev_index)
+                .expect("colour processes have developer specs");
+            let inside = |t: f64| t >= spec.min() - TEMP_EPSILON && t <= spec.max() + TEMP_EPSILON;
+            if !inside(step.min) || !inside(step.rated) || !inside(step.max) {
+                problems.push(format!(
+                    "{} runs {}-{}°C but {} needs {}±{}°C",
+                    label,
+                    step.min,
+                    step.max,
+                    process.category(),
+                    spec.rated,
+                    spec.tolerance
+                ));
+            }
+            dev_index += 1;
+            seen_dev = true;
+        } else if let Some((low, high)) = process.auxiliary_range() {
+            if step.rated < low - TEMP_EPSILON || step.rated > high + TEMP_EPSILON {
+                problems.push(format!(
+                    "{} at {}°C is outside the {} range of {}-{}°C",
+                    label,
+                    step.rated,
+                    process.category(),
+                    low,
+                    high
+                ));
+            }
+        }
+
+        if step.min > step.rated + TEMP_EPSILON || step.rated > step.max + TEMP_EPSILON {
+            problems.push(format!("{} needs min <= rated <= max temperature", label));
+        }
+    }
+
+    if process.is_colour() && dev_index == 0 {
+        problems.push(format!("{} program has no DEV step", process.category()));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str, min: f64, rated: f64, max: f64) -> StepTemperatures {
+        StepTemperatures {
+            name: name.to_string(),
+            min,
+            rated,
+            max,
+        }
+    }
+
+    fn c41(dev: StepTemperatures) -> Vec<StepTemperatures> {
+        vec![
+            step("PRE", 37.0, 37.8, 39.0),
+            dev,
+            step("BLIX", 33.0, 37.8, 39.0),
+            step("WASH", 33.0, 37.8, 39.0),
+            step("STAB", 24.0, 37.8, 41.0),
+        ]
+    }
+
+    #[test]
+    fn c41_developer_must_hold_the_tolerance() {
+        assert!(validate_steps(Process::C41, &c41(step("DEV", 37.65, 37.8, 37.95))).is_ok());
+        assert_eq!(
+            validate_steps(Process::C41, &c41(step("DEV", 37.5, 37.8, 38.1))),
+            Err("Step 2 (DEV) runs 37.5-38.1°C but C-41 needs 37.8±0.15°C".to_string())
+        );
+        assert!(validate_steps(Process::C41, &c41(step("DEV", 37.65, 38.0, 38.1))).is_err());
+    }
+
+    #[test]
+    fn e6_developers_have_their_own_tolerances() {
+        let e6 = |first: StepTemperatures, second: StepTemperatures| {
+            vec![
+                step("PRE", 37.0, 38.0, 39.0),
+                first,
+                step("WASH", 36.0, 38.0, 40.0),
+                second,
+                step("BLIX", 36.0, 38.0, 40.0),
+                step("WASH", 36.0, 38.0, 40.0),
+                step("STAB", 24.0, 38.0, 40.0),
+            ]
+        };
+        let first_ok = || step("DEV", 37.7, 38.0, 38.3);
+        let second_ok = || step("DEV", 37.0, 38.0, 39.0);
+        assert!(validate_steps(Process::E6, &e6(first_ok(), second_ok())).is_ok());
+
+        // The colour developer's ±1.1°C is too loose for the first developer.
+        let error = validate_steps(Process::E6, &e6(second_ok(), second_ok())).unwrap_err();
+        assert_eq!(error, "Step 2 (DEV) runs 37-39°C but E-6 needs 38±0.3°C");
+
+        let error = validate_steps(Process::E6, &e6(first_ok(), step("DEV", 36.5, 38.0, 39.0)))
+            .unwrap_err();
+        assert_eq!(error, "Step 4 (DEV) runs 36.5-39°C but E-6 needs 38±1.1°C");
+    }
+
+    #[test]
+    fn blix_follows_a_developer_and_stab_comes_last() {
+        let dev = || step("DEV", 37.65, 37.8, 37.95);
+        let blix = || step("BLIX", 33.0, 37.8, 39.0);
+        let stab = || step("STAB", 24.0, 37.8, 41.0);
+
+        let error = validate_steps(Process::C41, &[blix(), dev(), stab()]).unwrap_err();
+        assert_eq!(error, "Step 1 (BLIX) must come after a DEV step");
+
+        let error = validate_steps(Process::C41, &[dev(), stab(), blix()]).unwrap_err();
+        assert_eq!(error, "Step 2 (STAB) must be the last step");
+
+        let error = validate_steps(Process::C41, &[stab(), blix(), dev()]).unwrap_err();
+        assert_eq!(
+            error,
+            "Step 1 (STAB) must be the last step; Step 2 (BLIX) must come after a DEV step"
+        );
+
+        let error = validate_steps(Process::C41, &[blix(), stab()]).unwrap_err();
+        assert!(error.ends_with("C-41 program has no DEV step"), "{}", error);
+
+        // The ordering rules hold for black and white too.
+        let bw = [
+            step("BLIX", 18.0, 20.0, 24.0),
+            step("DEV", 18.0, 20.0, 24.0),
+        ];
+        assert!(validate_steps(Process::BlackAndWhite, &bw).is_err());
+    }
+
+    #[test]
+    fn auxiliary_steps_stay_in_the_process_range() {
+        let mut steps = c41(step("DEV", 37.65, 37.8, 37.95));
+        steps[3] = step("WASH", 40.0, 42.0, 43.0);
+        assert_eq!(
+            validate_steps(Process::C41, &steps),
+            Err("Step 4 (WASH) at 42°C is outside the C-41 range of 24-41°C".to_string())
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::db;
+use crate::process::Process;
+
+/// Mirrors `DEFAULT_TEMPLATE_STEPS` in the frontend constants.
+pub const DEFAULT_TEMPLATE_STEPS: [&str; 4] = ["DEV", "STOP", "FIX", "RINSE"];
+
+pub const RECIPE_COLUMNS: &str =
+    "id, name, film_stock, developer, dilution, category, notes, dev_time_reduced, created_at, updated_at";
+
+pub const STEP_COLUMNS: &str = "id, recipe_id, sort_order, name, time_min, time_sec, agitation, compensation, min_temperature, rated_temperature, max_temperature, formula_designator, logo_text";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Recipe {
+    pub id: String,
+    pub name: String,
+    pub film_stock: String,
+    pub developer: String,
+    pub dilution: String,
+    pub category: String,
+    pub notes: String,
+    pub dev_time_reduced: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    #[sqlx(skip)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Step {
+    pub id: String,
+    pub recipe_id: String,
+    pub sort_order: i64,
+    pub name: String,
+    pub time_min: i64,
+    pub time_sec: i64,
+    pub agitation: String,
+    pub compensation: String,
+    pub min_temperature: f64,
+    pub rated_temperature: f64,
+    pub max_temperature: f64,
+    pub formula_designator: String,
+    pub logo_text: String,
+}
+
+impl Step {
+    pub fn total_seconds(&self) -> i64 {
+        self.time_min * 60 + self.time_sec
+    }
+
+    pub fn set_total_seconds(&mut self, seconds: i64) {
+        let seconds = seconds.max(0);
+        self.time_min = seconds / 60;
+        self.time_sec = seconds % 60;
+    }
+}
+
+/// "9:45" for 585 seconds.
+pub fn format_duration(seconds: i64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The `default_*_temp` settings applied to new steps.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TemperatureDefaults {
+    pub min: f64,
+    pub rated: f64,
+    pub max: f64,
+}
+
+impl Default for TemperatureDefaults {
+    fn default() -> Self {
+        Self {
+            min: 18.0,
+            rated: 20.0,
+            max: 24.0,
+        }
+    }
+}
+
+impl TemperatureDefaults {
+    /// Keep the default window's offsets but centre it on another rated temperature.
+    pub fn rated_at(&self, rated: f64) -> Self {
+        Self {
+            min: rated - (self.rated - self.min),
+            rated,
+            max: rated + (self.max - self.rated),
+        }
+    }
+}
+
+pub async fn temperature_defaults(pool: &Pool<Sqlite>) -> Result<TemperatureDefaults, String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('default_min_temp', 'default_rated_temp', 'default_max_temp')",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load settings: {}", e))?;
+
+    let mut defaults = TemperatureDefaults::default();
+    for (key, value) in rows {
+        let Ok(value) = value.trim().parse::<f64>() else {
+            continue;
+        };
+        match key.as_str() {
+            "default_min_temp" => defaults.min = value,
+            "default_rated_temp" => defaults.rated = value,
+            "default_max_temp" => defaults.max = value,
+            _ => {}
+        }
+    }
+    Ok(defaults)
+}
+
+/// Same defaults as `defaultStep` in the frontend constants.
+pub fn default_step(name: &str, sort_order: i64, temps: TemperatureDefaults) -> Step {
+    let is_dev = name == "DEV";
+    let minutes = match name {
+        "DEV" => 0,
+        "STOP" => 1,
+        "FIX" => 5,
+        "RINSE" => 10,
+        _ => 5,
+    };
+
+    Step {
+        sort_order,
+        name: name.to_string(),
+        time_min: minutes,
+        time_sec: 0,
+        agitation: "Roll".to_string(),
+        compensation: if is_dev { "On" } else { "Off" }.to_string(),
+        min_temperature: temps.min,
+        rated_temperature: temps.rated,
+        max_temperature: temps.max,
+        formula_designator: if is_dev { "1.1.1" } else { "" }.to_string(),
+        logo_text: if is_dev { "B&W DEV" } else { "" }.to_string(),
+        ..Default::default()
+    }
+}
+
+/// A step of a colour process. Developers get the process tolerance around
+/// their rated temperature; other steps the process range, rated at the first
+/// developer's temperature.
+pub fn colour_step(name: &str, sort_order: i64, process: Process, dev_index: usize) -> Step {
+    let rated = process.developer_spec(0).map_or(0.0, |spec| spec.rated);
+    let (min, rated, max) = match process.developer_spec(dev_index) {
+        Some(spec) if name == "DEV" => (spec.min(), spec.rated, spec.max()),
+        _ => {
+            let (low, high) = process.auxiliary_range().unwrap_or((rated, rated));
+            (low, rated, high)
+        }
+    };
+    Step {
+        sort_order,
+        name: name.to_string(),
+        agitation: "Roll".to_string(),
+        compensation: "Off".to_string(),
+        min_temperature: min,
+        rated_temperature: rated,
+        max_temperature: max,
+        ..Default::default()
+    }
+}
+
+/// The steps of a colour process with their usual times, following the
+/// built-in C-41 and E-6 templates. ECN-2 ends like C-41, with a blix and a
+/// stabiliser. Empty for black and white.
+pub fn colour_steps(process: Process) -> Vec<Step> {
+    let plan: &[(&str, i64, &str)] = match process {
+        Process::BlackAndWhite => &[],
+        Process::C41 => &[
+            ("PRE", 300, ""),
+            ("DEV", 195, "C-41 DEV"),
+            ("BLIX", 390, ""),
+            ("WASH", 180, ""),
+            ("STAB", 60, ""),
+        ],
+        Process::E6 => &[
+            ("PRE", 300, ""),
+            ("DEV", 390, "FIRST DEV"),
+            ("WASH", 150, ""),
+            ("DEV", 360, "COLOR DEV"),
+            ("BLIX", 360, ""),
+            ("WASH", 240, ""),
+            ("STAB", 60, ""),
+        ],
+        Process::Ecn2 => &[
+            ("PRE", 60, ""),
+            ("DEV", 180, "ECN-2 DEV"),
+            ("STOP", 30, ""),
+            ("WASH", 60, ""),
+            ("BLIX", 390, ""),
+            ("WASH", 180, ""),
+            ("STAB", 60, ""),
+        ],
+    };
+    let mut dev_index = 0;
+    let mut steps = Vec::with_capacity(plan.len());
+    for (name, seconds, logo) in plan {
+        let mut step = colour_step(name, steps.len() as i64, process, dev_index);
+        step.set_total_seconds(*seconds);
+        step.logo_text = logo.to_string();
+        if *name == "DEV" {
+            dev_index += 1;
+        }
+        steps.push(step);
+    }
+    steps
+}
+
+/// A recipe with fresh ids and timestamps, ready for `insert_recipe`.
+pub fn new_recipe(name: &str) -> Recipe {
+    let now = db::now_iso();
+    Recipe {
+        id: db::new_id(),
+        name: name.to_string(),
+        category: "BW".to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+        ..Default::default()
+    }
+}
+
+/// Insert a recipe and its steps, assigning step ids and sort order.
+pub async fn insert_recipe(conn: &mut SqliteConnection, recipe: &mut Recipe) -> Result<(), String> {
+    sqlx::query(&format!(
+        "INSERT INTO recipes ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        RECIPE_COLUMNS
+    ))
+    .bind(&recipe.id)
+    .bind(&recipe.name)
+    .bind(&recipe.film_stock)
+    .bind(&recipe.developer)
+    .bind(&recipe.dilution)
+    .bind(&recipe.category)
+    .bind(&recipe.notes)
+    .bind(recipe.dev_time_reduced)
+    .bind(&recipe.created_at)
+    .bind(&recipe.updated_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to insert recipe: {}", e))?;
+
+    for (index, step) in recipe.steps.iter_mut().enumerate() {
+        step.id = db::new_id();
+        step.recipe_id = recipe.id.clone();
+        step.sort_order = index as i64;
+
+        sqlx::query(&format!(
+            "INSERT INTO steps ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            STEP_COLUMNS
+        ))
+        .bind(&step.id)
+        .bind(&step.recipe_id)
+        .bind(step.sort_order)
+        .bind(&step.name)
+        .bind(step.time_min)
+        .bind(step.time_sec)
+        .bind(&step.agitation)
+        .bind(&step.compensation)
+        .bind(step.min_temperature)
+        .bind(step.rated_temperature)
+        .bind(step.max_temperature)
+        .bind(&step.formula_designator)
+        .bind(&step.logo_text)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to insert step: {}", e))?;
+    }
+
+    Ok(())
+}
+
+async fn load_steps(pool: &Pool<Sqlite>, recipe_id: &str) -> Result<Vec<Step>, String> {
+    sqlx::query_as::<_, Step>(&format!(
+        "SELECT {} FROM steps WHERE recipe_id = ? ORDER BY sort_order",
+        STEP_COLUMNS
+    ))
+    .bind(recipe_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load steps: {}", e))
+}
+
+pub async fn load_recipe(pool: &Pool<Sqlite>, id: &str) -> Result<Recipe, String> {
+    let mut recipe = sqlx::query_as::<_, Recipe>(&format!(
+        "SELECT {} FROM recipes WHERE id = ?",
+        RECIPE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load recipe: {}", e))?
+    .ok_or_else(|| format!("Recipe {} not found", id))?;
+
+    recipe.steps = load_steps(pool, id).await?;
+    Ok(recipe)
+}
+
+pub async fn load_all_recipes(pool: &Pool<Sqlite>) -> Result<Vec<Recipe>, String> {
+    let mut recipes = sqlx::query_as::<_, Recipe>(&format!(
+        "SELECT {} FROM recipes ORDER BY updated_at DESC",
+        RECIPE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load recipes: {}", e))?;
+
+    for recipe in &mut recipes {
+        recipe.steps = load_steps(pool, &recipe.id).await?;
+    }
+    Ok(recipes)
+}
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chemistry::{self, ChemistryRequest};
+use crate::pdf::{self, Font, PdfWriter, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::process::Process;
+use crate::recipe::{self, Recipe};
+
+const MARGIN: f64 = 48.0;
+const CONTENT_WIDTH: f64 = PAGE_WIDTH - 2.0 * MARGIN;
+/// Lowest baseline before a new page is started; leaves room for the footer.
+const BOTTOM: f64 = PAGE_HEIGHT - 64.0;
+const ROW_HEIGHT: f64 = 20.0;
+
+/// Step table columns: heading and left edge relative to the margin.
+const STEP_COLUMNS: [(&str, f64); 6] = [
+    ("#", 0.0),
+    ("Step", 26.0),
+    ("Time", 100.0),
+    ("Agitation", 160.0),
+    ("Compensation", 240.0),
+    ("Temperature", 340.0),
+];
+
+/// Tank to print chemistry amounts for; mirrors the calculator's inputs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CardChemistry {
+    #[serde(default)]
+    pub tank: Option<String>,
+    #[serde(default)]
+    pub reel: Option<String>,
+    #[serde(default)]
+    pub volume_ml: Option<f64>,
+    #[serde(default)]
+    pub rolls: Option<f64>,
+    #[serde(default)]
+    pub min_fill_ml: Option<f64>,
+}
+
+fn format_temp(value: f64) -> String {
+    format!("{:.1}", value)
+}
+
+/// Lays out text top to bottom, starting a new page when the next block would
+/// run past the bottom margin.
+struct Layout {
+    pdf: PdfWriter,
+    y: f64,
+    heading: String,
+}
+
+impl Layout {
+    fn new(pdf: PdfWriter) -> Self {
+        Self {
+            pdf,
+            y: MARGIN,
+            heading: String::new(),
+        }
+    }
+
+    fn page(&mut self) {
+        self.pdf.new_page();
+        self.y = MARGIN;
+    }
+
+    /// Make room for `height` points, continuing on a new page if needed.
+    fn ensure(&mut self, height: f64) {
+        if self.y + height > BOTTOM {
+            self.page();
+            let heading = format!("{} (continued)", self.heading);
+            self.pdf
+                .text(MARGIN, self.y + 12.0, 12.0, Font::Bold, &heading);
+            self.y += 28.0;
+        }
+    }
+
+    fn text(&mut self, x: f64, size: f64, font: Font, text: &str) {
+        self.ensure(size * 1.4);
+        self.pdf.text(MARGIN + x, self.y + size, size, font, text);
+        self.y += size * 1.4;
+    }
+
+    fn section(&mut self, title: &str) {
+        self.ensure(40.0);
+        self.y += 10.0;
+        self.pdf
+            .text(MARGIN, self.y + 13.0, 13.0, Font::Bold, title);
+        self.y += 18.0;
+        self.pdf
+            .line(MARGIN, self.y, MARGIN + CONTENT_WIDTH, self.y, 0.75);
+        self.y += 6.0;
+    }
+
+    fn paragraph(&mut self, text: &str, size: f64) {
+        for line in pdf::wrap(text, Font::Regular, size, CONTENT_WIDTH) {
+            self.text(0.0, size, Font::Regular, &line);
+        }
+    }
+}
+
+fn draw_details(layout: &mut Layout, recipe: &Recipe) {
+    let process = Process::from_category(&recipe.category);
+    let details = [
+        ("Film", recipe.film_stock.as_str()),
+        ("Developer", recipe.developer.as_str()),
+        ("Dilution", recipe.dilution.as_str()),
+        ("Process", process.category()),
+    ];
+    for (label, value) in details {
+        let value = if value.trim().is_empty() { "-" } else { value };
+        layout.ensure(ROW_HEIGHT);
+        layout
+            .pdf
+            .text(MARGIN, layout.y + 12.0, 10.0, Font::Bold, label);
+        layout
+            .pdf
+            .text(MARGIN + 80.0, layout.y + 12.0, 12.0, Font::Regular, value);
+        layout.y += 18.0;
+    }
+}
+
+fn draw_steps(layout: &mut Layout, recipe: &Recipe) {
+    layout.section("Steps");
+
+    let header = |layout: &mut Layout| {
+        layout.ensure(ROW_HEIGHT * 2.0);
+        layout
+            .pdf
+            .fill_rect(MARGIN, layout.y, CONTENT_WIDTH, ROW_HEIGHT, 0.9);
+        for (title, x) in STEP_COLUMNS {
+            layout
+                .pdf
+                .text(MARGIN + x + 4.0, layout.y + 14.0, 10.0, Font::Bold, title);
+        }
+        layout.y += ROW_HEIGHT;
+    };
+    header(layout);
+
+    let mut steps = recipe.steps.iter().collect::<Vec<_>>();
+    steps.sort_by_key(|s| s.sort_order);
+    for (index, step) in steps.iter().enumerate() {
+        if layout.y + ROW_HEIGHT > BOTTOM {
+            layout.ensure(ROW_HEIGHT * 2.0);
+            header(layout);
+        }
+        let cells = [
+            (index + 1).to_string(),
+            step.name.clone(),
+            recipe::format_duration(step.total_seconds()),
+            step.agitation.clone(),
+            step.compensation.clone(),
+            format!(
+                "{}-{} °C (rated {})",
+                format_temp(step.min_temperature),
+                format_temp(step.max_temperature),
+                format_temp(step.rated_temperature)
+            ),
+        ];
+        for (column, ((_, x), cell)) in STEP_COLUMNS.iter().zip(&cells).enumerate() {
+            let font = if column == 1 {
+                Font::Bold
+            } else {
+                Font::Regular
+            };
+            layout
+                .pdf
+                .text(MARGIN + x + 4.0, layout.y + 14.0, 11.0, font, cell);
+        }
+        layout.y += ROW_HEIGHT;
+        layout
+            .pdf
+            .line(MARGIN, layout.y, MARGIN + CONTENT_WIDTH, layout.y, 0.25);
+    }
+
+    let total = steps.iter().map(|s| s.total_seconds()).sum::<i64>();
+    layout.ensure(ROW_HEIGHT);
+    layout.pdf.text(
+        MARGIN + STEP_COLUMNS[1].1 + 4.0,
+        layout.y + 14.0,
+        11.0,
+        Font::Bold,
+        "Total",
+    );
+    layout.pdf.text(
+        MARGIN + STEP_COLUMNS[2].1 + 4.0,
+        layout.y + 14.0,
+        11.0,
+        Font::Bold,
+        &recipe::format_duration(total),
+    );
+    layout.y += ROW_HEIGHT;
+}
+
+fn draw_chemistry(layout: &mut Layout, recipe: &Recipe, tank: &CardChemistry) {
+    let request = ChemistryRequest {
+        developer: recipe.developer.clone(),
+        dilution: recipe.dilution.clone(),
+        tank: tank.tank.clone(),
+        reel: tank.reel.clone(),
+        volume_ml: tank.volume_ml,
+        rolls: tank.rolls,
+        min_fill_ml: tank.min_fill_ml,
+    };
+
+    let validation = match chemistry::validate(&request) {
+        Ok(validation) => validation,
+        Err(e) => {
+            layout.section("Chemistry");
+            layout.paragraph(&format!("Amounts not calculated: {}", e), 11.0);
+            return;
+        }
+    };
+    let result = &validation.result;
+
+    // An explicit volume overrides the tank, so only name the tank when it set the fill.
+    let tank_label = match (&tank.tank, tank.volume_ml) {
+        (Some(name), None) => format!(
+            " for {} ({} reels)",
+            name,
+            tank.reel.as_deref().unwrap_or("35mm")
+        ),
+        _ => String::new(),
+    };
+    layout.section(&format!(
+        "Chemistry{}: {} ml",
+        tank_label,
+        chemistry::format_ml(result.total_ml)
+    ));
+    for part in &result.parts {
+        layout.text(
+            0.0,
+            12.0,
+            Font::Regular,
+            &format!("{}: {} ml", part.label, chemistry::format_ml(part.ml)),
+        );
+    }
+    layout.text(
+        0.0,
+        12.0,
+        Font::Regular,
+        &format!("Water: {} ml", chemistry::format_ml(result.water_ml)),
+    );
+    for issue in &validation.issues {
+        layout.paragraph(&format!("Warning: {}", issue.message), 10.0);
+    }
+    for suggestion in &validation.suggestions {
+        layout.paragraph(&format!("Suggestion: {}", suggestion.message), 10.0);
+    }
+}
+
+fn draw_recipe(layout: &mut Layout, recipe: &Recipe, chemistry: Option<&CardChemistry>) {
+    layout.heading = recipe.name.clone();
+    for line in pdf::wrap(&recipe.name, Font::Bold, 22.0, CONTENT_WIDTH) {
+        layout.text(0.0, 22.0, Font::Bold, &line);
+    }
+    layout.y += 8.0;
+
+    draw_details(layout, recipe);
+    draw_steps(layout, recipe);
+    if let Some(tank) = chemistry {
+        draw_chemistry(layout, recipe, tank);
+    }
+    if !recipe.notes.trim().is_empty() {
+        layout.section("Notes");
+        layout.paragraph(&recipe.notes, 11.0);
+    }
+}
+
+fn draw_footers(pdf: &mut PdfWriter, footer: &str) {
+    let total = pdf.page_count();
+    for page in 0..total {
+        let text = format!("{}  -  page {} of {}", footer, page + 1, total);
+        pdf.text_on(page, MARGIN, PAGE_HEIGHT - 32.0, 8.0, Font::Regular, &text);
+    }
+}
+
+fn draw_contents(recipes: &[Recipe], first_pages: &[usize]) -> PdfWriter {
+    let mut layout = Layout::new(PdfWriter::new());
+    layout.page();
+    layout.heading = "Recipes".to_string();
+    layout.text(0.0, 22.0, Font::Bold, "Recipes");
+    layout.y += 8.0;
+
+    for (recipe, page) in recipes.iter().zip(first_pages) {
+        let subtitle = [&recipe.film_stock, &recipe.developer, &recipe.dilution]
+            .iter()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(" / ");
+        layout.ensure(ROW_HEIGHT);
+        let page = (page + 1).to_string();
+        layout
+            .pdf
+            .text(MARGIN, layout.y + 12.0, 12.0, Font::Bold, &recipe.name);
+        layout.pdf.text(
+            MARGIN + CONTENT_WIDTH - pdf::text_width(&page, Font::Regular, 12.0),
+            layout.y + 12.0,
+            12.0,
+            Font::Regular,
+            &page,
+        );
+        layout.y += 15.0;
+        if !subtitle.is_empty() {
+            layout.pdf.text(
+                MARGIN + 12.0,
+                layout.y + 10.0,
+                10.0,
+                Font::Regular,
+                &subtitle,
+            );
+        }
+        layout.y += 9.0;
+    }
+    layout.pdf
+}
+
+/// A printable card per recipe, each starting on a new page. More than one
+/// recipe makes a booklet with a contents page in front. With `chemistry`,
+/// each card lists the developer amounts for that tank.
+pub fn render(recipes: &[Recipe], chemistry: Option<&CardChemistry>, generated: &str) -> Vec<u8> {
+    let booklet = recipes.len() > 1;
+    // Lay the contents out once to learn how many pages it takes.
+    let contents_pages = if booklet {
+        draw_contents(recipes, &vec![0; recipes.len()]).page_count()
+    } else {
+        0
+    };
+
+    let mut layout = Layout::new(PdfWriter::new());
+    let mut first_pages = Vec::with_capacity(recipes.len());
+    for recipe in recipes {
+        first_pages.push(contents_pages + layout.pdf.page_count());
+        layout.page();
+        draw_recipe(&mut layout, recipe, chemistry);
+    }
+    let cards = layout.pdf;
+
+    let mut pdf = if booklet {
+        let mut pdf = draw_contents(recipes, &first_pages);
+        pdf.append(cards);
+        pdf
+    } else {
+        cards
+    };
+
+    draw_footers(&mut pdf, &format!("AGO Recipe Manager - {}", generated));
+    let title = match recipes {
+        [recipe] => recipe.name.clone(),
+        _ => format!("{} recipes", recipes.len()),
+    };
+    pdf.finish(&title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(name: &str) -> Recipe {
+        let mut recipe = recipe::new_recipe(name);
+        recipe.category = "C-41".to_string();
+        recipe.film_stock = "Kodak Portra 400".to_string();
+        recipe.steps = recipe::colour_steps(Process::C41);
+        recipe
+    }
+
+    fn render_text(recipes: &[Recipe]) -> String {
+        String::from_utf8_lossy(&render(recipes, None, "2026-10-18")).into_owned()
+    }
+
+    #[test]
+    fn single_card_is_one_page_titled_after_the_recipe() {
+        let text = render_text(&[card("Portra (box speed)")]);
+        assert!(text.contains("/Count 1 >>"));
+        assert!(text.contains(r"/Title (Portra \(box speed\))"));
+        assert!(text.contains("(AGO Recipe Manager - 2026-10-18  -  page 1 of 1) Tj"));
+    }
+
+    #[test]
+    fn booklet_counts_contents_and_continued_pages() {
+        let mut long = card("Portra pushed");
+        long.notes = "Agitate gently for the first ten seconds. ".repeat(300);
+        let text = render_text(&[card("Portra (box speed)"), long]);
+
+        let pages = text.matches("/Type /Page /Parent").count();
+        // Contents, the first card, and the second card running over several pages.
+        assert!(pages > 3, "{}", pages);
+        assert!(text.contains(&format!("/Count {} >>", pages)));
+        assert!(text.contains(&format!("page {} of {}) Tj", pages, pages)));
+        assert!(!text.contains(&format!("page {} of", pages + 1)));
+        assert!(text.contains("(Portra pushed \\(continued\\)) Tj"));
+        assert!(text.contains("/Title (2 recipes)"));
+    }
+}
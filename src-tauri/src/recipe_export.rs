@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::ago_format::AgoProgramFile;
+use crate::process::Process;
+use crate::recipe::{self, Recipe, Step};
+use crate::share_code;
+
+const CSV_HEADER: [&str; 13] = [
+    "recipe",
+    "film_stock",
+    "developer",
+    "dilution",
+    "category",
+    "step",
+    "name",
+    "time",
+    "agitation",
+    "compensation",
+    "min_temperature",
+    "rated_temperature",
+    "max_temperature",
+];
+
+/// File formats `export_recipe_file` can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// The AGO program file, for importing back or uploading.
+    Ago,
+    Markdown,
+    /// One row per step.
+    Csv,
+    /// A short summary for messaging.
+    Text,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "ago" | "json" => Ok(ExportFormat::Ago),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "csv" => Ok(ExportFormat::Csv),
+            "text" | "txt" => Ok(ExportFormat::Text),
+            other => Err(format!(
+                "Unknown export format '{}', expected ago, markdown, csv or text",
+                other
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ago => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Text => "txt",
+        }
+    }
+
+    /// Name of the file dialog filter.
+    pub fn filter_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Ago => "JSON",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Text => "Text",
+        }
+    }
+
+    /// `name` as a file name with this format's extension, replacing the
+    /// extension of another export format. Other dots are part of the name
+    /// ("Rodinal 1.5 stops").
+    pub fn file_name(&self, name: &str) -> String {
+        let known = |ext: &str| {
+            [
+                ExportFormat::Ago,
+                ExportFormat::Markdown,
+                ExportFormat::Csv,
+                ExportFormat::Text,
+            ]
+            .iter()
+            .any(|f| ext.eq_ignore_ascii_case(f.extension()))
+        };
+        let stem = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() && known(ext) => stem,
+            _ => name,
+        };
+        share_code::export_file_name(stem, self.extension())
+    }
+}
+
+fn sorted_steps(recipe: &Recipe) -> Vec<&Step> {
+    let mut steps = recipe.steps.iter().collect::<Vec<_>>();
+    steps.sort_by_key(|s| s.sort_order);
+    steps
+}
+
+fn total_time(recipe: &Recipe) -> String {
+    recipe::format_duration(recipe.steps.iter().map(Step::total_seconds).sum())
+}
+
+fn temperature_window(step: &Step) -> String {
+    format!(
+        "{:.1}-{:.1} °C (rated {:.1})",
+        step.min_temperature, step.max_temperature, step.rated_temperature
+    )
+}
+
+fn markdown_cell(value: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value.replace('|', "\\|").replace('\n', " ")
+    }
+}
+
+pub fn to_markdown(recipe: &Recipe) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", recipe.name.trim());
+
+    out.push_str("| | |\n|---|---|\n");
+    for (label, value) in [
+        ("Film", recipe.film_stock.as_str()),
+        ("Developer", recipe.developer.as_str()),
+        ("Dilution", recipe.dilution.as_str()),
+        (
+            "Process",
+            Process::from_category(&recipe.category).category(),
+        ),
+    ] {
+        let _ = writeln!(out, "| **{}** | {} |", label, markdown_cell(value));
+    }
+
+    out.push_str("\n## Steps\n\n");
+    out.push_str("| # | Step | Time | Agitation | Compensation | Temperature |\n");
+    out.push_str("|---:|---|---:|---|---|---|\n");
+    for (index, step) in sorted_steps(recipe).into_iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} |",
+            index + 1,
+            markdown_cell(&step.name),
+            recipe::format_duration(step.total_seconds()),
+            markdown_cell(&step.agitation),
+            markdown_cell(&step.compensation),
+            temperature_window(step)
+        );
+    }
+    let _ = writeln!(out, "\n**Total:** {}", total_time(recipe));
+
+    if !recipe.notes.trim().is_empty() {
+        let _ = write!(out, "\n## Notes\n\n{}\n", recipe.notes.trim());
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(recipe: &Recipe) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for (index, step) in sorted_steps(recipe).into_iter().enumerate() {
+        let fields = [
+            recipe.name.clone(),
+            recipe.film_stock.clone(),
+            recipe.developer.clone(),
+            recipe.dilution.clone(),
+            recipe.category.clone(),
+            (index + 1).to_string(),
+            step.name.clone(),
+            recipe::format_duration(step.total_seconds()),
+            step.agitation.clone(),
+            step.compensation.clone(),
+            step.min_temperature.to_string(),
+            step.rated_temperature.to_string(),
+            step.max_temperature.to_string(),
+        ];
+        let row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// A few lines: name, what it develops, then one line per step.
+pub fn to_text(recipe: &Recipe) -> String {
+    let mut out = format!("{}\n", recipe.name.trim());
+    let chemistry = [&recipe.developer, &recipe.dilution]
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let summary = [
+        recipe.film_stock.trim(),
+        chemistry.as_str(),
+        Process::from_category(&recipe.category).category(),
+    ]
+    .iter()
+    .filter(|p| !p.is_empty())
+    .copied()
+    .collect::<Vec<_>>()
+    .join(" | ");
+    let _ = writeln!(out, "{}", summary);
+
+    for step in sorted_steps(recipe) {
+        let _ = write!(
+            out,
+            "{} {} {}",
+            step.name,
+            recipe::format_duration(step.total_seconds()),
+            step.agitation
+        );
+        let _ = writeln!(out, " @ {:.1}°C", step.rated_temperature);
+    }
+    let _ = writeln!(out, "Total {}", total_time(recipe));
+    if !recipe.notes.trim().is_empty() {
+        let _ = writeln!(out, "{}", recipe.notes.trim());
+    }
+    out
+}
+
+pub fn render(recipe: &Recipe, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Ago => AgoProgramFile::from_recipe(recipe).to_json(),
+        ExportFormat::Markdown => Ok(to_markdown(recipe)),
+        ExportFormat::Csv => Ok(to_csv(recipe)),
+        ExportFormat::Text => Ok(to_text(recipe)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(
+        name: &str,
+        sort_order: i64,
+        seconds: i64,
+        agitation: &str,
+        compensation: &str,
+    ) -> Step {
+        let mut step = Step {
+            name: name.to_string(),
+            sort_order,
+            agitation: agitation.to_string(),
+            compensation: compensation.to_string(),
+            min_temperature: 19.5,
+            rated_temperature: 20.0,
+            max_temperature: 20.5,
+            ..Default::default()
+        };
+        step.set_total_seconds(seconds);
+        step
+    }
+
+    fn sample() -> Recipe {
+        Recipe {
+            name: "HP5+ \"box\", 1/2 stop".to_string(),
+            film_stock: "Ilford HP5 Plus".to_string(),
+            developer: "Rodinal".to_string(),
+            dilution: "1+50".to_string(),
+            category: "BW".to_string(),
+            notes: "Stand for the last minute.".to_string(),
+            // Stored out of order; every format lists them by sort_order.
+            steps: vec![
+                step("FIX", 2, 300, "Every 30s | 10s", "Off"),
+                step("DEV", 0, 660, "Every 60s", "Temp"),
+                step("STOP", 1, 60, "Continuous", "Off"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        assert_eq!(csv_field("Every 60s"), "Every 60s");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+
+        let csv = to_csv(&sample());
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "\"HP5+ \"\"box\"\", 1/2 stop\",Ilford HP5 Plus,Rodinal,1+50,BW,1,DEV,11:00,Every 60s,Temp,19.5,20,20.5"
+        );
+        assert!(lines[2].contains(",2,STOP,1:00,"));
+        assert!(lines[3].contains(",3,FIX,5:00,Every 30s | 10s,Off,"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn markdown_escapes_table_cells() {
+        assert_eq!(markdown_cell(" a|b\nc "), "a\\|b c");
+        assert_eq!(markdown_cell("  "), "-");
+
+        let mut recipe = sample();
+        recipe.dilution = String::new();
+        let markdown = to_markdown(&recipe);
+        assert!(markdown.starts_with("# HP5+ \"box\", 1/2 stop\n"));
+        assert!(markdown.contains("| **Dilution** | - |"));
+        assert!(markdown.contains("| **Process** | BW |"));
+        assert!(
+            markdown.contains("| 1 | DEV | 11:00 | Every 60s | Temp | 19.5-20.5 °C (rated 20.0) |")
+        );
+        assert!(markdown.contains("| 3 | FIX | 5:00 | Every 30s \\| 10s | Off |"));
+        assert!(markdown.contains("**Total:** 17:00"));
+        assert!(markdown.ends_with("## Notes\n\nStand for the last minute.\n"));
+    }
+
+    #[test]
+    fn text_lists_every_step_with_its_temperature() {
+        assert_eq!(
+            to_text(&sample()),
+            "HP5+ \"box\", 1/2 stop\n\
+             Ilford HP5 Plus | Rodinal 1+50 | BW\n\
+             DEV 11:00 Every 60s @ 20.0°C\n\
+             STOP 1:00 Continuous @ 20.0°C\n\
+             FIX 5:00 Every 30s | 10s @ 20.0°C\n\
+             Total 17:00\n\
+             Stand for the last minute.\n"
+        );
+    }
+
+    #[test]
+    fn file_name_swaps_only_export_extensions() {
+        assert_eq!(ExportFormat::Csv.file_name("Tri-X.json"), "Tri-X.csv");
+        assert_eq!(ExportFormat::Markdown.file_name("Tri-X.TXT"), "Tri-X.md");
+        assert_eq!(ExportFormat::Ago.file_name("Tri-X"), "Tri-X.json");
+        assert_eq!(
+            ExportFormat::Text.file_name("Rodinal 1.5 stops"),
+            "Rodinal 1.5 stops.txt"
+        );
+        assert_eq!(
+            ExportFormat::Csv.file_name("Rodinal 1.5"),
+            "Rodinal 1.5.csv"
+        );
+        assert_eq!(
+            ExportFormat::Markdown.file_name("HP5+ 1/2 box.md"),
+            "HP5+ 1-2 box.md"
+        );
+        assert_eq!(ExportFormat::Csv.file_name(".csv"), "csv.csv");
+    }
+}
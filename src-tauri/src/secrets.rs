@@ -0,0 +1,402 @@
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::db;
+use crate::device;
+
+/// Service name credentials are filed under in the OS secret store.
+pub const SERVICE: &str = "com.francoisdekock.ago-recipe-manager";
+
+/// Set to a file path to keep secrets in an encrypted file instead of the OS
+/// store, e.g. in tests or on machines without a Secret Service.
+pub const FILE_STORE_ENV: &str = "AGO_SECRET_STORE_FILE";
+
+/// Plaintext password seeded by migration 1, moved into the store on startup.
+const LEGACY_PASSWORD_SETTING: &str = "ago_password";
+
+/// Where passwords and keys live. Accounts are plain names such as `wifi:AGO`.
+pub trait SecretStore: Send + Sync {
+    fn get(&self, account: &str) -> Result<Option<String>, String>;
+    fn set(&self, account: &str, secret: &str) -> Result<(), String>;
+    /// Removing a missing secret is not an error.
+    fn delete(&self, account: &str) -> Result<(), String>;
+}
+
+/// Keychain on macOS, Credential Manager on Windows, Secret Service on Linux.
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(account: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, account)
+            .map_err(|e| format!("Failed to open secret store: {}", e))
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        match Self::entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read secret: {}", e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        Self::entry(account)?
+            .set_password(secret)
+            .map_err(|e| format!("Failed to save secret: {}", e))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete secret: {}", e)),
+        }
+    }
+}
+
+/// Secrets sealed with ChaCha20-Poly1305 in a JSON file. The key sits next to
+/// it in `<file>.key`, so this guards against casual reading of the file, not
+/// against someone with access to both.
+pub struct FileStore {
+    path: PathBuf,
+    key: LessSafeKey,
+    lock: Mutex<()>,
+}
+
+impl FileStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let key_path = path.with_extension("key");
+        let key_bytes = match fs::read(&key_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let mut bytes = vec![0u8; CHACHA20_POLY1305.key_len()];
+                SystemRandom::new()
+                    .fill(&mut bytes)
+                    .map_err(|_| "Failed to generate secret store key".to_string())?;
+                if let Some(dir) = key_path.parent() {
+                    fs::create_dir_all(dir)
+                        .map_err(|e| format!("Failed to create secret store folder: {}", e))?;
+                }
+                write_private(&key_path, &bytes)?;
+                bytes
+            }
+            Err(e) => return Err(format!("Failed to read secret store key: {}", e)),
+        };
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key_bytes)
+            .map_err(|_| "Secret store key is invalid".to_string())?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            key: LessSafeKey::new(key),
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to read secret store: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(format!("Failed to read secret store: {}", e)),
+        }
+    }
+
+    fn save(&self, entries: &BTreeMap<String, String>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to save secret store: {}", e))?;
+        write_private(&self.path, json.as_bytes())
+    }
+
+    /// Nonce followed by ciphertext and tag, hex encoded. The account name is
+    /// bound in as associated data so entries cannot be swapped.
+    fn seal(&self, account: &str, secret: &str) -> Result<String, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "Failed to generate nonce".to_string())?;
+        let mut data = secret.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(account.as_bytes()),
+                &mut data,
+            )
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+        Ok(hex::encode([nonce.as_slice(), &data].concat()))
+    }
+
+    fn open_sealed(&self, account: &str, sealed: &str) -> Result<String, String> {
+        let bytes = hex::decode(sealed).map_err(|_| "Secret store entry is corrupt".to_string())?;
+        if bytes.len() < NONCE_LEN {
+            return Err("Secret store entry is corrupt".to_string());
+        }
+        let (nonce, data) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| "Secret store entry is corrupt".to_string())?;
+        let mut data = data.to_vec();
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::from(account.as_bytes()), &mut data)
+            .map_err(|_| "Failed to decrypt secret".to_string())?;
+        String::from_utf8(plain.to_vec()).map_err(|_| "Secret store entry is corrupt".to_string())
+    }
+}
+
+impl SecretStore for FileStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        match self.load()?.get(account) {
+            Some(sealed) => self.open_sealed(account, sealed).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut entries = self.load()?;
+        entries.insert(account.to_string(), self.seal(account, secret)?);
+        self.save(&entries)
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut entries = self.load()?;
+        if entries.remove(account).is_some() {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// The OS store, or the encrypted file named by `AGO_SECRET_STORE_FILE`.
+pub fn default_store() -> Result<Box<dyn SecretStore>, String> {
+    match std::env::var_os(FILE_STORE_ENV) {
+        Some(path) if !path.is_empty() => Ok(Box::new(FileStore::open(Path::new(&path))?)),
+        _ => Ok(Box::new(KeyringStore)),
+    }
+}
+
+pub fn wifi_account(ssid: &str) -> String {
+    format!("wifi:{}", ssid.trim())
+}
+
+pub fn wifi_password(store: &dyn SecretStore, ssid: &str) -> Result<Option<String>, String> {
+    store.get(&wifi_account(ssid))
+}
+
+/// Move the plaintext `ago_password` setting into the store under the default
+/// device's credential, then delete it. An existing stored password wins.
+pub async fn migrate_plaintext_password(
+    pool: &Pool<Sqlite>,
+    store: &dyn SecretStore,
+) -> Result<(), String> {
+    let Some(password) = db::setting(pool, LEGACY_PASSWORD_SETTING).await? else {
+        return Ok(());
+    };
+    // Migration 14 has already moved `ago_ssid` onto the default device.
+    let device: Option<(String, String)> =
+        sqlx::query_as("SELECT ssid, credential_ref FROM devices WHERE id = ?")
+            .bind(device::DEFAULT_DEVICE_ID)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load devices: {}", e))?;
+    let account = match device {
+        Some((_, credential)) if !credential.trim().is_empty() => credential.trim().to_string(),
+        Some((ssid, _)) => wifi_account(&ssid),
+        None => wifi_account("AGO"),
+    };
+    if !password.is_empty() && store.get(&account)?.is_none() {
+        store.set(&account, &password)?;
+    }
+    sqlx::query("DELETE FROM settings WHERE key = ?")
+        .bind(LEGACY_PASSWORD_SETTING)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to remove plaintext password: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "ago-secrets-{}-{}-{}",
+                name,
+                std::process::id(),
+                db::new_id()
+            ));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn file_store_round_trips_without_plaintext() {
+        let dir = TempDir::new("round-trip");
+        let path = dir.0.join("secrets.json");
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.get("wifi:AGO").unwrap(), None);
+
+        store.set("wifi:AGO", "hunter22").unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("hunter22"));
+
+        // A second handle reads the same key file.
+        let reopened = FileStore::open(&path).unwrap();
+        assert_eq!(
+            reopened.get("wifi:AGO").unwrap().as_deref(),
+            Some("hunter22")
+        );
+
+        reopened.delete("wifi:AGO").unwrap();
+        reopened.delete("wifi:Missing").unwrap();
+        assert_eq!(store.get("wifi:AGO").unwrap(), None);
+    }
+
+    #[test]
+    fn file_store_rejects_tampered_entries() {
+        let dir = TempDir::new("tamper");
+        let store = FileStore::open(&dir.0.join("secrets.json")).unwrap();
+        let sealed = store.seal("wifi:AGO", "hunter22").unwrap();
+
+        let mut bytes = hex::decode(&sealed).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        assert_eq!(
+            store.open_sealed("wifi:AGO", &hex::encode(&bytes)),
+            Err("Failed to decrypt secret".to_string())
+        );
+        assert!(store.open_sealed("wifi:AGO", "zz").is_err());
+        assert!(store.open_sealed("wifi:AGO", "00").is_err());
+        assert_eq!(store.open_sealed("wifi:AGO", &sealed).unwrap(), "hunter22");
+    }
+
+    #[test]
+    fn file_store_binds_entries_to_their_account() {
+        let dir = TempDir::new("aad");
+        let path = dir.0.join("secrets.json");
+        let store = FileStore::open(&path).unwrap();
+        store.set("wifi:AGO", "hunter22").unwrap();
+        store.set("wifi:Other", "other-pass").unwrap();
+
+        // Copy one account's entry over another's; it must not decrypt there.
+        let mut entries = store.load().unwrap();
+        let moved = entries["wifi:AGO"].clone();
+        entries.insert("wifi:Other".to_string(), moved);
+        store.save(&entries).unwrap();
+
+        assert_eq!(
+            store.get("wifi:Other"),
+            Err("Failed to decrypt secret".to_string())
+        );
+        assert_eq!(store.get("wifi:AGO").unwrap().as_deref(), Some("hunter22"));
+    }
+
+    async fn legacy_pool(ssid: &str, credential_ref: &str) -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE devices (id TEXT PRIMARY KEY, ssid TEXT NOT NULL, credential_ref TEXT NOT NULL DEFAULT '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO settings (key, value) VALUES (?, 'plain-pass')")
+            .bind(LEGACY_PASSWORD_SETTING)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO devices (id, ssid, credential_ref) VALUES (?, ?, ?)")
+            .bind(device::DEFAULT_DEVICE_ID)
+            .bind(ssid)
+            .bind(credential_ref)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn legacy_setting_count(pool: &Pool<Sqlite>) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM settings WHERE key = ?")
+            .bind(LEGACY_PASSWORD_SETTING)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn plaintext_password_moves_into_the_store() {
+        let dir = TempDir::new("migrate");
+        let store = FileStore::open(&dir.0.join("secrets.json")).unwrap();
+        block_on(async {
+            let pool = legacy_pool("AGO-7", "wifi:AGO-7").await;
+            migrate_plaintext_password(&pool, &store).await.unwrap();
+            assert_eq!(
+                wifi_password(&store, "AGO-7").unwrap().as_deref(),
+                Some("plain-pass")
+            );
+            assert_eq!(legacy_setting_count(&pool).await, 0);
+
+            // Running again with nothing left to move is a no-op.
+            migrate_plaintext_password(&pool, &store).await.unwrap();
+        });
+    }
+
+    #[test]
+    fn plaintext_password_does_not_replace_a_stored_one() {
+        let dir = TempDir::new("migrate-keep");
+        let store = FileStore::open(&dir.0.join("secrets.json")).unwrap();
+        store.set("wifi:Lab", "stored-pass").unwrap();
+        block_on(async {
+            // No credential reference: the account comes from the device's SSID.
+            let pool = legacy_pool("Lab", "").await;
+            migrate_plaintext_password(&pool, &store).await.unwrap();
+            assert_eq!(
+                wifi_password(&store, "Lab").unwrap().as_deref(),
+                Some("stored-pass")
+            );
+            assert_eq!(legacy_setting_count(&pool).await, 0);
+        });
+    }
+}
@@ -0,0 +1,447 @@
+use qrcode::{EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+use crate::recipe::{self, Recipe, Step};
+
+/// Share codes start with this so a scanned code is recognisable.
+pub const SHARE_PREFIX: &str = "AGO:";
+
+/// Binary layout version, the first byte of every code.
+const SHARE_VERSION: u8 = 1;
+
+/// Flag: the body after the flags byte is DEFLATE compressed.
+const FLAG_DEFLATE: u8 = 0b0000_0001;
+
+/// Decompressed bodies larger than this are rejected.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Pixels per QR module in PNG output, and the blank border in modules.
+const PNG_SCALE: usize = 8;
+const QUIET_ZONE: usize = 4;
+
+/// Agitation and compensation values that fit in one byte; anything else is
+/// written as `OTHER` followed by the text.
+const AGITATIONS: [&str; 4] = ["Roll", "Stick", "Stand", "Off"];
+const COMPENSATIONS: [&str; 3] = ["On", "Mon", "Off"];
+const OTHER: u8 = 0xff;
+
+/// A recipe's share code and the QR code for it, ready to show on screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareCode {
+    pub code: String,
+    pub svg: String,
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_varint(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Temperatures go as signed tenths of a degree.
+fn write_temp(out: &mut Vec<u8>, value: f64) {
+    let tenths = (value * 10.0).round() as i64;
+    write_varint(out, ((tenths << 1) ^ (tenths >> 63)) as u64);
+}
+
+fn write_choice(out: &mut Vec<u8>, value: &str, choices: &[&str]) {
+    match choices.iter().position(|c| *c == value) {
+        Some(index) => out.push(index as u8),
+        None => {
+            out.push(OTHER);
+            write_str(out, value);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| "Share code is truncated".to_string())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Share code has an invalid number".to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.varint()? as usize;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "Share code is truncated".to_string())?;
+        let text = std::str::from_utf8(&self.bytes[self.pos..end])
+            .map_err(|_| "Share code has invalid text".to_string())?;
+        self.pos = end;
+        Ok(text.to_string())
+    }
+
+    fn temp(&mut self) -> Result<f64, String> {
+        let raw = self.varint()?;
+        let tenths = ((raw >> 1) as i64) ^ -((raw & 1) as i64);
+        Ok(tenths as f64 / 10.0)
+    }
+
+    fn choice(&mut self, choices: &[&str]) -> Result<String, String> {
+        match self.byte()? {
+            OTHER => self.string(),
+            index => choices
+                .get(index as usize)
+                .map(|c| c.to_string())
+                .ok_or_else(|| "Share code has an unknown step setting".to_string()),
+        }
+    }
+}
+
+fn encode_body(recipe: &Recipe) -> Vec<u8> {
+    let mut out = Vec::new();
+    for field in [
+        &recipe.name,
+        &recipe.film_stock,
+        &recipe.developer,
+        &recipe.dilution,
+        &recipe.category,
+        &recipe.notes,
+    ] {
+        write_str(&mut out, field);
+    }
+    write_varint(&mut out, recipe.dev_time_reduced.max(0) as u64);
+
+    let mut steps = recipe.steps.iter().collect::<Vec<_>>();
+    steps.sort_by_key(|s| s.sort_order);
+    write_varint(&mut out, steps.len() as u64);
+    for step in steps {
+        write_str(&mut out, &step.name);
+        write_varint(&mut out, step.total_seconds().max(0) as u64);
+        write_choice(&mut out, &step.agitation, &AGITATIONS);
+        write_choice(&mut out, &step.compensation, &COMPENSATIONS);
+        write_temp(&mut out, step.min_temperature);
+        write_temp(&mut out, step.rated_temperature);
+        write_temp(&mut out, step.max_temperature);
+        write_str(&mut out, &step.formula_designator);
+        write_str(&mut out, &step.logo_text);
+    }
+    out
+}
+
+fn decode_body(body: &[u8]) -> Result<Recipe, String> {
+    let mut reader = Reader {
+        bytes: body,
+        pos: 0,
+    };
+    let mut recipe = recipe::new_recipe(&reader.string()?);
+    recipe.film_stock = reader.string()?;
+    recipe.developer = reader.string()?;
+    recipe.dilution = reader.string()?;
+    recipe.category = reader.string()?;
+    recipe.notes = reader.string()?;
+    recipe.dev_time_reduced = reader.varint()? as i64;
+
+    let count = reader.varint()? as usize;
+    if count > body.len() {
+        return Err("Share code has an invalid step count".to_string());
+    }
+    for index in 0..count {
+        let mut step = Step {
+            sort_order: index as i64,
+            name: reader.string()?,
+            ..Default::default()
+        };
+        step.set_total_seconds(reader.varint()? as i64);
+        step.agitation = reader.choice(&AGITATIONS)?;
+        step.compensation = reader.choice(&COMPENSATIONS)?;
+        step.min_temperature = reader.temp()?;
+        step.rated_temperature = reader.temp()?;
+        step.max_temperature = reader.temp()?;
+        step.formula_designator = reader.string()?;
+        step.logo_text = reader.string()?;
+        recipe.steps.push(step);
+    }
+    if reader.pos != body.len() {
+        return Err("Share code has trailing data".to_string());
+    }
+    Ok(recipe)
+}
+
+/// Pack a recipe into `AGO:` plus base45 of version, flags, the (possibly
+/// compressed) body and a CRC-32. Base45 keeps QR codes in alphanumeric mode.
+pub fn encode(recipe: &Recipe) -> String {
+    let body = encode_body(recipe);
+    let compressed = miniz_oxide::deflate::compress_to_vec(&body, 9);
+    let (flags, body) = if compressed.len() < body.len() {
+        (FLAG_DEFLATE, compressed)
+    } else {
+        (0, body)
+    };
+
+    let mut bytes = vec![SHARE_VERSION, flags];
+    bytes.extend_from_slice(&body);
+    let checksum = crc32fast::hash(&bytes);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+    format!("{}{}", SHARE_PREFIX, base45::encode(&bytes))
+}
+
+/// Unpack a share code into a fresh, unsaved recipe. The prefix is optional so
+/// codes pasted without it still work.
+pub fn decode(code: &str) -> Result<Recipe, String> {
+    // Space is a base45 digit, so only line breaks and tabs are trimmed from the data.
+    let code = code.trim_matches(|c: char| c != ' ' && c.is_whitespace());
+    let prefixed = code.trim_start();
+    let payload = prefixed
+        .strip_prefix(SHARE_PREFIX)
+        .or_else(|| prefixed.strip_prefix(&SHARE_PREFIX.to_lowercase()))
+        .unwrap_or(code);
+    let bytes = base45::decode(payload).map_err(|e| format!("Invalid share code: {:?}", e))?;
+    if bytes.len() < 6 {
+        return Err("Share code is too short".to_string());
+    }
+
+    let (data, checksum) = bytes.split_at(bytes.len() - 4);
+    let expected = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    if crc32fast::hash(data) != expected {
+        return Err("Share code checksum does not match; it may be mistyped".to_string());
+    }
+    if data[0] != SHARE_VERSION {
+        return Err(format!(
+            "Share code version {} is not supported by this version of the app",
+            data[0]
+        ));
+    }
+
+    let flags = data[1];
+    let body = if flags & FLAG_DEFLATE != 0 {
+        miniz_oxide::inflate::decompress_to_vec_with_limit(&data[2..], MAX_BODY_BYTES)
+            .map_err(|_| "Share code could not be decompressed".to_string())?
+    } else {
+        data[2..].to_vec()
+    };
+    decode_body(&body)
+}
+
+/// A file name for a saved QR code or recipe card. Recipe names may hold `/`
+/// ("HP5+ 1/2 box speed") and other characters that are not allowed in file
+/// names, so those become `-`.
+pub fn export_file_name(name: &str, extension: &str) -> String {
+    let stem = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect::<String>();
+    let stem = stem.trim().trim_matches('.').trim();
+    let stem = if stem.is_empty() { "recipe" } else { stem };
+    format!("{}.{}", stem, extension)
+}
+
+fn qr_code(code: &str) -> Result<QrCode, String> {
+    QrCode::with_error_correction_level(code, EcLevel::M)
+        .map_err(|e| format!("Recipe is too large for a QR code: {}", e))
+}
+
+pub fn qr_svg(code: &str) -> Result<String, String> {
+    Ok(qr_code(code)?
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build())
+}
+
+/// Grayscale PNG of the QR code with the standard quiet zone.
+pub fn qr_png(code: &str) -> Result<Vec<u8>, String> {
+    let qr = qr_code(code)?;
+    let modules = qr.width();
+    let colors = qr.to_colors();
+    let size = (modules + 2 * QUIET_ZONE) * PNG_SCALE;
+
+    let mut pixels = vec![255u8; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != qrcode::Color::Dark {
+            continue;
+        }
+        let (mx, my) = (index % modules + QUIET_ZONE, index / modules + QUIET_ZONE);
+        for y in my * PNG_SCALE..(my + 1) * PNG_SCALE {
+            pixels[y * size + mx * PNG_SCALE..y * size + (mx + 1) * PNG_SCALE].fill(0);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to write PNG: {}", e))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_recipe() -> Recipe {
+        let mut recipe = recipe::new_recipe("Cold stand");
+        recipe.film_stock = "Tri-X 400".to_string();
+        recipe.developer = "Rodinal".to_string();
+        recipe.dilution = "1+100".to_string();
+        recipe.category = "BW".to_string();
+        recipe.notes = "Garage in winter".to_string();
+        recipe.steps = vec![
+            Step {
+                sort_order: 0,
+                name: "DEV".to_string(),
+                time_min: 60,
+                agitation: "Semi-stand".to_string(),
+                compensation: "Auto".to_string(),
+                min_temperature: -2.5,
+                rated_temperature: 0.0,
+                max_temperature: 3.1,
+                ..Default::default()
+            },
+            Step {
+                sort_order: 1,
+                name: "FIX".to_string(),
+                time_min: 5,
+                time_sec: 30,
+                agitation: "Roll".to_string(),
+                compensation: "Off".to_string(),
+                min_temperature: -40.0,
+                rated_temperature: 20.0,
+                max_temperature: 24.0,
+                formula_designator: "1.1.1".to_string(),
+                logo_text: "FIX".to_string(),
+                ..Default::default()
+            },
+        ];
+        recipe
+    }
+
+    /// The code without its prefix, as raw bytes: version, flags, body, CRC-32.
+    fn code_bytes(code: &str) -> Vec<u8> {
+        base45::decode(code.strip_prefix(SHARE_PREFIX).unwrap()).unwrap()
+    }
+
+    fn code_from_bytes(bytes: &[u8]) -> String {
+        format!("{}{}", SHARE_PREFIX, base45::encode(bytes))
+    }
+
+    #[test]
+    fn round_trips_other_settings_and_negative_temperatures() {
+        let recipe = sample_recipe();
+        let decoded = decode(&encode(&recipe)).unwrap();
+
+        assert_eq!(decoded.name, recipe.name);
+        assert_eq!(decoded.film_stock, recipe.film_stock);
+        assert_eq!(decoded.developer, recipe.developer);
+        assert_eq!(decoded.dilution, recipe.dilution);
+        assert_eq!(decoded.notes, recipe.notes);
+        assert_eq!(decoded.steps.len(), 2);
+        for (before, after) in recipe.steps.iter().zip(&decoded.steps) {
+            assert_eq!(after.name, before.name);
+            assert_eq!(after.total_seconds(), before.total_seconds());
+            assert_eq!(after.agitation, before.agitation);
+            assert_eq!(after.compensation, before.compensation);
+            assert_eq!(after.min_temperature, before.min_temperature);
+            assert_eq!(after.rated_temperature, before.rated_temperature);
+            assert_eq!(after.max_temperature, before.max_temperature);
+            assert_eq!(after.formula_designator, before.formula_designator);
+            assert_eq!(after.logo_text, before.logo_text);
+        }
+    }
+
+    #[test]
+    fn one_character_typo_fails_the_checksum() {
+        let code = encode(&sample_recipe());
+        let payload_start = SHARE_PREFIX.len();
+        for offset in [0, 7, code.len() - payload_start - 1] {
+            let mut chars = code.chars().collect::<Vec<_>>();
+            let index = payload_start + offset;
+            chars[index] = if chars[index] == 'A' { 'B' } else { 'A' };
+            let typo = chars.into_iter().collect::<String>();
+            let error = decode(&typo).unwrap_err();
+            assert!(
+                error.contains("checksum"),
+                "typo at {} gave: {}",
+                offset,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_codes_are_rejected() {
+        let code = encode(&sample_recipe());
+        for cut in [SHARE_PREFIX.len() + 3, code.len() / 2, code.len() - 3] {
+            assert!(
+                decode(&code[..cut]).is_err(),
+                "accepted a code cut at {}",
+                cut
+            );
+        }
+
+        // A body that ends early is caught even behind a valid checksum.
+        let mut bytes = vec![SHARE_VERSION, 0];
+        bytes.extend_from_slice(&encode_body(&sample_recipe())[..10]);
+        let checksum = crc32fast::hash(&bytes);
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+        assert_eq!(
+            decode(&code_from_bytes(&bytes)).unwrap_err(),
+            "Share code is truncated"
+        );
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut bytes = code_bytes(&encode(&sample_recipe()));
+        let data_len = bytes.len() - 4;
+        bytes[0] = SHARE_VERSION + 1;
+        let checksum = crc32fast::hash(&bytes[..data_len]);
+        bytes[data_len..].copy_from_slice(&checksum.to_be_bytes());
+
+        let error = decode(&code_from_bytes(&bytes)).unwrap_err();
+        assert!(error.contains("version 2 is not supported"), "{}", error);
+    }
+
+    #[test]
+    fn export_file_names_drop_path_separators() {
+        assert_eq!(
+            export_file_name("HP5+ 1/2 box speed", "png"),
+            "HP5+ 1-2 box speed.png"
+        );
+        assert_eq!(export_file_name("a\\b:c", "svg"), "a-b-c.svg");
+        assert_eq!(export_file_name("../..", "png"), "-.png");
+        assert_eq!(export_file_name("  ", "png"), "recipe.png");
+    }
+}
@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::wifi;
+use crate::db;
+use crate::device;
+
+pub const CONNECTED_EVENT: &str = "ago://connected";
+pub const LOST_EVENT: &str = "ago://lost";
+pub const RESTORED_EVENT: &str = "ago://restored";
+
+pub const IDLE_MINUTES_SETTING: &str = "reconnect_idle_minutes";
+const DEFAULT_IDLE_MINUTES: u64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// What the supervisor last saw. Sent as the payload of every event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionState {
+    /// The active device's programs API answers.
+    pub connected: bool,
+    pub device_id: String,
+    /// Wi-Fi interface in use; empty when none was found.
+    pub interface: String,
+    pub current_network: String,
+    /// Network to go back to once done with the AGO.
+    pub previous_network: Option<String>,
+}
+
+struct Watch {
+    state: ConnectionState,
+    auto_reconnect: bool,
+    idle_after: Option<Duration>,
+    last_activity: Instant,
+}
+
+/// Watches the Wi-Fi network and the active device from the Rust side, so
+/// reconnecting does not depend on the webview being alive.
+pub struct Supervisor {
+    watch: Mutex<Watch>,
+    holds: AtomicUsize,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Supervisor {
+            watch: Mutex::new(Watch {
+                state: ConnectionState::default(),
+                auto_reconnect: true,
+                idle_after: Some(Duration::from_secs(DEFAULT_IDLE_MINUTES * 60)),
+                last_activity: Instant::now(),
+            }),
+            holds: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Supervisor {
+    pub fn state(&self) -> ConnectionState {
+        self.watch.lock().unwrap().state.clone()
+    }
+
+    fn touch(&self) {
+        self.watch.lock().unwrap().last_activity = Instant::now();
+    }
+
+    /// Remember the network to return to, unless it is one of the AGO networks.
+    fn remember(&self, ssid: &str, ago_networks: &[String]) {
+        if ssid.is_empty() || ago_networks.iter().any(|n| n == ssid) {
+            return;
+        }
+        self.watch.lock().unwrap().state.previous_network = Some(ssid.to_string());
+    }
+
+    fn idle_expired(&self) -> bool {
+        let watch = self.watch.lock().unwrap();
+        let Some(idle_after) = watch.idle_after else {
+            return false;
+        };
+        watch.state.connected
+            && watch.auto_reconnect
+            && watch.state.previous_network.is_some()
+            && self.holds.load(Ordering::SeqCst) == 0
+            && watch.last_activity.elapsed() >= idle_after
+    }
+}
+
+/// Keeps the connection counted as in use until dropped, so the idle timer
+/// does not switch networks in the middle of an operation.
+pub struct Hold {
+    app: AppHandle,
+}
+
+impl Drop for Hold {
+    fn drop(&mut self) {
+        let supervisor = self.app.state::<Supervisor>();
+        supervisor.holds.fetch_sub(1, Ordering::SeqCst);
+        supervisor.touch();
+    }
+}
+
+pub fn hold(app: &AppHandle) -> Hold {
+    let supervisor = app.state::<Supervisor>();
+    supervisor.holds.fetch_add(1, Ordering::SeqCst);
+    supervisor.touch();
+    Hold { app: app.clone() }
+}
+
+/// Record the network in use before joining an AGO.
+pub fn remember_network(app: &AppHandle, ssid: &str, ago_network: &str) {
+    app.state::<Supervisor>()
+        .remember(ssid, &[ago_network.to_string()]);
+}
+
+/// Poll until the app exits. Started once from `setup`.
+pub async fn run(app: AppHandle) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = check(&app).await {
+            log::warn!("Connection check failed: {}", e);
+            continue;
+        }
+        if app.state::<Supervisor>().idle_expired() {
+            if let Err(e) = restore(&app) {
+                log::error!("Failed to restore the previous network: {}", e);
+            }
+        }
+    }
+}
+
+/// Read the current network and whether the active device answers, and emit
+/// `ago://connected` or `ago://lost` when that changed.
+pub async fn check(app: &AppHandle) -> Result<ConnectionState, String> {
+    let pool = db::pool(app).await?;
+    let device_id = db::setting(&pool, "active_device_id")
+        .await?
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| device::DEFAULT_DEVICE_ID.to_string());
+    let auto_reconnect = db::setting(&pool, "auto_reconnect").await?.as_deref() != Some("false");
+    let idle_minutes = db::setting(&pool, IDLE_MINUTES_SETTING)
+        .await?
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_IDLE_MINUTES);
+    let devices = device::load_all_devices(&pool).await?;
+    let ago_networks = devices.iter().map(|d| d.ssid.clone()).collect::<Vec<_>>();
+    let device = devices.into_iter().find(|d| d.id == device_id);
+
+    let supervisor = app.state::<Supervisor>();
+    let known_interface = supervisor.state().interface;
+    let (interface, current_network) =
+        tokio::task::spawn_blocking(move || read_network(known_interface))
+            .await
+            .map_err(|e| format!("Failed to read the Wi-Fi network: {}", e))?;
+    let reachable = match &device {
+        Some(device) => wifi::is_reachable(device).await?,
+        None => false,
+    };
+    if !reachable {
+        supervisor.remember(&current_network, &ago_networks);
+    }
+
+    let (event, state) = {
+        let mut watch = supervisor.watch.lock().unwrap();
+        watch.auto_reconnect = auto_reconnect;
+        watch.idle_after = (idle_minutes > 0).then(|| Duration::from_secs(idle_minutes * 60));
+        // Switching devices starts over rather than reporting the old one lost.
+        let was_connected = watch.state.connected && watch.state.device_id == device_id;
+        let event = match (was_connected, reachable) {
+            (false, true) => {
+                watch.last_activity = Instant::now();
+                Some(CONNECTED_EVENT)
+            }
+            (true, false) => Some(LOST_EVENT),
+            _ => None,
+        };
+        watch.state.connected = reachable;
+        watch.state.device_id = device_id;
+        watch.state.interface = interface;
+        watch.state.current_network = current_network;
+        (event, watch.state.clone())
+    };
+    if let Some(event) = event {
+        emit(app, event, &state);
+    }
+    Ok(state)
+}
+
+/// The Wi-Fi interface, looked up when not known yet, and the network it is
+/// joined to. Both come from `networksetup`, which only macOS has; elsewhere
+/// both are empty. Blocks; run it off the async runtime.
+fn read_network(interface: String) -> (String, String) {
+    if !cfg!(target_os = "macos") {
+        return (String::new(), String::new());
+    }
+    let interface = if interface.is_empty() {
+        wifi::wifi_interface().unwrap_or_default()
+    } else {
+        interface
+    };
+    let current_network = if interface.is_empty() {
+        String::new()
+    } else {
+        wifi::current_network(&interface).unwrap_or_default()
+    };
+    (interface, current_network)
+}
+
+/// Rejoin the remembered network and emit `ago://restored`. Returns the SSID
+/// rejoined, or `None` when there was nothing to go back to.
+pub fn restore(app: &AppHandle) -> Result<Option<String>, String> {
+    let supervisor = app.state::<Supervisor>();
+    let ConnectionState {
+        interface,
+        previous_network,
+        ..
+    } = supervisor.state();
+    let Some(ssid) = previous_network.filter(|_| !interface.is_empty()) else {
+        return Ok(None);
+    };
+    wifi::reconnect(&interface, &ssid)?;
+    mark_restored(app, &ssid);
+    Ok(Some(ssid))
+}
+
+/// Note that `ssid` was rejoined after using an AGO and emit `ago://restored`.
+pub fn mark_restored(app: &AppHandle, ssid: &str) {
+    let state = {
+        let supervisor = app.state::<Supervisor>();
+        let mut watch = supervisor.watch.lock().unwrap();
+        watch.state.connected = false;
+        watch.state.current_network = ssid.to_string();
+        watch.state.previous_network = None;
+        watch.state.clone()
+    };
+    emit(app, RESTORED_EVENT, &state);
+}
+
+/// The webview may be gone; the supervisor carries on without it.
+fn emit(app: &AppHandle, event: &str, state: &ConnectionState) {
+    if let Err(e) = app.emit(event, state) {
+        log::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// On quit, leave the AGO network if auto-reconnect is on.
+pub fn restore_on_exit(app: &AppHandle) {
+    let supervisor = app.state::<Supervisor>();
+    let should_restore = {
+        let watch = supervisor.watch.lock().unwrap();
+        watch.state.connected && watch.auto_reconnect
+    };
+    if !should_restore {
+        return;
+    }
+    match restore(app) {
+        Ok(Some(ssid)) => log::info!("Rejoined {} on exit", ssid),
+        Ok(None) => {}
+        Err(e) => log::error!("Failed to restore the previous network on exit: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ago_networks() -> Vec<String> {
+        vec!["AGO-3F2A".to_string()]
+    }
+
+    /// Connected to an AGO with a network to go back to, and no idle grace.
+    fn idle_supervisor() -> Supervisor {
+        let supervisor = Supervisor::default();
+        supervisor.remember("Studio", &ago_networks());
+        {
+            let mut watch = supervisor.watch.lock().unwrap();
+            watch.state.connected = true;
+            watch.idle_after = Some(Duration::ZERO);
+        }
+        supervisor
+    }
+
+    #[test]
+    fn remembers_only_networks_to_go_back_to() {
+        let supervisor = Supervisor::default();
+        supervisor.remember("", &ago_networks());
+        supervisor.remember("AGO-3F2A", &ago_networks());
+        assert_eq!(supervisor.state().previous_network, None);
+
+        supervisor.remember("Studio", &ago_networks());
+        supervisor.remember("AGO-3F2A", &ago_networks());
+        assert_eq!(
+            supervisor.state().previous_network.as_deref(),
+            Some("Studio")
+        );
+    }
+
+    #[test]
+    fn idle_expires_only_when_nothing_holds_the_connection() {
+        assert!(idle_supervisor().idle_expired());
+        assert!(!Supervisor::default().idle_expired());
+
+        let held = idle_supervisor();
+        held.holds.fetch_add(1, Ordering::SeqCst);
+        assert!(!held.idle_expired());
+
+        let recent = idle_supervisor();
+        recent.watch.lock().unwrap().idle_after = Some(Duration::from_secs(600));
+        recent.touch();
+        assert!(!recent.idle_expired());
+
+        let never = idle_supervisor();
+        never.watch.lock().unwrap().idle_after = None;
+        assert!(!never.idle_expired());
+
+        let manual = idle_supervisor();
+        manual.watch.lock().unwrap().auto_reconnect = false;
+        assert!(!manual.idle_expired());
+
+        let nowhere_to_go = idle_supervisor();
+        nowhere_to_go.watch.lock().unwrap().state.previous_network = None;
+        assert!(!nowhere_to_go.idle_expired());
+
+        let disconnected = idle_supervisor();
+        disconnected.watch.lock().unwrap().state.connected = false;
+        assert!(!disconnected.idle_expired());
+    }
+}
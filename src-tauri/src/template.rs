@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::db;
+use crate::recipe::{self, Recipe, Step};
+
+const TEMPLATE_COLUMNS: &str = "id, name, category, description, builtin, created_at, updated_at";
+
+const TEMPLATE_STEP_COLUMNS: &str = "id, template_id, sort_order, name, time_min, time_sec, agitation, compensation, min_temperature, rated_temperature, max_temperature, formula_designator, logo_text";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub description: String,
+    /// Shipped with the app rather than created by the user.
+    pub builtin: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    #[sqlx(skip)]
+    pub steps: Vec<TemplateStep>,
+}
+
+/// A step skeleton; same fields as a recipe step, owned by a template.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(default)]
+pub struct TemplateStep {
+    pub id: String,
+    pub template_id: String,
+    pub sort_order: i64,
+    pub name: String,
+    pub time_min: i64,
+    pub time_sec: i64,
+    pub agitation: String,
+    pub compensation: String,
+    pub min_temperature: f64,
+    pub rated_temperature: f64,
+    pub max_temperature: f64,
+    pub formula_designator: String,
+    pub logo_text: String,
+}
+
+impl TemplateStep {
+    /// A recipe step with the template's settings; ids are assigned by `insert_recipe`.
+    pub fn to_step(&self) -> Step {
+        Step {
+            sort_order: self.sort_order,
+            name: self.name.clone(),
+            time_min: self.time_min,
+            time_sec: self.time_sec,
+            agitation: self.agitation.clone(),
+            compensation: self.compensation.clone(),
+            min_temperature: self.min_temperature,
+            rated_temperature: self.rated_temperature,
+            max_temperature: self.max_temperature,
+            formula_designator: self.formula_designator.clone(),
+            logo_text: self.logo_text.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<TemplateStep>,
+}
+
+/// Check a template's name and steps before saving.
+pub fn validate(name: &str, steps: &[TemplateStep]) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Template name is required".to_string());
+    }
+    if steps.is_empty() {
+        return Err("Template needs at least one step".to_string());
+    }
+    for (index, step) in steps.iter().enumerate() {
+        if step.name.trim().is_empty() {
+            return Err(format!("Step {} has no name", index + 1));
+        }
+        if step.time_min < 0 || !(0..60).contains(&step.time_sec) {
+            return Err(format!("Step {} ({}) has an invalid time", index + 1, step.name));
+        }
+        if step.min_temperature > step.rated_temperature
+            || step.rated_temperature > step.max_temperature
+        {
+            return Err(format!(
+                "Step {} ({}) needs min <= rated <= max temperature",
+                index + 1,
+                step.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn load_template_steps(
+    pool: &Pool<Sqlite>,
+    template_id: &str,
+) -> Result<Vec<TemplateStep>, String> {
+    sqlx::query_as::<_, TemplateStep>(&format!(
+        "SELECT {} FROM template_steps WHERE template_id = ? ORDER BY sort_order",
+        TEMPLATE_STEP_COLUMNS
+    ))
+    .bind(template_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load template steps: {}", e))
+}
+
+pub async fn load_template(pool: &Pool<Sqlite>, id: &str) -> Result<Template, String> {
+    let mut template = sqlx::query_as::<_, Template>(&format!(
+        "SELECT {} FROM templates WHERE id = ?",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load template: {}", e))?
+    .ok_or_else(|| format!("Template {} not found", id))?;
+
+    template.steps = load_template_steps(pool, id).await?;
+    Ok(template)
+}
+
+/// Built-in templates are read-only; they are changed by duplicating them.
+pub async fn ensure_editable(pool: &Pool<Sqlite>, id: &str) -> Result<(), String> {
+    let builtin: bool = sqlx::query_scalar("SELECT builtin FROM templates WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load template: {}", e))?
+        .ok_or_else(|| format!("Template {} not found", id))?;
+    if builtin {
+        return Err("Built-in templates can't be changed; duplicate it to edit".to_string());
+    }
+    Ok(())
+}
+
+pub async fn load_all_templates(pool: &Pool<Sqlite>) -> Result<Vec<Template>, String> {
+    let mut templates = sqlx::query_as::<_, Template>(&format!(
+        "SELECT {} FROM templates ORDER BY builtin DESC, name",
+        TEMPLATE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load templates: {}", e))?;
+
+    for template in &mut templates {
+        template.steps = load_template_steps(pool, &template.id).await?;
+    }
+    Ok(templates)
+}
+
+pub async fn insert_template(conn: &mut SqliteConnection, template: &Template) -> Result<(), String> {
+    sqlx::query(&format!(
+        "INSERT INTO templates ({}) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(&template.id)
+    .bind(&template.name)
+    .bind(&template.category)
+    .bind(&template.description)
+    .bind(template.builtin)
+    .bind(&template.created_at)
+    .bind(&template.updated_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to insert template: {}", e))?;
+
+    replace_template_steps(conn, &template.id, &template.steps).await
+}
+
+/// Swap a template's steps for `steps`, assigning fresh ids and sort order.
+pub async fn replace_template_steps(
+    conn: &mut SqliteConnection,
+    template_id: &str,
+    steps: &[TemplateStep],
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM template_steps WHERE template_id = ?")
+        .bind(template_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to clear template steps: {}", e))?;
+
+    for (index, step) in steps.iter().enumerate() {
+        sqlx::query(&format!(
+            "INSERT INTO template_steps ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            TEMPLATE_STEP_COLUMNS
+        ))
+        .bind(db::new_id())
+        .bind(template_id)
+        .bind(index as i64)
+        .bind(step.name.trim())
+        .bind(step.time_min)
+        .bind(step.time_sec)
+        .bind(&step.agitation)
+        .bind(&step.compensation)
+        .bind(step.min_temperature)
+        .bind(step.rated_temperature)
+        .bind(step.max_temperature)
+        .bind(&step.formula_designator)
+        .bind(&step.logo_text)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to insert template step: {}", e))?;
+    }
+    Ok(())
+}
+
+/// `name` trimmed, or `None` when it is missing or blank.
+fn given_name(name: Option<String>) -> Option<String> {
+    name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+/// An unsaved user copy of `source`, built-in or not, named `name` or
+/// "<source> (copy)".
+pub fn duplicate(source: Template, name: Option<String>) -> Template {
+    let now = db::now_iso();
+    Template {
+        id: db::new_id(),
+        name: given_name(name).unwrap_or_else(|| format!("{} (copy)", source.name)),
+        builtin: false,
+        created_at: now.clone(),
+        updated_at: now,
+        ..source
+    }
+}
+
+/// A fresh, unsaved recipe with copies of the template's steps, named `name`
+/// or "New <template> recipe".
+pub fn recipe_from_template(template: &Template, name: Option<String>) -> Recipe {
+    let name = given_name(name).unwrap_or_else(|| format!("New {} recipe", template.name));
+    let mut recipe = recipe::new_recipe(&name);
+    recipe.category = template.category.clone();
+    recipe.steps = template.steps.iter().map(TemplateStep::to_step).collect();
+    recipe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// An in-memory database with the built-in templates from the migrations.
+    async fn template_pool() -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for migration in crate::migrations::get_migrations() {
+            sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn step_names(template: &Template) -> Vec<&str> {
+        template.steps.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn built_in_templates_are_locked() {
+        block_on(async {
+            let pool = template_pool().await;
+            let error = ensure_editable(&pool, "builtin-c41").await.unwrap_err();
+            assert_eq!(
+                error,
+                "Built-in templates can't be changed; duplicate it to edit"
+            );
+            assert_eq!(
+                ensure_editable(&pool, "missing").await.unwrap_err(),
+                "Template missing not found"
+            );
+
+            // A copy of a built-in belongs to the user.
+            let copy = duplicate(load_template(&pool, "builtin-c41").await.unwrap(), None);
+            let mut conn = pool.acquire().await.unwrap();
+            insert_template(&mut conn, &copy).await.unwrap();
+            drop(conn);
+            assert!(ensure_editable(&pool, &copy.id).await.is_ok());
+        });
+    }
+
+    #[test]
+    fn duplicate_copies_steps_into_a_user_template() {
+        block_on(async {
+            let pool = template_pool().await;
+            let source = load_template(&pool, "builtin-e6").await.unwrap();
+
+            let copy = duplicate(source.clone(), None);
+            assert_ne!(copy.id, source.id);
+            assert_eq!(copy.name, "E-6 (copy)");
+            assert!(!copy.builtin);
+            assert_eq!(copy.category, "E-6");
+            assert_eq!(copy.description, source.description);
+            assert_eq!(
+                step_names(&copy),
+                ["PRE", "DEV", "WASH", "DEV", "BLIX", "WASH", "STAB"]
+            );
+
+            let mut conn = pool.acquire().await.unwrap();
+            insert_template(&mut conn, &copy).await.unwrap();
+            drop(conn);
+            let stored = load_template(&pool, &copy.id).await.unwrap();
+            assert_eq!(step_names(&stored), step_names(&source));
+            assert_eq!(stored.steps[1].logo_text, "FIRST DEV");
+            assert_eq!(stored.steps[1].min_temperature, 37.7);
+            assert!(stored.steps.iter().all(|s| s.template_id == copy.id));
+
+            let named = duplicate(source.clone(), Some("  E-6 warm  ".to_string()));
+            assert_eq!(named.name, "E-6 warm");
+            let blank = duplicate(source, Some("   ".to_string()));
+            assert_eq!(blank.name, "E-6 (copy)");
+        });
+    }
+
+    #[test]
+    fn recipe_from_template_copies_the_steps() {
+        block_on(async {
+            let pool = template_pool().await;
+            let template = load_template(&pool, "builtin-two-bath").await.unwrap();
+
+            let recipe = recipe_from_template(&template, None);
+            assert_eq!(recipe.name, "New Two-bath B&W recipe");
+            assert_eq!(recipe.category, "BW");
+            assert!(!recipe.id.is_empty());
+            let steps = recipe
+                .steps
+                .iter()
+                .map(|s| {
+                    (
+                        s.name.as_str(),
+                        s.total_seconds(),
+                        s.agitation.as_str(),
+                        s.logo_text.as_str(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                steps,
+                [
+                    ("DEV", 180, "Roll", "BATH A"),
+                    ("DEV", 180, "Stand", "BATH B"),
+                    ("FIX", 300, "Roll", ""),
+                    ("RINSE", 600, "Roll", ""),
+                ]
+            );
+            // Step ids and the recipe link are assigned when the recipe is saved.
+            assert!(recipe
+                .steps
+                .iter()
+                .all(|s| s.id.is_empty() && s.recipe_id.is_empty()));
+
+            let named = recipe_from_template(&template, Some(" HP5+ two-bath ".to_string()));
+            assert_eq!(named.name, "HP5+ two-bath");
+        });
+    }
+}
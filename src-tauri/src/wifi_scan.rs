@@ -0,0 +1,296 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A Wi-Fi network seen in a scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WifiNetwork {
+    pub ssid: String,
+    /// Signal quality from 0 to 100.
+    pub signal: u8,
+    /// Signal in dBm, when the system reports it.
+    pub rssi_dbm: Option<i32>,
+    /// "Open", "WPA2 Personal", ...
+    pub security: String,
+    /// The interface is joined to this network.
+    pub in_use: bool,
+    /// The SSID is named like an AGO access point or used by a saved device.
+    pub ago_like: bool,
+    /// Saved device profile using this SSID.
+    pub device_id: Option<String>,
+}
+
+fn ago_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    // "AGO", "AGO-3F2A", "AGO_Lab", "AGO2"; not "Agora".
+    PATTERN.get_or_init(|| Regex::new(r"(?i)^\s*ago(?:$|[^a-z])").expect("valid AGO pattern"))
+}
+
+/// Whether an SSID follows the AGO access point naming.
+pub fn is_ago_ssid(ssid: &str) -> bool {
+    ago_pattern().is_match(ssid)
+}
+
+/// Approximate quality for a dBm reading: -100 dBm and below is 0, -50 and above is 100.
+pub fn signal_from_dbm(dbm: i32) -> u8 {
+    (2 * (dbm + 100)).clamp(0, 100) as u8
+}
+
+fn network(ssid: String, signal: u8, rssi_dbm: Option<i32>, security: String) -> WifiNetwork {
+    WifiNetwork {
+        ago_like: is_ago_ssid(&ssid),
+        ssid,
+        signal,
+        rssi_dbm,
+        security,
+        in_use: false,
+        device_id: None,
+    }
+}
+
+/// Split one line of `nmcli -t` output, where `:` inside a field is escaped as `\:`.
+fn nmcli_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    fields.last_mut().unwrap().push(next);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Parse `nmcli -t -f IN-USE,SSID,SIGNAL,SECURITY device wifi list`.
+pub fn parse_nmcli(output: &str) -> Vec<WifiNetwork> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = nmcli_fields(line);
+            let [in_use, ssid, signal, security] = fields.as_slice() else {
+                return None;
+            };
+            let security = security.trim();
+            let mut found = network(
+                ssid.clone(),
+                signal.trim().parse::<u8>().unwrap_or(0).min(100),
+                None,
+                if security.is_empty() || security == "--" {
+                    "Open".to_string()
+                } else {
+                    security.replace(' ', " / ")
+                },
+            );
+            found.in_use = in_use.trim() == "*";
+            Some(found)
+        })
+        .collect()
+}
+
+/// "spairport_security_mode_wpa2_personal" -> "WPA2 Personal".
+fn macos_security(raw: &str) -> String {
+    let mode = raw.trim_start_matches("spairport_security_mode_");
+    if mode.is_empty() || mode == "none" {
+        return "Open".to_string();
+    }
+    mode.split('_')
+        .map(|word| match word {
+            "wep" | "wpa" | "wpa2" | "wpa3" | "owe" => word.to_uppercase(),
+            _ => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn macos_network(entry: &Value) -> Option<WifiNetwork> {
+    let ssid = entry.get("_name")?.as_str()?.to_string();
+    // "-55 dBm / -90 dBm"
+    let rssi_dbm = entry
+        .get("spairport_signal_noise")
+        .and_then(Value::as_str)
+        .and_then(|s| s.split_whitespace().next())
+        .and_then(|s| s.parse::<i32>().ok());
+    let security = entry
+        .get("spairport_security_mode")
+        .and_then(Value::as_str)
+        .map(macos_security)
+        .unwrap_or_else(|| "Unknown".to_string());
+    Some(network(
+        ssid,
+        rssi_dbm.map(signal_from_dbm).unwrap_or(0),
+        rssi_dbm,
+        security,
+    ))
+}
+
+/// Parse `system_profiler SPAirPortDataType -json`, keeping the networks seen
+/// by `interface` (every interface when empty).
+pub fn parse_system_profiler(json: &str, interface: &str) -> Result<Vec<WifiNetwork>, String> {
+    let root: Value = serde_json::from_str(json)
+        .map_err(|e| format!("Failed to read system_profiler output: {}", e))?;
+    let mut found = Vec::new();
+    let sections = root
+        .get("SPAirPortDataType")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for section in sections {
+        let interfaces = section
+            .get("spairport_airport_interfaces")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|i| {
+                interface.is_empty() || i.get("_name").and_then(Value::as_str) == Some(interface)
+            });
+        for entry in interfaces {
+            if let Some(mut current) = entry
+                .get("spairport_current_network_information")
+                .and_then(macos_network)
+            {
+                current.in_use = true;
+                found.push(current);
+            }
+            let others = entry
+                .get("spairport_airport_other_local_wireless_networks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten();
+            found.extend(others.filter_map(macos_network));
+        }
+    }
+    Ok(found)
+}
+
+/// One entry per SSID, keeping the strongest signal, with hidden networks
+/// dropped. AGO-like networks come first, then by signal.
+pub fn merge(networks: Vec<WifiNetwork>) -> Vec<WifiNetwork> {
+    let mut by_ssid: HashMap<String, WifiNetwork> = HashMap::new();
+    for found in networks {
+        if found.ssid.trim().is_empty() {
+            continue;
+        }
+        match by_ssid.get_mut(&found.ssid) {
+            Some(seen) => {
+                let in_use = seen.in_use || found.in_use;
+                if found.signal > seen.signal {
+                    *seen = found;
+                }
+                seen.in_use = in_use;
+            }
+            None => {
+                by_ssid.insert(found.ssid.clone(), found);
+            }
+        }
+    }
+    let mut merged = by_ssid.into_values().collect::<Vec<_>>();
+    merged.sort_by(|a, b| {
+        b.ago_like
+            .cmp(&a.ago_like)
+            .then(b.signal.cmp(&a.signal))
+            .then_with(|| a.ssid.to_lowercase().cmp(&b.ssid.to_lowercase()))
+    });
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nmcli_terse_output() {
+        let found = parse_nmcli(include_str!("../testdata/wifi/nmcli.txt"));
+        let summary = found
+            .iter()
+            .map(|n| (n.ssid.as_str(), n.signal, n.security.as_str(), n.in_use))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("Studio:5G", 74, "WPA2", true),
+                ("AGO-3F2A", 62, "Open", false),
+                ("Neighbour WiFi", 40, "WPA1 / WPA2", false),
+                ("", 35, "WPA2", false),
+                ("AGO-3F2A", 48, "Open", false),
+                ("Agora Cafe", 30, "WPA2 / 802.1X", false),
+                ("Guest", 22, "Open", false),
+            ]
+        );
+        assert!(found.iter().all(|n| n.rssi_dbm.is_none()));
+        assert!(found[1].ago_like);
+        assert!(!found[5].ago_like);
+    }
+
+    #[test]
+    fn parses_system_profiler_json() {
+        let json = include_str!("../testdata/wifi/system_profiler.json");
+        let found = parse_system_profiler(json, "en0").unwrap();
+        let summary = found
+            .iter()
+            .map(|n| {
+                (
+                    n.ssid.as_str(),
+                    n.rssi_dbm,
+                    n.signal,
+                    n.security.as_str(),
+                    n.in_use,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("Studio", Some(-52), 96, "WPA2 Personal", true),
+                ("AGO-3F2A", Some(-61), 78, "Open", false),
+                ("Studio", Some(-67), 66, "WPA2 Personal", false),
+                ("Neighbour WiFi", Some(-80), 40, "WPA3 Transition", false),
+                ("Printer", None, 0, "Unknown", false),
+            ]
+        );
+        assert_eq!(parse_system_profiler(json, "").unwrap().len(), 5);
+        assert!(parse_system_profiler(json, "awdl0").unwrap().is_empty());
+        assert!(parse_system_profiler("not json", "en0").is_err());
+    }
+
+    #[test]
+    fn merge_keeps_strongest_and_puts_ago_first() {
+        let merged = merge(parse_nmcli(include_str!("../testdata/wifi/nmcli.txt")));
+        let summary = merged
+            .iter()
+            .map(|n| (n.ssid.as_str(), n.signal))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("AGO-3F2A", 62),
+                ("Studio:5G", 74),
+                ("Neighbour WiFi", 40),
+                ("Agora Cafe", 30),
+                ("Guest", 22),
+            ]
+        );
+
+        let mut found =
+            parse_system_profiler(include_str!("../testdata/wifi/system_profiler.json"), "en0")
+                .unwrap();
+        // Joined on the weaker band: the stronger entry wins but stays in use.
+        found[0].signal = 10;
+        let merged = merge(found);
+        let studio = merged.iter().find(|n| n.ssid == "Studio").unwrap();
+        assert_eq!((studio.signal, studio.in_use), (66, true));
+        assert_eq!(merged[0].ssid, "AGO-3F2A");
+    }
+}
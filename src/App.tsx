@@ -1,9 +1,13 @@
 import { useEffect, useState, useCallback, Component, type ReactNode } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "./lib/store";
+import type { ConnectionState } from "./lib/types";
 import { Sidebar } from "./components/Sidebar";
 import { RecipeEditor } from "./components/RecipeEditor";
 import { AgoConnection } from "./components/AgoConnection";
 import { AgoPrograms } from "./components/AgoPrograms";
+import { FleetDeploy } from "./components/FleetDeploy";
 import { Settings } from "./components/Settings";
 import { MdcLookup } from "./components/MdcLookup";
 import { EmptyState } from "./components/EmptyState";
@@ -39,11 +43,40 @@ function AppInner() {
   const recipes = useAppStore((s) => s.recipes);
   const loadRecipes = useAppStore((s) => s.loadRecipes);
   const loadSettings = useAppStore((s) => s.loadSettings);
+  const loadDevices = useAppStore((s) => s.loadDevices);
+  const applyConnectionState = useAppStore((s) => s.applyConnectionState);
+  const showToast = useAppStore((s) => s.showToast);
+  const activeDeviceId = useAppStore((s) => s.settings.active_device_id);
 
   useEffect(() => {
     loadRecipes().catch((e) => console.error("loadRecipes failed:", e));
     loadSettings().catch((e) => console.error("loadSettings failed:", e));
-  }, [loadRecipes, loadSettings]);
+    loadDevices().catch((e) => console.error("loadDevices failed:", e));
+  }, [loadRecipes, loadSettings, loadDevices]);
+
+  // The backend supervisor watches the AGO connection and reports changes.
+  useEffect(() => {
+    const unlisteners = Promise.all([
+      listen<ConnectionState>("ago://connected", (e) => applyConnectionState(e.payload)),
+      listen<ConnectionState>("ago://lost", (e) => {
+        applyConnectionState(e.payload);
+        showToast("Lost connection to AGO", "error");
+      }),
+      listen<ConnectionState>("ago://restored", (e) => {
+        applyConnectionState(e.payload);
+        showToast(`Reconnected to ${e.payload.current_network}`);
+      }),
+    ]);
+    return () => {
+      unlisteners.then((fns) => fns.forEach((unlisten) => unlisten()));
+    };
+  }, [applyConnectionState, showToast]);
+
+  useEffect(() => {
+    invoke<ConnectionState>("get_connection_state")
+      .then(applyConnectionState)
+      .catch((e) => console.error("get_connection_state failed:", e));
+  }, [activeDeviceId, applyConnectionState]);
 
   const selectedRecipe = recipes.find((r) => r.id === selectedRecipeId) ?? null;
 
@@ -66,6 +99,7 @@ function AppInner() {
             <AgoPrograms />
           </div>
         )}
+        {activeView === "deploy" && <FleetDeploy />}
         {activeView === "settings" && <Settings />}
       </main>
       <Toast />
@@ -1,7 +1,8 @@
-import { useEffect, useRef } from "react";
+import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
-import { useAppStore } from "../lib/store";
+import { selectActiveDevice, useAppStore } from "../lib/store";
+import type { ConnectionState, Device, DeviceProbe, NewDevice, WifiNetwork } from "../lib/types";
 
 function normalizeSsid(value: string): string {
   return value.replace(/^"+|"+$/g, "").trim().toLowerCase();
@@ -14,10 +15,10 @@ function isAgoSsidMatch(currentSsid: string, targetSsid: string): boolean {
   return current === target || current.includes(target) || target.includes(current);
 }
 
-async function probeAgoIp(ip: string): Promise<boolean> {
+async function probeAgo(deviceId: string): Promise<boolean> {
   try {
     return await invoke<boolean>("wifi_probe_ago", {
-      ip,
+      deviceId,
     });
   } catch {
     return false;
@@ -33,12 +34,49 @@ export function AgoConnection() {
     settings,
     setWifiStatus,
     setCurrentSsid,
-    setPreviousSsid,
     setWifiInterface,
+    applyConnectionState,
     showToast,
+    devices,
+    loadDevices,
+    setActiveDevice,
   } = useAppStore();
+  const device = useAppStore(selectActiveDevice);
+  const deviceId = device?.id ?? "";
+  const agoSsid = device?.ssid || "AGO";
+  const agoIp = device?.ip || "10.10.10.1";
+  const [hasPassword, setHasPassword] = useState<boolean | null>(null);
+  const [deviceProbe, setDeviceProbe] = useState<DeviceProbe | null>(null);
+  const [probing, setProbing] = useState(false);
+  const [networks, setNetworks] = useState<WifiNetwork[] | null>(null);
+  const [scanning, setScanning] = useState(false);
 
-  const pollRef = useRef<ReturnType<typeof setInterval>>(undefined);
+  useEffect(() => {
+    setHasPassword(null);
+    if (!deviceId) return;
+    invoke<boolean>("wifi_has_password", { deviceId })
+      .then(setHasPassword)
+      .catch(() => setHasPassword(null));
+  }, [deviceId]);
+
+  useEffect(() => {
+    setDeviceProbe(null);
+    if (!deviceId) return;
+    invoke<DeviceProbe | null>("get_device_probe", { deviceId })
+      .then(setDeviceProbe)
+      .catch(() => setDeviceProbe(null));
+  }, [deviceId]);
+
+  const handleProbe = async () => {
+    setProbing(true);
+    try {
+      setDeviceProbe(await invoke<DeviceProbe>("probe_device", { deviceId }));
+    } catch (e) {
+      showToast(`Device check failed: ${e}`, "error");
+    } finally {
+      setProbing(false);
+    }
+  };
 
   // Detect WiFi interface on mount
   useEffect(() => {
@@ -47,52 +85,62 @@ export function AgoConnection() {
       .catch(() => setWifiInterface(""));
   }, [setWifiInterface]);
 
-  // Poll connection status. Prefer SSID check when interface is available,
-  // otherwise fall back to AGO IP probe so manual joins are still recognized.
-  useEffect(() => {
-    const check = async () => {
-      const agoIp = settings.ago_ip || "10.10.10.1";
-      const agoSsid = settings.ago_ssid || "AGO";
-
-      if (!wifiInterface) {
-        const reachable = await probeAgoIp(agoIp);
-        setWifiStatus(reachable ? "connected" : "disconnected");
-        if (!reachable) {
-          setCurrentSsid("");
-        }
-        return;
-      }
-
-      try {
-        const ssid = await invoke<string>("wifi_get_current_network", {
-          interface: wifiInterface,
-        });
-        setCurrentSsid(ssid);
-        if (isAgoSsidMatch(ssid, agoSsid)) {
-          setWifiStatus("connected");
-          return;
-        }
+  const handleScan = async () => {
+    setScanning(true);
+    try {
+      setNetworks(
+        await invoke<WifiNetwork[]>("wifi_scan_networks", { interface: wifiInterface || null })
+      );
+    } catch (e) {
+      showToast(`WiFi scan failed: ${e}`, "error");
+    } finally {
+      setScanning(false);
+    }
+  };
 
-        const reachable = await probeAgoIp(agoIp);
-        setWifiStatus(reachable ? "connected" : "disconnected");
-      } catch {
-        setCurrentSsid("");
-        const reachable = await probeAgoIp(agoIp);
-        setWifiStatus(reachable ? "connected" : "disconnected");
-      }
+  const handleUseNetwork = async (network: WifiNetwork) => {
+    setStoredPassword(null);
+    if (network.device_id) {
+      await setActiveDevice(network.device_id);
+      return;
+    }
+    const device: NewDevice = {
+      name: network.ssid,
+      ip: "10.10.10.1",
+      ssid: network.ssid,
     };
+    try {
+      const created = await invoke<Device>("create_device", { device });
+      await loadDevices();
+      await setActiveDevice(created.id);
+      setNetworks((list) =>
+        list?.map((n) => (n.ssid === network.ssid ? { ...n, device_id: created.id } : n)) ?? null
+      );
+      showToast(`Added ${network.ssid}; set its WiFi password in Settings`);
+    } catch (e) {
+      showToast(`Failed to add device: ${e}`, "error");
+    }
+  };
 
-    check();
-    pollRef.current = setInterval(check, 3000);
-    return () => clearInterval(pollRef.current);
-  }, [wifiInterface, settings.ago_ip, settings.ago_ssid, setCurrentSsid, setWifiStatus]);
+  // The backend supervisor polls the connection; ask it directly after a
+  // change so the status does not wait for its next check.
+  const refreshConnection = async () => {
+    try {
+      applyConnectionState(await invoke<ConnectionState>("get_connection_state"));
+    } catch {
+      // The supervisor reports the change on its next check.
+    }
+  };
 
   const handleConnect = async () => {
-    const targetSsid = settings.ago_ssid || "AGO";
-    const targetIp = settings.ago_ip || "10.10.10.1";
+    const targetSsid = agoSsid;
+    if (!deviceId) {
+      showToast("Add an AGO device in Settings first", "error");
+      return;
+    }
 
     if (!wifiInterface) {
-      if (await probeAgoIp(targetIp)) {
+      if (await probeAgo(deviceId)) {
         setWifiStatus("connected");
         showToast("AGO is reachable");
         return;
@@ -107,25 +155,20 @@ export function AgoConnection() {
       showToast("Already connected to AGO");
       return;
     }
-    if (await probeAgoIp(targetIp)) {
+    if (await probeAgo(deviceId)) {
       setWifiStatus("connected");
       showToast("AGO is reachable");
       return;
     }
 
-    // Save current SSID before switching
-    if (currentSsid && !isAgoSsidMatch(currentSsid, targetSsid)) {
-      setPreviousSsid(currentSsid);
-    }
-
+    // The backend remembers the current network before switching.
     setWifiStatus("connecting");
     try {
       await invoke("wifi_connect", {
         interface: wifiInterface,
-        ssid: targetSsid,
-        password: settings.ago_password || "12345678",
+        deviceId,
       });
-      // Polling will update status
+      await refreshConnection();
       showToast("Connected to AGO");
     } catch (e) {
       // One more read in case connection happened but command returned a benign error.
@@ -142,7 +185,7 @@ export function AgoConnection() {
       } catch {
         // Ignore secondary read errors and report original failure.
       }
-      if (await probeAgoIp(targetIp)) {
+      if (await probeAgo(deviceId)) {
         setWifiStatus("connected");
         showToast("Connected to AGO");
         return;
@@ -159,12 +202,8 @@ export function AgoConnection() {
     try {
       const shouldAutoReconnect = settings.auto_reconnect === "true";
       if (shouldAutoReconnect && previousSsid) {
-        await invoke("wifi_reconnect", {
-          interface: wifiInterface,
-          ssid: previousSsid,
-          password: "",
-        });
-        showToast(`Reconnected to ${previousSsid}`);
+        // Reports ago://restored, which shows the toast.
+        await invoke<string | null>("restore_previous_network");
       } else {
         showToast("Disconnected from AGO. Reconnect to your WiFi manually.", "info");
       }
@@ -175,7 +214,7 @@ export function AgoConnection() {
   };
 
   const openAgoInterface = async () => {
-    const ip = settings.ago_ip || "10.10.10.1";
+    const ip = agoIp;
     try {
       const existing = await WebviewWindow.getByLabel("ago-web");
       if (existing) {
@@ -198,6 +237,27 @@ export function AgoConnection() {
     <div className="max-w-xl mx-auto p-6">
       <h2 className="text-2xl font-semibold mb-6">AGO Connection</h2>
 
+      {devices.length > 1 && (
+        <div className="mb-4">
+          <label className="block text-sm font-medium mb-1">Device</label>
+          <select
+            value={deviceId}
+            onChange={(e) => {
+              setStoredPassword(null);
+              setActiveDevice(e.target.value);
+            }}
+            disabled={wifiStatus === "connecting"}
+            className="w-full px-3 py-2 rounded-lg border border-(--color-border) bg-(--color-surface) text-sm"
+          >
+            {devices.map((d) => (
+              <option key={d.id} value={d.id}>
+                {d.name}
+              </option>
+            ))}
+          </select>
+        </div>
+      )}
+
       {/* Status card */}
       <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 mb-6">
         <div className="flex items-center gap-3 mb-4">
@@ -228,15 +288,11 @@ export function AgoConnection() {
         <div className="grid grid-cols-2 gap-2 mb-4">
           <div className="rounded-md border border-(--color-border) bg-(--color-surface) px-2.5 py-2">
             <p className="text-[11px] uppercase tracking-wide text-(--color-text-tertiary)">AGO SSID</p>
-            <p className="text-sm font-mono text-(--color-text-primary)">
-              {settings.ago_ssid || "AGO"}
-            </p>
+            <p className="text-sm font-mono text-(--color-text-primary)">{agoSsid}</p>
           </div>
           <div className="rounded-md border border-(--color-border) bg-(--color-surface) px-2.5 py-2">
             <p className="text-[11px] uppercase tracking-wide text-(--color-text-tertiary)">AGO IP</p>
-            <p className="text-sm font-mono text-(--color-text-primary)">
-              {settings.ago_ip || "10.10.10.1"}
-            </p>
+            <p className="text-sm font-mono text-(--color-text-primary)">{agoIp}</p>
           </div>
         </div>
 
@@ -266,6 +322,98 @@ export function AgoConnection() {
         </div>
       </div>
 
+      {/* Nearby networks */}
+      <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 mb-6">
+        <div className="flex items-center justify-between mb-3">
+          <h3 className="text-lg font-medium">Nearby Networks</h3>
+          <button
+            onClick={handleScan}
+            disabled={scanning}
+            className="px-3 py-1.5 bg-(--color-surface-hover) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-border) transition-colors disabled:opacity-50"
+          >
+            {scanning ? "Scanning..." : "Scan"}
+          </button>
+        </div>
+        {networks === null ? (
+          <p className="text-sm text-(--color-text-tertiary)">
+            Scan to find AGO access points, for example after a unit's SSID was renamed. Networks
+            named like an AGO are listed first.
+          </p>
+        ) : networks.length === 0 ? (
+          <p className="text-sm text-(--color-text-tertiary)">No networks found.</p>
+        ) : (
+          <div className="max-h-64 overflow-y-auto rounded-md border border-(--color-border) bg-(--color-surface) divide-y divide-(--color-border)">
+            {networks.map((n) => (
+              <div key={n.ssid} className="flex items-center gap-2 px-3 py-1.5 text-sm">
+                <span
+                  className={`flex-1 truncate font-mono ${
+                    n.ago_like ? "text-(--color-accent) font-medium" : "text-(--color-text-primary)"
+                  }`}
+                >
+                  {n.ssid}
+                  {n.in_use && <span className="ml-1 text-xs text-(--color-text-tertiary)">(current)</span>}
+                </span>
+                <span className="text-xs text-(--color-text-tertiary)">{n.security}</span>
+                <span
+                  className="w-12 text-right text-xs text-(--color-text-secondary)"
+                  title={n.rssi_dbm !== null ? `${n.rssi_dbm} dBm` : undefined}
+                >
+                  {n.signal}%
+                </span>
+                <button
+                  onClick={() => handleUseNetwork(n)}
+                  disabled={n.device_id !== null && n.device_id === deviceId}
+                  className="px-2 py-1 text-xs rounded-md border border-(--color-border) hover:bg-(--color-surface-hover) disabled:opacity-50"
+                >
+                  {n.device_id ? (n.device_id === deviceId ? "Selected" : "Select") : "Add device"}
+                </button>
+              </div>
+            ))}
+          </div>
+        )}
+      </div>
+
+      {/* Device check */}
+      <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 mb-6">
+        <div className="flex items-center justify-between mb-3">
+          <h3 className="text-lg font-medium">Device Check</h3>
+          <button
+            onClick={handleProbe}
+            disabled={probing || !deviceId}
+            className="px-3 py-1.5 bg-(--color-surface-hover) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-border) transition-colors disabled:opacity-50"
+          >
+            {probing ? "Checking..." : "Check"}
+          </button>
+        </div>
+        {deviceProbe ? (
+          <dl className="grid grid-cols-2 gap-x-4 gap-y-1 text-sm">
+            <dt className="text-(--color-text-tertiary)">Reachable</dt>
+            <dd>
+              {deviceProbe.reachable ? "Yes" : "No"}
+              {deviceProbe.latency_ms !== null && ` (${deviceProbe.latency_ms} ms)`}
+            </dd>
+            <dt className="text-(--color-text-tertiary)">Programs API</dt>
+            <dd>
+              {deviceProbe.programs_api
+                ? `Yes, ${deviceProbe.program_count ?? 0} custom programs`
+                : "Not found - this may not be an AGO"}
+            </dd>
+            <dt className="text-(--color-text-tertiary)">Advertised methods</dt>
+            <dd className="font-mono">
+              {deviceProbe.advertised_methods.length > 0 ? deviceProbe.advertised_methods.join(", ") : "Unknown"}
+            </dd>
+            <dt className="text-(--color-text-tertiary)">Last upload used</dt>
+            <dd className="font-mono">{deviceProbe.last_write_method ?? "-"}</dd>
+            <dt className="text-(--color-text-tertiary)">Firmware</dt>
+            <dd>{deviceProbe.firmware_hints.join(", ") || "Unknown"}</dd>
+            <dt className="text-(--color-text-tertiary)">Checked</dt>
+            <dd>{deviceProbe.probed_at ? new Date(deviceProbe.probed_at).toLocaleString() : "-"}</dd>
+          </dl>
+        ) : (
+          <p className="text-sm text-(--color-text-tertiary)">Not checked yet.</p>
+        )}
+      </div>
+
       {/* Warning about internet */}
       <div className="bg-(--color-warning)/10 border border-(--color-warning)/30 rounded-lg p-4 mb-6">
         <p className="text-sm text-(--color-text-primary)">
@@ -314,19 +462,19 @@ export function AgoConnection() {
           <p>1. Enable WiFi on your AGO device</p>
           <p>
             2. On your Mac, join WiFi network:{" "}
-            <span className="font-mono">{settings.ago_ssid || "AGO"}</span>
+            <span className="font-mono">{agoSsid}</span>
           </p>
           <p>
             3. Password:{" "}
-            <span className="font-mono">
-              {settings.ago_password || "12345678"}
-            </span>
+            {hasPassword
+              ? "saved in the system keychain"
+              : hasPassword === false
+                ? "not saved - set it in Settings"
+                : "unknown"}
           </p>
           <p>
             4. Open browser to:{" "}
-            <span className="font-mono">
-              http://{settings.ago_ip || "10.10.10.1"}
-            </span>
+            <span className="font-mono">http://{agoIp}</span>
           </p>
         </div>
       </details>
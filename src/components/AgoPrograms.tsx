@@ -1,12 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { useAppStore } from "../lib/store";
-import {
-  fetchAgoUploads,
-  deleteAgoUpload,
-  deleteAgoUploadsByFilename,
-  type AgoUpload,
-} from "../lib/db";
+import { selectActiveDevice, useAppStore } from "../lib/store";
+import { fetchAgoUploads, deleteAgoUpload, type AgoUpload } from "../lib/db";
 
 interface AgoDeviceProgram {
   filename: string;
@@ -34,7 +29,7 @@ function formatProgramDisplayName(program: AgoDeviceProgram, fallback: string):
 }
 
 export function AgoPrograms() {
-  const settings = useAppStore((s) => s.settings);
+  const device = useAppStore(selectActiveDevice);
   const wifiStatus = useAppStore((s) => s.wifiStatus);
   const showToast = useAppStore((s) => s.showToast);
   const [uploads, setUploads] = useState<AgoProgramRow[]>([]);
@@ -43,7 +38,7 @@ export function AgoPrograms() {
   const [loading, setLoading] = useState(false);
   const [deviceListError, setDeviceListError] = useState<string | null>(null);
 
-  const ip = settings.ago_ip || "10.10.10.1";
+  const deviceId = device?.id;
 
   useEffect(() => {
     let cancelled = false;
@@ -53,11 +48,13 @@ export function AgoPrograms() {
       setDeviceListError(null);
 
       try {
-        const localUploads = await fetchAgoUploads();
+        const localUploads = await fetchAgoUploads(deviceId);
 
-        if (wifiStatus === "connected") {
+        if (wifiStatus === "connected" && deviceId) {
           try {
-            const devicePrograms = await invoke<AgoDeviceProgram[]>("list_ago_programs", { ip });
+            const devicePrograms = await invoke<AgoDeviceProgram[]>("list_ago_programs", {
+              deviceId,
+            });
             if (cancelled) return;
 
             const localByFilename = new Map(localUploads.map((u) => [u.filename, u]));
@@ -66,6 +63,7 @@ export function AgoPrograms() {
               return {
                 id: program.filename,
                 recipe_id: local?.recipe_id ?? null,
+                device_id: deviceId,
                 filename: program.filename,
                 display_name: formatProgramDisplayName(
                   program,
@@ -103,17 +101,16 @@ export function AgoPrograms() {
     return () => {
       cancelled = true;
     };
-  }, [ip, wifiStatus]);
+  }, [deviceId, wifiStatus]);
 
   const handleDelete = async (upload: AgoProgramRow) => {
     setDeleting(upload.id);
     try {
-      if (wifiStatus === "connected") {
+      if (wifiStatus === "connected" && deviceId) {
         await invoke<string>("delete_ago_program", {
-          ip,
+          deviceId,
           filename: upload.filename,
         });
-        await deleteAgoUploadsByFilename(upload.filename);
         setUploads((prev) => prev.filter((u) => u.id !== upload.id));
         showToast("Program deleted from AGO");
       } else {
@@ -1,26 +1,91 @@
-import { useMemo, useState } from "react";
-import { TANK_VOLUMES, REEL_LABELS, type ReelType } from "../lib/constants";
-import { parseDilution, calculateChemistry } from "../lib/chemistry";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useAppStore } from "../lib/store";
+import { REEL_LABELS, type ReelType } from "../lib/constants";
+import type { ChemistryResult, Dilution, TankVolume } from "../lib/types";
 
-export function ChemistryCalculator({ dilution }: { dilution: string }) {
+export function ChemistryCalculator({
+  developer,
+  dilution,
+  recipeId,
+}: {
+  developer: string;
+  dilution: string;
+  recipeId?: string;
+}) {
+  const showToast = useAppStore((s) => s.showToast);
+  const [tanks, setTanks] = useState<TankVolume[]>([]);
   const [tankIndex, setTankIndex] = useState(1); // Default: Universal tank
-  const [reelType, setReelType] = useState<ReelType>("35mm");
+  const [reelType, setReelType] = useState("35mm");
+  const [parsed, setParsed] = useState<Dilution | null>(null);
+  const [result, setResult] = useState<ChemistryResult | null>(null);
 
-  const tank = TANK_VOLUMES[tankIndex];
-  const availableReels = useMemo(
-    () => Object.keys(tank.volumes) as ReelType[],
-    [tank]
-  );
+  useEffect(() => {
+    invoke<TankVolume[]>("list_tank_volumes")
+      .then(setTanks)
+      .catch((e) => showToast(`Failed to load tank volumes: ${e}`, "error"));
+  }, [showToast]);
+
+  useEffect(() => {
+    if (!dilution.trim()) {
+      setParsed(null);
+      return;
+    }
+    let cancelled = false;
+    invoke<Dilution>("parse_dilution", { dilution, developer })
+      .then((d) => {
+        if (!cancelled) setParsed(d);
+      })
+      .catch(() => {
+        if (!cancelled) setParsed(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [dilution, developer]);
+
+  const tank = tanks[tankIndex] ?? tanks[0];
+  const availableReels = tank ? tank.volumes.map((v) => v.reel) : [];
 
   // Reset reel if not available for selected tank
   const activeReel = availableReels.includes(reelType) ? reelType : availableReels[0];
-  const totalMl = tank.volumes[activeReel] ?? 0;
+  const totalMl = tank?.volumes.find((v) => v.reel === activeReel)?.ml ?? 0;
 
-  const parsed = useMemo(() => parseDilution(dilution), [dilution]);
-  const result = useMemo(
-    () => (parsed ? calculateChemistry(totalMl, parsed) : null),
-    [totalMl, parsed]
-  );
+  useEffect(() => {
+    if (!parsed || !tank || !activeReel) {
+      setResult(null);
+      return;
+    }
+    let cancelled = false;
+    invoke<ChemistryResult>("calculate_chemistry", {
+      request: { developer, dilution, tank: tank.tank, reel: activeReel },
+    })
+      .then((r) => {
+        if (!cancelled) setResult(r);
+      })
+      .catch(() => {
+        if (!cancelled) setResult(null);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [parsed, developer, dilution, tank, activeReel]);
+
+  const handlePrintCard = async () => {
+    if (!recipeId) return;
+    try {
+      await invoke("export_recipe_pdf", {
+        recipeIds: [recipeId],
+        chemistry: { tank: tank?.tank, reel: activeReel },
+      });
+      showToast("Recipe card exported");
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Export failed: ${msg}`, "error");
+      }
+    }
+  };
 
   return (
     <section className="rounded-3xl border border-(--color-border) bg-(--color-surface-secondary) p-5 shadow-sm">
@@ -44,7 +109,7 @@ export function ChemistryCalculator({ dilution }: { dilution: string }) {
                 onChange={(e) => setTankIndex(Number(e.target.value))}
                 className="w-full px-3 py-2 text-sm rounded-lg bg-(--color-surface) border border-(--color-border) text-(--color-text-primary) outline-none focus:border-(--color-accent)"
               >
-                {TANK_VOLUMES.map((t, i) => (
+                {tanks.map((t, i) => (
                   <option key={t.tank} value={i}>
                     {t.tank}
                   </option>
@@ -57,12 +122,12 @@ export function ChemistryCalculator({ dilution }: { dilution: string }) {
               </label>
               <select
                 value={activeReel}
-                onChange={(e) => setReelType(e.target.value as ReelType)}
+                onChange={(e) => setReelType(e.target.value)}
                 className="w-full px-3 py-2 text-sm rounded-lg bg-(--color-surface) border border-(--color-border) text-(--color-text-primary) outline-none focus:border-(--color-accent)"
               >
                 {availableReels.map((r) => (
                   <option key={r} value={r}>
-                    {REEL_LABELS[r]}
+                    {REEL_LABELS[r as ReelType] ?? r}
                   </option>
                 ))}
               </select>
@@ -89,18 +154,20 @@ export function ChemistryCalculator({ dilution }: { dilution: string }) {
             <div className="h-px bg-(--color-border) my-2" />
             {result && (
               <div className="grid grid-cols-2 gap-3 mt-3">
-                <div className="text-center">
-                  <p className="text-xs text-(--color-text-tertiary) uppercase tracking-wide mb-1">
-                    Developer
-                  </p>
-                  <p
-                    className="text-2xl font-medium text-(--color-accent)"
-                    style={{ fontFamily: "var(--font-display)" }}
-                  >
-                    {result.developerMl}
-                    <span className="text-sm ml-0.5">ml</span>
-                  </p>
-                </div>
+                {result.parts.map((part) => (
+                  <div key={part.label} className="text-center">
+                    <p className="text-xs text-(--color-text-tertiary) uppercase tracking-wide mb-1">
+                      {result.parts.length > 1 ? part.label : "Developer"}
+                    </p>
+                    <p
+                      className="text-2xl font-medium text-(--color-accent)"
+                      style={{ fontFamily: "var(--font-display)" }}
+                    >
+                      {part.ml}
+                      <span className="text-sm ml-0.5">ml</span>
+                    </p>
+                  </div>
+                ))}
                 <div className="text-center">
                   <p className="text-xs text-(--color-text-tertiary) uppercase tracking-wide mb-1">
                     Water
@@ -109,13 +176,26 @@ export function ChemistryCalculator({ dilution }: { dilution: string }) {
                     className="text-2xl font-medium text-(--color-text-primary)"
                     style={{ fontFamily: "var(--font-display)" }}
                   >
-                    {result.waterMl}
+                    {result.water_ml}
                     <span className="text-sm ml-0.5">ml</span>
                   </p>
                 </div>
               </div>
             )}
           </div>
+          {result?.warnings.map((warning, i) => (
+            <p key={i} className="text-xs text-(--color-warning)">
+              {warning}
+            </p>
+          ))}
+          {recipeId && (
+            <button
+              onClick={handlePrintCard}
+              className="px-3.5 py-2 text-sm rounded-full border border-(--color-border) text-(--color-text-secondary) hover:bg-(--color-surface-hover) transition-colors"
+            >
+              Print Recipe Card (PDF)
+            </button>
+          )}
         </div>
       )}
     </section>
@@ -0,0 +1,296 @@
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useAppStore } from "../lib/store";
+import type { Collection, DeployReport, DeviceDeployReport, SyncAction } from "../lib/types";
+
+const ACTION_LABELS: Record<SyncAction, string> = {
+  upload: "Upload",
+  replace: "Replace",
+  keep: "Keep",
+  remove: "Remove",
+};
+
+const STATUS_LABELS: Record<DeviceDeployReport["status"], string> = {
+  planned: "Dry run",
+  synced: "Synced",
+  partial: "Partly synced",
+  failed: "Failed",
+};
+
+export function FleetDeploy() {
+  const { recipes, devices, wifiInterface, setWifiInterface, showToast } = useAppStore();
+  const [collections, setCollections] = useState<Collection[]>([]);
+  const [selectedId, setSelectedId] = useState("");
+  const [draft, setDraft] = useState<Collection | null>(null);
+  const [newName, setNewName] = useState("");
+  const [deviceIds, setDeviceIds] = useState<string[]>([]);
+  const [prune, setPrune] = useState(false);
+  const [running, setRunning] = useState(false);
+  const [report, setReport] = useState<DeployReport | null>(null);
+
+  const loadCollections = useCallback(async () => {
+    try {
+      const list = await invoke<Collection[]>("list_collections");
+      setCollections(list);
+      return list;
+    } catch (e) {
+      showToast(`Failed to load collections: ${e}`, "error");
+      return [];
+    }
+  }, [showToast]);
+
+  useEffect(() => {
+    loadCollections().then((list) => {
+      if (list.length > 0) setSelectedId((id) => id || list[0].id);
+    });
+  }, [loadCollections]);
+
+  useEffect(() => {
+    if (wifiInterface) return;
+    invoke<string>("wifi_get_interface")
+      .then(setWifiInterface)
+      .catch(() => setWifiInterface(""));
+  }, [wifiInterface, setWifiInterface]);
+
+  useEffect(() => {
+    setDraft(collections.find((c) => c.id === selectedId) ?? null);
+  }, [collections, selectedId]);
+
+  const toggleRecipe = (recipeId: string) => {
+    setDraft((d) =>
+      d
+        ? {
+            ...d,
+            recipe_ids: d.recipe_ids.includes(recipeId)
+              ? d.recipe_ids.filter((id) => id !== recipeId)
+              : [...d.recipe_ids, recipeId],
+          }
+        : d
+    );
+  };
+
+  const toggleDevice = (id: string) => {
+    setDeviceIds((ids) => (ids.includes(id) ? ids.filter((d) => d !== id) : [...ids, id]));
+  };
+
+  const handleCreate = async () => {
+    try {
+      const created = await invoke<Collection>("create_collection", {
+        collection: { name: newName },
+      });
+      setNewName("");
+      await loadCollections();
+      setSelectedId(created.id);
+    } catch (e) {
+      showToast(`Failed to create collection: ${e}`, "error");
+    }
+  };
+
+  const handleSave = async () => {
+    if (!draft) return;
+    try {
+      await invoke<Collection>("update_collection", { collection: draft });
+      await loadCollections();
+      showToast("Collection saved");
+    } catch (e) {
+      showToast(`Failed to save collection: ${e}`, "error");
+    }
+  };
+
+  const handleDelete = async () => {
+    if (!draft || !confirm(`Delete the collection ${draft.name}? Its recipes are kept.`)) return;
+    try {
+      await invoke("delete_collection", { id: draft.id });
+      const list = await loadCollections();
+      setSelectedId(list[0]?.id ?? "");
+    } catch (e) {
+      showToast(`Failed to delete collection: ${e}`, "error");
+    }
+  };
+
+  const handleDeploy = async (dryRun: boolean) => {
+    if (!draft) return;
+    setRunning(true);
+    setReport(null);
+    try {
+      const result = await invoke<DeployReport>("deploy_collection", {
+        collectionId: draft.id,
+        deviceIds,
+        interface: wifiInterface || null,
+        dryRun,
+        prune,
+      });
+      setReport(result);
+      const failed = result.devices.filter((d) => d.status === "failed" || d.status === "partial");
+      if (dryRun) {
+        showToast("Dry run finished");
+      } else if (failed.length > 0) {
+        showToast(`Deploy finished with problems on ${failed.length} device(s)`, "error");
+      } else {
+        showToast(`Deployed ${result.collection_name} to ${result.devices.length} device(s)`);
+      }
+    } catch (e) {
+      showToast(`Deploy failed: ${e}`, "error");
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <div className="max-w-xl mx-auto p-6 space-y-6">
+      <h2 className="text-2xl font-semibold">Fleet Deploy</h2>
+
+      {/* Collection */}
+      <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 space-y-3">
+        <h3 className="text-lg font-medium">Collection</h3>
+        <div className="flex gap-2">
+          <select
+            value={selectedId}
+            onChange={(e) => setSelectedId(e.target.value)}
+            className="flex-1 px-3 py-1.5 text-sm rounded-md bg-(--color-surface) border border-(--color-border) text-(--color-text-primary) outline-none"
+          >
+            {collections.length === 0 && <option value="">No collections yet</option>}
+            {collections.map((c) => (
+              <option key={c.id} value={c.id}>
+                {c.name} ({c.recipe_ids.length})
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={handleDelete}
+            disabled={!draft}
+            className="px-3 py-1.5 border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+          >
+            Delete
+          </button>
+        </div>
+        <div className="flex gap-2">
+          <input
+            value={newName}
+            onChange={(e) => setNewName(e.target.value)}
+            placeholder="House programs"
+            className="flex-1 px-3 py-1.5 text-sm rounded-md bg-(--color-surface) border border-(--color-border) text-(--color-text-primary) outline-none focus:border-(--color-accent)"
+          />
+          <button
+            onClick={handleCreate}
+            disabled={!newName.trim()}
+            className="px-3 py-1.5 border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+          >
+            New Collection
+          </button>
+        </div>
+        {draft && (
+          <>
+            <div className="max-h-56 overflow-y-auto rounded-md border border-(--color-border) bg-(--color-surface) divide-y divide-(--color-border)">
+              {recipes.map((r) => (
+                <label key={r.id} className="flex items-center gap-2 px-3 py-1.5 text-sm">
+                  <input
+                    type="checkbox"
+                    checked={draft.recipe_ids.includes(r.id)}
+                    onChange={() => toggleRecipe(r.id)}
+                  />
+                  <span className="truncate">{r.name || r.film_stock || "Untitled"}</span>
+                </label>
+              ))}
+            </div>
+            <button
+              onClick={handleSave}
+              className="px-3 py-1.5 bg-(--color-accent) text-white rounded-md text-sm font-medium hover:bg-(--color-accent-hover) transition-colors"
+            >
+              Save Collection
+            </button>
+          </>
+        )}
+      </div>
+
+      {/* Devices */}
+      <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 space-y-3">
+        <h3 className="text-lg font-medium">Devices</h3>
+        <div className="space-y-1">
+          {devices.map((d) => (
+            <label key={d.id} className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                checked={deviceIds.includes(d.id)}
+                onChange={() => toggleDevice(d.id)}
+              />
+              <span>{d.name}</span>
+              <span className="text-xs font-mono text-(--color-text-tertiary)">
+                {d.ssid} · {d.ip}
+              </span>
+            </label>
+          ))}
+        </div>
+        <label className="flex items-center gap-2 text-sm">
+          <input type="checkbox" checked={prune} onChange={(e) => setPrune(e.target.checked)} />
+          Remove custom programs that are not in the collection
+        </label>
+        {!wifiInterface && (
+          <p className="text-xs text-(--color-warning)">
+            WiFi interface not detected; only devices reachable on the current network can be updated.
+          </p>
+        )}
+        <div className="flex gap-2">
+          <button
+            onClick={() => handleDeploy(true)}
+            disabled={running || !draft || deviceIds.length === 0}
+            className="px-4 py-2 border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+          >
+            Dry Run
+          </button>
+          <button
+            onClick={() => handleDeploy(false)}
+            disabled={running || !draft || deviceIds.length === 0}
+            className="px-4 py-2 bg-(--color-accent) text-white rounded-lg text-sm font-medium hover:bg-(--color-accent-hover) transition-colors disabled:opacity-50"
+          >
+            {running ? "Deploying..." : "Deploy"}
+          </button>
+        </div>
+      </div>
+
+      {/* Report */}
+      {report && (
+        <div className="bg-(--color-surface-secondary) border border-(--color-border) rounded-xl p-5 space-y-4">
+          <h3 className="text-lg font-medium">
+            {report.dry_run ? "Planned changes" : "Deploy report"}: {report.collection_name}
+          </h3>
+          {report.devices.map((d) => (
+            <div key={d.device_id} className="space-y-1">
+              <p className="text-sm font-medium">
+                {d.device_name}{" "}
+                <span className="text-xs text-(--color-text-tertiary)">{STATUS_LABELS[d.status]}</span>
+              </p>
+              {d.error && <p className="text-xs text-(--color-danger)">{d.error}</p>}
+              {d.plan.changes.map((c, i) => (
+                <p key={i} className="text-xs text-(--color-text-secondary)">
+                  <span className="inline-block w-16 font-medium">{ACTION_LABELS[c.action]}</span>
+                  {c.label}
+                  {c.filenames.length > 0 && (
+                    <span className="ml-1 font-mono text-(--color-text-tertiary)">
+                      {c.filenames.join(", ")}
+                    </span>
+                  )}
+                </p>
+              ))}
+              {d.log.map((line, i) => (
+                <p key={i} className="text-xs font-mono text-(--color-text-tertiary)">
+                  {line}
+                </p>
+              ))}
+            </div>
+          ))}
+          {report.restored_network && (
+            <p className="text-xs text-(--color-text-tertiary)">
+              Rejoined {report.restored_network}.
+            </p>
+          )}
+          {report.warnings.map((w, i) => (
+            <p key={i} className="text-xs text-(--color-warning)">
+              {w}
+            </p>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
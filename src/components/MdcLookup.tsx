@@ -1,9 +1,10 @@
-import { useState, useMemo, useRef, useEffect } from "react";
+import { useState, useRef, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { useAppStore } from "../lib/store";
-import type { MdcEntry } from "../lib/types";
-import mdcData from "../data/massive_dev_chart.json";
+import type { MdcEntry, MdcPage } from "../lib/types";
 
-const allEntries = mdcData as MdcEntry[];
+const SUGGESTION_LIMIT = 12;
+const PAGE_SIZE = 100;
 
 export function MdcLookup() {
   const applyMdcEntry = useAppStore((s) => s.applyMdcEntry);
@@ -19,45 +20,95 @@ export function MdcLookup() {
   const [selectedDev, setSelectedDev] = useState("");
   const [filmFocused, setFilmFocused] = useState(false);
   const [devFocused, setDevFocused] = useState(false);
+  const [filmSuggestions, setFilmSuggestions] = useState<string[]>([]);
+  const [devSuggestions, setDevSuggestions] = useState<string[]>([]);
+  const [results, setResults] = useState<MdcEntry[]>([]);
+  const [total, setTotal] = useState(0);
+  const [page, setPage] = useState(0);
+  const [loading, setLoading] = useState(false);
   const filmInputRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
     filmInputRef.current?.focus();
   }, []);
 
-  const allFilms = useMemo(() => {
-    const set = new Set(allEntries.map((e) => e.film));
-    return Array.from(set).sort();
-  }, []);
-
-  const filmSuggestions = useMemo(() => {
-    if (!filmQuery || selectedFilm) return [];
-    const q = filmQuery.toLowerCase();
-    return allFilms.filter((f) => f.toLowerCase().includes(q)).slice(0, 12);
-  }, [filmQuery, selectedFilm, allFilms]);
-
-  const availableDevs = useMemo(() => {
-    if (!selectedFilm) return [];
-    const devs = new Set(
-      allEntries.filter((e) => e.film === selectedFilm).map((e) => e.developer)
-    );
-    return Array.from(devs).sort();
-  }, [selectedFilm]);
+  useEffect(() => {
+    if (!filmQuery || selectedFilm) {
+      setFilmSuggestions([]);
+      return;
+    }
+    let cancelled = false;
+    invoke<string[]>("list_mdc_films", { query: filmQuery, limit: SUGGESTION_LIMIT })
+      .then((films) => {
+        if (!cancelled) setFilmSuggestions(films);
+      })
+      .catch(() => {
+        if (!cancelled) setFilmSuggestions([]);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [filmQuery, selectedFilm]);
 
-  const devSuggestions = useMemo(() => {
-    if (!devQuery || selectedDev || !selectedFilm) return [];
-    const q = devQuery.toLowerCase();
-    return availableDevs.filter((d) => d.toLowerCase().includes(q)).slice(0, 12);
-  }, [devQuery, selectedDev, selectedFilm, availableDevs]);
+  useEffect(() => {
+    if (!devQuery || selectedDev || !selectedFilm) {
+      setDevSuggestions([]);
+      return;
+    }
+    let cancelled = false;
+    invoke<string[]>("list_mdc_developers", {
+      query: devQuery,
+      film: selectedFilm,
+      limit: SUGGESTION_LIMIT,
+    })
+      .then((devs) => {
+        if (!cancelled) setDevSuggestions(devs);
+      })
+      .catch(() => {
+        if (!cancelled) setDevSuggestions([]);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [devQuery, selectedDev, selectedFilm]);
 
-  const results = useMemo(() => {
-    if (!selectedFilm) return [];
-    let filtered = allEntries.filter((e) => e.film === selectedFilm);
-    if (selectedDev) {
-      filtered = filtered.filter((e) => e.developer === selectedDev);
+  useEffect(() => {
+    if (!selectedFilm) {
+      setResults([]);
+      setTotal(0);
+      return;
     }
-    return filtered;
-  }, [selectedFilm, selectedDev]);
+    let cancelled = false;
+    setLoading(true);
+    invoke<MdcPage>("search_mdc", {
+      query: {
+        film: selectedFilm,
+        developer: selectedDev || null,
+        page,
+        page_size: PAGE_SIZE,
+      },
+    })
+      .then((result) => {
+        if (cancelled) return;
+        // Name matching is by substring, so keep only the names that were picked.
+        const entries = result.entries.filter(
+          (e) => e.film === selectedFilm && (!selectedDev || e.developer === selectedDev)
+        );
+        setResults((prev) => (page === 0 ? entries : [...prev, ...entries]));
+        setTotal(result.total);
+      })
+      .catch((e) => {
+        if (!cancelled) showToast(`Chart search failed: ${e}`, "error");
+      })
+      .finally(() => {
+        if (!cancelled) setLoading(false);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedFilm, selectedDev, page, showToast]);
+
+  const hasMore = (page + 1) * PAGE_SIZE < total;
 
   const handleSelectFilm = (film: string) => {
     setSelectedFilm(film);
@@ -65,12 +116,14 @@ export function MdcLookup() {
     setFilmFocused(false);
     setSelectedDev("");
     setDevQuery("");
+    setPage(0);
   };
 
   const handleSelectDev = (dev: string) => {
     setSelectedDev(dev);
     setDevQuery(dev);
     setDevFocused(false);
+    setPage(0);
   };
 
   const handleApply = async (entry: MdcEntry) => {
@@ -94,6 +147,7 @@ export function MdcLookup() {
   const handleClearDev = () => {
     setDevQuery("");
     setSelectedDev("");
+    setPage(0);
   };
 
   const formatTime = (val: string) => {
@@ -187,6 +241,7 @@ export function MdcLookup() {
                 onChange={(e) => {
                   setDevQuery(e.target.value);
                   setSelectedDev("");
+                  setPage(0);
                 }}
                 onFocus={() => setDevFocused(true)}
                 onBlur={() => setTimeout(() => setDevFocused(false), 150)}
@@ -230,54 +285,73 @@ export function MdcLookup() {
           </h3>
           <p className="text-xs text-(--color-text-tertiary)">
             {selectedFilm
-              ? `${results.length} result${results.length !== 1 ? "s" : ""}`
+              ? loading && results.length === 0
+                ? "Searching..."
+                : `${results.length} result${results.length !== 1 ? "s" : ""}`
               : "Start typing a film name"}
           </p>
         </div>
 
         <div className="flex-1 overflow-y-auto min-h-0">
           {selectedFilm && results.length > 0 ? (
-            <table className="w-full text-sm">
-              <thead className="sticky top-0 bg-(--color-surface-secondary)">
-                <tr className="text-xs text-(--color-text-tertiary) uppercase tracking-wider">
-                  <th className="text-left py-1.5 font-medium">Developer</th>
-                  <th className="text-left py-1.5 font-medium">Dilution</th>
-                  <th className="text-left py-1.5 font-medium">ISO</th>
-                  <th className="text-left py-1.5 font-medium">35mm</th>
-                  <th className="text-left py-1.5 font-medium">120</th>
-                  <th className="text-left py-1.5 font-medium">Temp</th>
-                </tr>
-              </thead>
-              <tbody>
-                {results.map((entry, i) => (
-                  <tr
-                    key={i}
-                    onClick={() => handleApply(entry)}
-                    className="cursor-pointer border-t border-(--color-border)/50 hover:bg-(--color-accent)/10 transition-colors"
-                    title={targetRecipe ? `Apply to "${targetRecipe.name}"` : "Select a recipe first"}
-                  >
-                    <td className="py-2 text-(--color-text-primary) font-medium">
-                      {entry.developer}
-                    </td>
-                    <td className="py-2 text-(--color-text-secondary)">
-                      {entry.dilution || "-"}
-                    </td>
-                    <td className="py-2 text-(--color-text-secondary)">
-                      {entry.iso}
-                    </td>
-                    <td className="py-2 text-(--color-text-primary) font-mono">
-                      {formatTime(entry.time_35mm)}
-                    </td>
-                    <td className="py-2 text-(--color-text-secondary) font-mono">
-                      {formatTime(entry.time_120)}
-                    </td>
-                    <td className="py-2 text-(--color-text-secondary)">
-                      {entry.temp_c || "-"}
-                    </td>
+            <>
+              <table className="w-full text-sm">
+                <thead className="sticky top-0 bg-(--color-surface-secondary)">
+                  <tr className="text-xs text-(--color-text-tertiary) uppercase tracking-wider">
+                    <th className="text-left py-1.5 font-medium">Developer</th>
+                    <th className="text-left py-1.5 font-medium">Dilution</th>
+                    <th className="text-left py-1.5 font-medium">ISO</th>
+                    <th className="text-left py-1.5 font-medium">35mm</th>
+                    <th className="text-left py-1.5 font-medium">120</th>
+                    <th className="text-left py-1.5 font-medium">Temp</th>
                   </tr>
-                ))}
-              </tbody>
-            </table>
+                </thead>
+                <tbody>
+                  {results.map((entry) => (
+                    <tr
+                      key={entry.id}
+                      onClick={() => handleApply(entry)}
+                      className="cursor-pointer border-t border-(--color-border)/50 hover:bg-(--color-accent)/10 transition-colors"
+                      title={targetRecipe ? `Apply to "${targetRecipe.name}"` : "Select a recipe first"}
+                    >
+                      <td className="py-2 text-(--color-text-primary) font-medium">
+                        {entry.developer}
+                      </td>
+                      <td className="py-2 text-(--color-text-secondary)">
+                        {entry.dilution || "-"}
+                      </td>
+                      <td className="py-2 text-(--color-text-secondary)">
+                        {entry.iso}
+                      </td>
+                      <td className="py-2 text-(--color-text-primary) font-mono">
+                        {formatTime(entry.time_35mm)}
+                      </td>
+                      <td className="py-2 text-(--color-text-secondary) font-mono">
+                        {formatTime(entry.time_120)}
+                      </td>
+                      <td className="py-2 text-(--color-text-secondary)">
+                        {entry.temp_c || "-"}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+              {hasMore && (
+                <div className="flex justify-center py-3">
+                  <button
+                    onClick={() => setPage((p) => p + 1)}
+                    disabled={loading}
+                    className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+                  >
+                    {loading ? "Loading..." : "Load more"}
+                  </button>
+                </div>
+              )}
+            </>
+          ) : selectedFilm && loading ? (
+            <p className="text-sm text-(--color-text-tertiary) text-center py-12">
+              Searching...
+            </p>
           ) : selectedFilm && results.length === 0 ? (
             <p className="text-sm text-(--color-text-tertiary) text-center py-12">
               No results found
@@ -1,10 +1,10 @@
 import { useState } from "react";
-import { useAppStore } from "../lib/store";
+import { selectActiveDevice, useAppStore } from "../lib/store";
 import { StepList } from "./StepList";
 import { recipeToAgoJson, generateAgoFilename } from "../lib/ago-format";
 import { invoke } from "@tauri-apps/api/core";
-import type { Recipe } from "../lib/types";
-import { DEVELOPERS } from "../lib/constants";
+import type { ExportFormat, Recipe, SessionReport, ShareCode } from "../lib/types";
+import { DEVELOPERS, EXPORT_FORMATS } from "../lib/constants";
 import { insertAgoUpload } from "../lib/db";
 import { ChemistryCalculator } from "./ChemistryCalculator";
 
@@ -13,21 +13,25 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
   const deleteRecipe = useAppStore((s) => s.deleteRecipe);
   const duplicateRecipe = useAppStore((s) => s.duplicateRecipe);
   const showToast = useAppStore((s) => s.showToast);
-  const settings = useAppStore((s) => s.settings);
+  const device = useAppStore(selectActiveDevice);
   const wifiStatus = useAppStore((s) => s.wifiStatus);
+  const wifiInterface = useAppStore((s) => s.wifiInterface);
+  const [uploading, setUploading] = useState(false);
   const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
+  const [exportFormat, setExportFormat] = useState<ExportFormat>("ago");
+  const [shareCode, setShareCode] = useState<ShareCode | null>(null);
   const recipeMetaSummary = [recipe.film_stock, recipe.developer, recipe.dilution]
     .filter(Boolean)
     .join(" • ");
 
   const handleExport = async () => {
     try {
-      const agoJson = recipeToAgoJson(recipe);
-      const jsonContent = JSON.stringify(agoJson, null, 2);
-      const filename = generateAgoFilename(recipe);
+      const filename =
+        exportFormat === "ago" ? generateAgoFilename(recipe) : recipe.name || "recipe";
       await invoke("export_recipe_file", {
-        jsonContent,
+        recipe,
         defaultName: filename,
+        format: exportFormat,
       });
       showToast("Recipe exported");
     } catch (e) {
@@ -38,20 +42,47 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
     }
   };
 
+  const handleShare = async () => {
+    if (shareCode) {
+      setShareCode(null);
+      return;
+    }
+    try {
+      setShareCode(await invoke<ShareCode>("recipe_share_code", { recipe }));
+    } catch (e) {
+      showToast(`Share failed: ${e}`, "error");
+    }
+  };
+
+  const handleSaveQr = async () => {
+    try {
+      await invoke("export_recipe_qr", { recipe, format: "png" });
+      showToast("QR code saved");
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Export failed: ${msg}`, "error");
+      }
+    }
+  };
+
   const handleUpload = async () => {
-    const ip = settings.ago_ip || "10.10.10.1";
-    const endpoint = settings.ago_upload_endpoint || "/api/files/programs/custom";
-    const fieldName = settings.ago_upload_field || "json";
+    if (!device) {
+      showToast("Add an AGO device in Settings first", "error");
+      return;
+    }
+    if (wifiStatus !== "connected") {
+      await handleSessionUpload(device.id);
+      return;
+    }
     const filename = generateAgoFilename(recipe);
 
     try {
-      const agoJson = recipeToAgoJson(recipe);
+      const jsonContent = await recipeToAgoJson(recipe);
       const result = await invoke<{ message: string; agoFilename?: string; ago_filename?: string }>("upload_recipe_file", {
-        ip,
-        endpoint,
-        fieldName,
+        deviceId: device.id,
         filename,
-        jsonContent: JSON.stringify(agoJson, null, 2),
+        jsonContent,
         filmStock: recipe.film_stock,
         developer: recipe.developer,
         dilution: recipe.dilution,
@@ -60,6 +91,7 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
       await insertAgoUpload({
         id: crypto.randomUUID(),
         recipe_id: recipe.id,
+        device_id: device.id,
         filename: uploadedFilename,
         display_name: recipe.name || recipe.film_stock || "Custom Program",
         uploaded_at: new Date().toISOString(),
@@ -70,6 +102,32 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
     }
   };
 
+  // Joins the AGO network just for this upload; the backend switches back
+  // to the current network afterwards, even when the upload fails.
+  const handleSessionUpload = async (deviceId: string) => {
+    setUploading(true);
+    try {
+      const report = await invoke<SessionReport>("run_device_session", {
+        deviceId,
+        interface: wifiInterface || null,
+        operations: [{ kind: "upload", recipe_id: recipe.id }],
+      });
+      const failure = report.join_error ?? report.operations.find((o) => !o.ok)?.message;
+      if (failure) {
+        showToast(`Upload failed: ${failure}`, "error");
+      } else if (report.restore.error) {
+        showToast(`Recipe uploaded, but ${report.restore.error}`, "error");
+      } else {
+        const back = report.restore.restored ? ` and rejoined ${report.restore.original_network}` : "";
+        showToast(`Recipe uploaded to ${report.device_name}${back}`);
+      }
+    } catch (e) {
+      showToast(`Upload failed: ${e}`, "error");
+    } finally {
+      setUploading(false);
+    }
+  };
+
   return (
     <div className="max-w-5xl mx-auto p-6 space-y-4">
       <section className="rounded-3xl border border-(--color-border) bg-(--color-surface-secondary) p-6 shadow-sm">
@@ -97,18 +155,20 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
         <div className="flex flex-wrap gap-2 items-center">
           <button
             onClick={handleUpload}
-            disabled={wifiStatus !== "connected"}
+            disabled={uploading || (wifiStatus !== "connected" && !wifiInterface)}
             className="flex items-center gap-1.5 px-5 py-2.5 text-sm rounded-full bg-(--color-accent) text-white hover:bg-(--color-accent-hover) transition-colors disabled:opacity-50 disabled:cursor-not-allowed font-medium"
             title={
               wifiStatus === "connected"
                 ? "Upload recipe directly to AGO"
-                : "Connect to AGO WiFi first"
+                : wifiInterface
+                  ? "Join the AGO WiFi, upload, then switch back"
+                  : "Connect to AGO WiFi first"
             }
           >
             <svg className="w-4 h-4" fill="none" viewBox="0 0 24 24" strokeWidth={1.5} stroke="currentColor">
               <path strokeLinecap="round" strokeLinejoin="round" d="M4.5 12.75 10.5 18l9-13.5" />
             </svg>
-            Upload to AGO
+            {uploading ? "Uploading..." : "Upload to AGO"}
           </button>
           <button
             onClick={handleExport}
@@ -119,6 +179,24 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
             </svg>
             Export
           </button>
+          <select
+            value={exportFormat}
+            onChange={(e) => setExportFormat(e.target.value as ExportFormat)}
+            className="px-3 py-2 text-sm rounded-full border border-(--color-border) bg-(--color-surface) text-(--color-text-secondary) outline-none"
+            title="Export format"
+          >
+            {EXPORT_FORMATS.map((f) => (
+              <option key={f.value} value={f.value}>
+                {f.label}
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={handleShare}
+            className="flex items-center gap-1.5 px-3.5 py-2 text-sm rounded-full border border-(--color-border) text-(--color-text-secondary) hover:bg-(--color-surface-hover) transition-colors"
+          >
+            Share
+          </button>
           <button
             onClick={() => duplicateRecipe(recipe.id)}
             className="flex items-center gap-1.5 px-3.5 py-2 text-sm rounded-full border border-(--color-border) text-(--color-text-secondary) hover:bg-(--color-surface-hover) transition-colors"
@@ -139,6 +217,36 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
             Delete
           </button>
         </div>
+        {shareCode && (
+          <div className="mt-3 flex flex-wrap gap-4 items-start">
+            <div
+              className="w-48 h-48 rounded-xl bg-white p-2"
+              dangerouslySetInnerHTML={{ __html: shareCode.svg }}
+            />
+            <div className="flex-1 min-w-[12rem] space-y-2">
+              <textarea
+                readOnly
+                value={shareCode.code}
+                rows={4}
+                className="w-full px-3 py-2 text-xs font-mono rounded-lg border border-(--color-border) bg-(--color-surface) text-(--color-text-secondary)"
+              />
+              <div className="flex gap-2">
+                <button
+                  onClick={() => navigator.clipboard.writeText(shareCode.code)}
+                  className="px-3.5 py-2 text-sm rounded-full border border-(--color-border) text-(--color-text-secondary) hover:bg-(--color-surface-hover) transition-colors"
+                >
+                  Copy Code
+                </button>
+                <button
+                  onClick={handleSaveQr}
+                  className="px-3.5 py-2 text-sm rounded-full border border-(--color-border) text-(--color-text-secondary) hover:bg-(--color-surface-hover) transition-colors"
+                >
+                  Save QR Image
+                </button>
+              </div>
+            </div>
+          </div>
+        )}
       </section>
 
       <section className="rounded-3xl border border-(--color-border) bg-(--color-surface-secondary) p-5 space-y-4 shadow-sm">
@@ -211,7 +319,11 @@ export function RecipeEditor({ recipe }: { recipe: Recipe }) {
         </div>
       </section>
 
-      <ChemistryCalculator dilution={recipe.dilution} />
+      <ChemistryCalculator
+        developer={recipe.developer}
+        dilution={recipe.dilution}
+        recipeId={recipe.id}
+      />
 
       <section className="rounded-3xl border border-(--color-border) bg-(--color-surface-secondary) p-5 shadow-sm">
         <h3 className="text-sm font-semibold text-(--color-text-secondary) mb-3 uppercase tracking-[0.08em]">
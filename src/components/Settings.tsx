@@ -1,18 +1,153 @@
-import { useState } from "react";
-import { useAppStore } from "../lib/store";
+import { useEffect, useState } from "react";
+import { selectActiveDevice, useAppStore } from "../lib/store";
 import { invoke } from "@tauri-apps/api/core";
 import { agoJsonToRecipeData } from "../lib/ago-format";
 import * as db from "../lib/db";
+import type {
+  BundleImportReport,
+  Device,
+  DiscoveredDevice,
+  ExternalImportReport,
+  FolderImportReport,
+  NewDevice,
+  Recipe,
+  TrustedKey,
+} from "../lib/types";
 
 export function Settings() {
-  const { settings, updateSetting, showToast, loadRecipes } = useAppStore();
+  const {
+    settings,
+    updateSetting,
+    showToast,
+    loadRecipes,
+    loadSettings,
+    recipes,
+    devices,
+    loadDevices,
+    setActiveDevice,
+  } = useAppStore();
+  const activeDevice = useAppStore(selectActiveDevice);
+  const [deviceDraft, setDeviceDraft] = useState<Device | null>(null);
+  const [discovered, setDiscovered] = useState<DiscoveredDevice[] | null>(null);
+  const [discovering, setDiscovering] = useState(false);
   const [uploadDebugLog, setUploadDebugLog] = useState("");
+  const [folderReport, setFolderReport] = useState<FolderImportReport | null>(null);
+  const [shareCode, setShareCode] = useState("");
+  const [wifiPassword, setWifiPassword] = useState("");
+  const [hasWifiPassword, setHasWifiPassword] = useState(false);
+  const [publicKey, setPublicKey] = useState("");
+  const [newKeyName, setNewKeyName] = useState("");
+  const [newKey, setNewKey] = useState("");
+  const trustedKeys = parseTrustedKeys(settings.trusted_bundle_keys);
+
+  const deviceId = activeDevice?.id ?? "";
+
+  useEffect(() => {
+    setDeviceDraft(activeDevice);
+  }, [activeDevice]);
+
+  useEffect(() => {
+    if (!deviceId) {
+      setHasWifiPassword(false);
+      return;
+    }
+    invoke<boolean>("wifi_has_password", { deviceId })
+      .then(setHasWifiPassword)
+      .catch(() => setHasWifiPassword(false));
+  }, [deviceId, activeDevice?.credential_ref, activeDevice?.ssid]);
+
+  const updateDraft = (field: keyof Device, value: string) => {
+    setDeviceDraft((d) => (d ? { ...d, [field]: value } : d));
+  };
+
+  const handleSaveDevice = async () => {
+    if (!deviceDraft) return;
+    try {
+      await invoke<Device>("update_device", { device: deviceDraft });
+      await loadDevices();
+      showToast("Device saved");
+    } catch (e) {
+      showToast(`Failed to save device: ${e}`, "error");
+    }
+  };
+
+  const handleAddDevice = async () => {
+    const device: NewDevice = {
+      name: `AGO ${devices.length + 1}`,
+      ip: "10.10.10.1",
+      ssid: "AGO",
+    };
+    try {
+      const created = await invoke<Device>("create_device", { device });
+      await loadDevices();
+      await setActiveDevice(created.id);
+    } catch (e) {
+      showToast(`Failed to add device: ${e}`, "error");
+    }
+  };
+
+  const handleDiscover = async () => {
+    setDiscovering(true);
+    try {
+      setDiscovered(await invoke<DiscoveredDevice[]>("discover_devices", {}));
+    } catch (e) {
+      showToast(`Device search failed: ${e}`, "error");
+    } finally {
+      setDiscovering(false);
+    }
+  };
+
+  const handleAddDiscovered = async (found: DiscoveredDevice) => {
+    if (found.device_id) {
+      await setActiveDevice(found.device_id);
+      return;
+    }
+    const device: NewDevice = {
+      name: found.hostname || `AGO at ${found.ip}`,
+      ip: found.ip,
+      ssid: activeDevice?.ssid || "AGO",
+      firmware_notes: found.firmware_hints.join(", "),
+    };
+    try {
+      const created = await invoke<Device>("create_device", { device });
+      await loadDevices();
+      await setActiveDevice(created.id);
+      setDiscovered((list) =>
+        list?.map((d) => (d.ip === found.ip ? { ...d, device_id: created.id } : d)) ?? null
+      );
+    } catch (e) {
+      showToast(`Failed to add device: ${e}`, "error");
+    }
+  };
+
+  const handleDeleteDevice = async () => {
+    if (!activeDevice || devices.length < 2) return;
+    if (!confirm(`Delete ${activeDevice.name}?`)) return;
+    try {
+      await invoke("delete_device", { id: activeDevice.id });
+      const next = devices.find((d) => d.id !== activeDevice.id);
+      await loadDevices();
+      if (next) await setActiveDevice(next.id);
+    } catch (e) {
+      showToast(`Failed to delete device: ${e}`, "error");
+    }
+  };
+
+  const handleSaveWifiPassword = async () => {
+    try {
+      await invoke("wifi_set_password", { deviceId, password: wifiPassword });
+      setHasWifiPassword(wifiPassword !== "");
+      setWifiPassword("");
+      showToast(wifiPassword ? "WiFi password saved to the system keychain" : "WiFi password removed");
+    } catch (e) {
+      showToast(`Failed to save WiFi password: ${e}`, "error");
+    }
+  };
 
   const handleImport = async () => {
     try {
       const content = await invoke<string>("import_recipe_file", {});
-      const parsed = JSON.parse(content);
-      const recipeData = agoJsonToRecipeData(parsed);
+      const recipeData = await agoJsonToRecipeData(content);
 
       const id = crypto.randomUUID();
       const now = new Date().toISOString();
@@ -48,6 +183,114 @@ export function Settings() {
     }
   };
 
+  const handleImportFolder = async () => {
+    try {
+      const report = await invoke<FolderImportReport>("import_recipe_folder", {});
+      setFolderReport(report);
+      await loadRecipes();
+      showToast(
+        `Imported ${report.imported}, ${report.duplicates} duplicate, ${report.invalid} invalid`,
+        report.invalid > 0 ? "info" : "success"
+      );
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Folder import failed: ${msg}`, "error");
+      }
+    }
+  };
+
+  const handleImportExternal = async () => {
+    try {
+      const report = await invoke<ExternalImportReport>("import_external_recipes", {});
+      await loadRecipes();
+      const unmapped = report.unmapped_fields.length
+        ? `; not mapped: ${report.unmapped_fields.join(", ")}`
+        : "";
+      showToast(
+        `Imported ${report.imported.length}, skipped ${report.skipped.length}${unmapped}`,
+        report.skipped.length || unmapped ? "info" : "success"
+      );
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Import failed: ${msg}`, "error");
+      }
+    }
+  };
+
+  const handleImportShareCode = async () => {
+    try {
+      const recipe = await invoke<Recipe>("import_share_code", { code: shareCode });
+      setShareCode("");
+      await loadRecipes();
+      showToast(`Imported ${recipe.name}`);
+    } catch (e) {
+      showToast(`Import failed: ${e}`, "error");
+    }
+  };
+
+  const handleExportBundle = async () => {
+    try {
+      await invoke("export_recipe_bundle", {
+        recipeIds: recipes.map((r) => r.id),
+        publisher: settings.bundle_publisher || "",
+      });
+      showToast("Signed bundle exported");
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Export failed: ${msg}`, "error");
+      }
+    }
+  };
+
+  const handleImportBundle = async () => {
+    try {
+      const report = await invoke<BundleImportReport>("import_recipe_bundle", {});
+      await loadRecipes();
+      const { verification } = report;
+      const from = verification.signer ? ` from ${verification.signer}` : "";
+      showToast(
+        [`Imported ${report.imported.length} recipes${from}`, ...verification.warnings].join("; "),
+        verification.warnings.length ? "info" : "success"
+      );
+    } catch (e) {
+      const msg = String(e);
+      if (!msg.includes("cancelled")) {
+        showToast(`Import failed: ${msg}`, "error");
+      }
+    }
+  };
+
+  const handleShowPublicKey = async () => {
+    try {
+      setPublicKey(await invoke<string>("bundle_public_key"));
+    } catch (e) {
+      showToast(`Failed to load signing key: ${e}`, "error");
+    }
+  };
+
+  const handleTrustKey = async () => {
+    try {
+      await invoke<TrustedKey[]>("trust_bundle_key", { name: newKeyName, publicKey: newKey });
+      setNewKeyName("");
+      setNewKey("");
+      await loadSettings();
+    } catch (e) {
+      showToast(`Failed to trust key: ${e}`, "error");
+    }
+  };
+
+  const handleUntrustKey = async (key: string) => {
+    try {
+      await invoke<TrustedKey[]>("untrust_bundle_key", { publicKey: key });
+      await loadSettings();
+    } catch (e) {
+      showToast(`Failed to remove key: ${e}`, "error");
+    }
+  };
+
   const handleLoadUploadDebug = async () => {
     try {
       const content = await invoke<string>("get_upload_debug_log");
@@ -77,25 +320,133 @@ export function Settings() {
           AGO Connection
         </h3>
         <div className="space-y-3">
-          <SettingField
-            label="AGO IP Address"
-            value={settings.ago_ip || ""}
-            onChange={(v) => updateSetting("ago_ip", v)}
-            placeholder="10.10.10.1"
-          />
-          <SettingField
-            label="WiFi SSID"
-            value={settings.ago_ssid || ""}
-            onChange={(v) => updateSetting("ago_ssid", v)}
-            placeholder="AGO"
-          />
-          <SettingField
-            label="WiFi Password"
-            value={settings.ago_password || ""}
-            onChange={(v) => updateSetting("ago_password", v)}
-            placeholder="12345678"
-            type="password"
-          />
+          <div className="flex items-end gap-2">
+            <div className="flex-1">
+              <label className="block text-xs font-medium text-(--color-text-secondary) mb-1">
+                Device
+              </label>
+              <select
+                value={deviceId}
+                onChange={(e) => setActiveDevice(e.target.value)}
+                className="w-full px-3 py-1.5 text-sm rounded-md bg-(--color-surface-secondary) border border-(--color-border) text-(--color-text-primary) outline-none"
+              >
+                {devices.map((d) => (
+                  <option key={d.id} value={d.id}>
+                    {d.name}
+                  </option>
+                ))}
+              </select>
+            </div>
+            <button
+              onClick={handleAddDevice}
+              className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors"
+            >
+              Add
+            </button>
+            <button
+              onClick={handleDeleteDevice}
+              disabled={devices.length < 2}
+              className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+            >
+              Delete
+            </button>
+            <button
+              onClick={handleDiscover}
+              disabled={discovering}
+              className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+            >
+              {discovering ? "Searching..." : "Find"}
+            </button>
+          </div>
+          {discovered && (
+            <div className="rounded-md border border-(--color-border) bg-(--color-surface) divide-y divide-(--color-border)">
+              {discovered.length === 0 && (
+                <p className="px-3 py-2 text-xs text-(--color-text-tertiary)">
+                  No AGO found on this network.
+                </p>
+              )}
+              {discovered.map((d) => (
+                <div key={d.ip} className="flex items-center justify-between px-3 py-2 gap-2">
+                  <div className="min-w-0">
+                    <p className="text-sm font-mono">
+                      {d.ip}
+                      {d.hostname && (
+                        <span className="ml-2 font-sans text-(--color-text-secondary)">{d.hostname}</span>
+                      )}
+                    </p>
+                    <p className="text-xs text-(--color-text-tertiary) truncate">
+                      {d.latency_ms} ms · {d.program_count} custom programs
+                      {d.firmware_hints.length > 0 && ` · ${d.firmware_hints.join(", ")}`}
+                    </p>
+                  </div>
+                  <button
+                    onClick={() => handleAddDiscovered(d)}
+                    className="shrink-0 px-2.5 py-1 text-xs rounded-md border border-(--color-border) hover:bg-(--color-surface-hover)"
+                  >
+                    {d.device_id ? "Select" : "Add"}
+                  </button>
+                </div>
+              ))}
+            </div>
+          )}
+          {deviceDraft && (
+            <>
+              <SettingField
+                label="Name"
+                value={deviceDraft.name}
+                onChange={(v) => updateDraft("name", v)}
+                placeholder="Darkroom AGO"
+              />
+              <SettingField
+                label="AGO IP Address"
+                value={deviceDraft.ip}
+                onChange={(v) => updateDraft("ip", v)}
+                placeholder="10.10.10.1"
+              />
+              <SettingField
+                label="WiFi SSID"
+                value={deviceDraft.ssid}
+                onChange={(v) => updateDraft("ssid", v)}
+                placeholder="AGO"
+              />
+              <SettingField
+                label="Firmware Notes"
+                value={deviceDraft.firmware_notes}
+                onChange={(v) => updateDraft("firmware_notes", v)}
+                placeholder="e.g. 1.4.2, updated March"
+              />
+              <button
+                onClick={handleSaveDevice}
+                className="px-3 py-1.5 bg-(--color-accent) text-white rounded-md text-sm font-medium hover:bg-(--color-accent-hover) transition-colors"
+              >
+                Save Device
+              </button>
+            </>
+          )}
+          <div>
+            <div className="flex items-end gap-2">
+              <div className="flex-1">
+                <SettingField
+                  label="WiFi Password"
+                  value={wifiPassword}
+                  onChange={setWifiPassword}
+                  placeholder={hasWifiPassword ? "Saved - type to replace" : "12345678"}
+                  type="password"
+                />
+              </div>
+              <button
+                onClick={handleSaveWifiPassword}
+                disabled={!wifiPassword && !hasWifiPassword}
+                className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+              >
+                {wifiPassword || !hasWifiPassword ? "Save" : "Remove"}
+              </button>
+            </div>
+            <p className="mt-1 text-xs text-(--color-text-tertiary)">
+              Stored in the system keychain for {activeDevice?.name || "this device"}, not in the
+              app database.
+            </p>
+          </div>
           <div className="flex items-center justify-between py-1">
             <label className="text-sm text-(--color-text-primary)">
               Auto-reconnect to previous WiFi
@@ -122,6 +473,21 @@ export function Settings() {
               />
             </button>
           </div>
+          {settings.auto_reconnect === "true" && (
+            <div>
+              <SettingField
+                label="Return to previous WiFi after idle (minutes)"
+                value={settings.reconnect_idle_minutes ?? ""}
+                onChange={(v) => updateSetting("reconnect_idle_minutes", v)}
+                placeholder="10"
+                type="number"
+              />
+              <p className="mt-1 text-xs text-(--color-text-tertiary)">
+                Leaves the AGO network when it has not been used for this long, and when the app
+                quits. Use 0 to only switch back on quit.
+              </p>
+            </div>
+          )}
         </div>
         <details className="mt-3">
           <summary className="text-xs text-(--color-text-tertiary) cursor-pointer hover:text-(--color-text-secondary)">
@@ -130,16 +496,17 @@ export function Settings() {
           <div className="mt-2 space-y-3">
             <SettingField
               label="Compatibility Upload Endpoint"
-              value={settings.ago_upload_endpoint || ""}
-              onChange={(v) => updateSetting("ago_upload_endpoint", v)}
+              value={deviceDraft?.upload_endpoint ?? ""}
+              onChange={(v) => updateDraft("upload_endpoint", v)}
               placeholder="/api/files/programs/custom"
             />
             <SettingField
               label="Compatibility Upload Field"
-              value={settings.ago_upload_field || ""}
-              onChange={(v) => updateSetting("ago_upload_field", v)}
+              value={deviceDraft?.upload_field ?? ""}
+              onChange={(v) => updateDraft("upload_field", v)}
               placeholder="json"
             />
+            <p className="text-xs text-(--color-text-tertiary)">Saved with the device.</p>
           </div>
         </details>
       </section>
@@ -174,6 +541,25 @@ export function Settings() {
         </div>
       </section>
 
+      {/* Chemistry */}
+      <section className="mb-8">
+        <h3 className="text-sm font-semibold text-(--color-text-secondary) mb-3 uppercase tracking-wider">
+          Chemistry
+        </h3>
+        <div>
+          <SettingField
+            label="Minimum AGO fill (ml)"
+            value={settings.ago_min_fill_ml ?? ""}
+            onChange={(v) => updateSetting("ago_min_fill_ml", v)}
+            placeholder="250"
+            type="number"
+          />
+          <p className="mt-1 text-xs text-(--color-text-tertiary)">
+            Chemistry checks warn when a mix is smaller than this.
+          </p>
+        </div>
+      </section>
+
       {/* Import */}
       <section className="mb-8">
         <h3 className="text-sm font-semibold text-(--color-text-secondary) mb-3 uppercase tracking-wider">
@@ -188,6 +574,145 @@ export function Settings() {
         >
           Import Recipe from JSON
         </button>
+        <button
+          onClick={handleImportFolder}
+          className="ml-2 px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors"
+        >
+          Import Folder
+        </button>
+        <button
+          onClick={handleImportExternal}
+          className="ml-2 px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors"
+        >
+          Import CSV / Other JSON
+        </button>
+        <div className="mt-3 flex gap-2">
+          <input
+            type="text"
+            value={shareCode}
+            onChange={(e) => setShareCode(e.target.value)}
+            placeholder="Paste a share code (AGO:...)"
+            className="flex-1 px-3 py-2 bg-(--color-surface) border border-(--color-border) rounded-lg text-sm font-mono outline-none focus:border-(--color-accent)"
+          />
+          <button
+            onClick={handleImportShareCode}
+            disabled={!shareCode.trim()}
+            className="px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+          >
+            Import Code
+          </button>
+        </div>
+        {folderReport && folderReport.files.length > 0 && (
+          <ul className="mt-3 space-y-1 text-xs text-(--color-text-secondary)">
+            {folderReport.files.map((file) => (
+              <li key={file.path}>
+                <span className="font-medium">{file.status}</span> {file.path}
+                {file.recipe_name && ` — ${file.recipe_name}`}
+                {file.message && ` — ${file.message}`}
+              </li>
+            ))}
+          </ul>
+        )}
+      </section>
+
+      {/* Bundles */}
+      <section className="mb-8">
+        <h3 className="text-sm font-semibold text-(--color-text-secondary) mb-3 uppercase tracking-wider">
+          Recipe Bundles
+        </h3>
+        <p className="text-sm text-(--color-text-secondary) mb-3">
+          Bundles are signed with this app's key so recipients can check they have not been altered.
+        </p>
+        <div className="space-y-3">
+          <SettingField
+            label="Publisher Name"
+            value={settings.bundle_publisher || ""}
+            onChange={(v) => updateSetting("bundle_publisher", v)}
+            placeholder="My Film Lab"
+          />
+          <div className="flex gap-2">
+            <button
+              onClick={handleExportBundle}
+              disabled={recipes.length === 0}
+              className="px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+            >
+              Export All as Bundle
+            </button>
+            <button
+              onClick={handleImportBundle}
+              className="px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors"
+            >
+              Import Bundle
+            </button>
+            <button
+              onClick={handleShowPublicKey}
+              className="px-4 py-2 bg-(--color-surface-secondary) border border-(--color-border) rounded-lg text-sm font-medium hover:bg-(--color-surface-hover) transition-colors"
+            >
+              Show My Public Key
+            </button>
+          </div>
+          {publicKey && (
+            <p className="text-xs font-mono break-all text-(--color-text-secondary)">{publicKey}</p>
+          )}
+          <div className="flex items-center justify-between py-1">
+            <label className="text-sm text-(--color-text-primary)">
+              Unsigned or untrusted bundles
+            </label>
+            <select
+              value={settings.untrusted_bundle_policy || "warn"}
+              onChange={(e) => updateSetting("untrusted_bundle_policy", e.target.value)}
+              className="px-2 py-1 text-sm rounded-md bg-(--color-surface-secondary) border border-(--color-border) text-(--color-text-primary) outline-none"
+            >
+              <option value="warn">Import with a warning</option>
+              <option value="refuse">Refuse</option>
+            </select>
+          </div>
+          <div>
+            <p className="text-xs font-medium text-(--color-text-secondary) mb-1">Trusted Keys</p>
+            {trustedKeys.length === 0 && (
+              <p className="text-xs text-(--color-text-tertiary)">No trusted keys yet.</p>
+            )}
+            <ul className="space-y-1">
+              {trustedKeys.map((key) => (
+                <li key={key.public_key} className="flex items-center gap-2 text-xs">
+                  <span className="font-medium text-(--color-text-primary)">{key.name}</span>
+                  <span className="flex-1 font-mono truncate text-(--color-text-tertiary)">
+                    {key.public_key}
+                  </span>
+                  <button
+                    onClick={() => handleUntrustKey(key.public_key)}
+                    className="text-(--color-danger) hover:underline"
+                  >
+                    Remove
+                  </button>
+                </li>
+              ))}
+            </ul>
+            <div className="mt-2 flex gap-2">
+              <input
+                type="text"
+                value={newKeyName}
+                onChange={(e) => setNewKeyName(e.target.value)}
+                placeholder="Name"
+                className="w-32 px-3 py-1.5 text-sm rounded-md bg-(--color-surface-secondary) border border-(--color-border) text-(--color-text-primary) outline-none focus:border-(--color-accent)"
+              />
+              <input
+                type="text"
+                value={newKey}
+                onChange={(e) => setNewKey(e.target.value)}
+                placeholder="Public key"
+                className="flex-1 px-3 py-1.5 text-sm font-mono rounded-md bg-(--color-surface-secondary) border border-(--color-border) text-(--color-text-primary) outline-none focus:border-(--color-accent)"
+              />
+              <button
+                onClick={handleTrustKey}
+                disabled={!newKeyName.trim() || !newKey.trim()}
+                className="px-3 py-1.5 bg-(--color-surface-secondary) border border-(--color-border) rounded-md text-sm font-medium hover:bg-(--color-surface-hover) transition-colors disabled:opacity-50"
+              >
+                Trust
+              </button>
+            </div>
+          </div>
+        </div>
       </section>
 
       {/* About */}
@@ -242,6 +767,15 @@ export function Settings() {
   );
 }
 
+function parseTrustedKeys(value: string | undefined): TrustedKey[] {
+  try {
+    const keys = JSON.parse(value || "[]");
+    return Array.isArray(keys) ? keys : [];
+  } catch {
+    return [];
+  }
+}
+
 function SettingField({
   label,
   value,
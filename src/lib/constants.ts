@@ -1,3 +1,5 @@
+import type { ExportFormat } from "./types";
+
 export const DEVELOPERS = [
   "510 Pyro",
   "FX-39",
@@ -7,23 +9,30 @@ export const DEVELOPERS = [
   "Rodinal",
 ];
 
-export const STEP_NAMES = ["DEV", "STOP", "FIX", "BLIX", "RINSE", "PRE", "WASH"];
+export const STEP_NAMES = ["DEV", "STOP", "FIX", "BLIX", "STAB", "RINSE", "PRE", "WASH"];
+
+export const EXPORT_FORMATS: { value: ExportFormat; label: string }[] = [
+  { value: "ago", label: "AGO JSON" },
+  { value: "markdown", label: "Markdown" },
+  { value: "csv", label: "CSV" },
+  { value: "text", label: "Plain text" },
+];
 
 export const AGITATION_OPTIONS = ["Roll", "Stick", "Stand", "Off"] as const;
 
 export const COMPENSATION_OPTIONS = ["On", "Mon", "Off"] as const;
 
 export const DEFAULT_SETTINGS = {
-  ago_ip: "10.10.10.1",
-  ago_ssid: "AGO",
-  ago_password: "12345678",
-  ago_upload_endpoint: "/api/files/programs/custom",
-  ago_upload_field: "json",
+  active_device_id: "default",
   default_min_temp: "18",
   default_rated_temp: "20",
   default_max_temp: "24",
   export_folder: "",
   auto_reconnect: "true",
+  reconnect_idle_minutes: "10",
+  bundle_publisher: "",
+  trusted_bundle_keys: "[]",
+  untrusted_bundle_policy: "warn",
 };
 
 export function defaultStep(recipeId: string, sortOrder: number, name: string): {
@@ -71,11 +80,3 @@ export const REEL_LABELS: Record<ReelType, string> = {
   "5x7": "5x7",
   paper: "Paper 5",
 };
-
-export const TANK_VOLUMES: { tank: string; volumes: Partial<Record<ReelType, number>> }[] = [
-  { tank: "35mm tank", volumes: { "35mm": 250 } },
-  { tank: "Universal tank", volumes: { "35mm": 350 } },
-  { tank: "Multi-reel 3", volumes: { "35mm": 450, "4x5": 350 } },
-  { tank: "Multi-reel 5", volumes: { "35mm": 650, "4x5": 500, "5x7": 350, paper: 250 } },
-  { tank: "Multi-reel 8", volumes: { "35mm": 950, "4x5": 750, "5x7": 500, paper: 400 } },
-];
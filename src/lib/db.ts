@@ -235,13 +235,20 @@ export async function upsertSetting(key: string, value: string): Promise<void> {
 export interface AgoUpload {
   id: string;
   recipe_id: string | null;
+  device_id: string | null;
   filename: string;
   display_name: string;
   uploaded_at: string;
 }
 
-export async function fetchAgoUploads(): Promise<AgoUpload[]> {
+export async function fetchAgoUploads(deviceId?: string): Promise<AgoUpload[]> {
   const d = await getDb();
+  if (deviceId) {
+    return d.select<AgoUpload[]>(
+      "SELECT * FROM ago_uploads WHERE device_id = $1 ORDER BY uploaded_at DESC",
+      [deviceId]
+    );
+  }
   return d.select<AgoUpload[]>(
     "SELECT * FROM ago_uploads ORDER BY uploaded_at DESC"
   );
@@ -250,9 +257,16 @@ export async function fetchAgoUploads(): Promise<AgoUpload[]> {
 export async function insertAgoUpload(upload: AgoUpload): Promise<void> {
   const d = await getDb();
   await d.execute(
-    `INSERT INTO ago_uploads (id, recipe_id, filename, display_name, uploaded_at)
-     VALUES ($1, $2, $3, $4, $5)`,
-    [upload.id, upload.recipe_id, upload.filename, upload.display_name, upload.uploaded_at]
+    `INSERT INTO ago_uploads (id, recipe_id, device_id, filename, display_name, uploaded_at)
+     VALUES ($1, $2, $3, $4, $5, $6)`,
+    [
+      upload.id,
+      upload.recipe_id,
+      upload.device_id,
+      upload.filename,
+      upload.display_name,
+      upload.uploaded_at,
+    ]
   );
 }
 
@@ -260,8 +274,3 @@ export async function deleteAgoUpload(id: string): Promise<void> {
   const d = await getDb();
   await d.execute("DELETE FROM ago_uploads WHERE id = $1", [id]);
 }
-
-export async function deleteAgoUploadsByFilename(filename: string): Promise<void> {
-  const d = await getDb();
-  await d.execute("DELETE FROM ago_uploads WHERE filename = $1", [filename]);
-}
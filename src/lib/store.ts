@@ -1,5 +1,6 @@
 import { create } from "zustand";
-import type { Recipe, ViewType, MdcEntry } from "./types";
+import { invoke } from "@tauri-apps/api/core";
+import type { ConnectionState, Device, Recipe, ViewType, MdcEntry } from "./types";
 import * as db from "./db";
 import { defaultStep, DEFAULT_TEMPLATE_STEPS, DEFAULT_SETTINGS } from "./constants";
 
@@ -49,8 +50,13 @@ interface AppState {
   wifiInterface: string;
   setWifiStatus: (status: "disconnected" | "connecting" | "connected") => void;
   setCurrentSsid: (ssid: string) => void;
-  setPreviousSsid: (ssid: string) => void;
   setWifiInterface: (iface: string) => void;
+  applyConnectionState: (state: ConnectionState) => void;
+
+  // Devices
+  devices: Device[];
+  loadDevices: () => Promise<void>;
+  setActiveDevice: (id: string) => Promise<void>;
 
   // Settings
   settings: Record<string, string>;
@@ -333,8 +339,24 @@ export const useAppStore = create<AppState>((set, get) => ({
   wifiInterface: "",
   setWifiStatus: (status) => set({ wifiStatus: status }),
   setCurrentSsid: (ssid) => set({ currentSsid: ssid }),
-  setPreviousSsid: (ssid) => set({ previousSsid: ssid }),
   setWifiInterface: (iface) => set({ wifiInterface: iface }),
+  applyConnectionState: (state) =>
+    set((s) => ({
+      wifiStatus: state.connected ? "connected" : s.wifiStatus === "connecting" ? "connecting" : "disconnected",
+      currentSsid: state.current_network,
+      previousSsid: state.previous_network ?? "",
+      wifiInterface: state.interface || s.wifiInterface,
+    })),
+
+  // Devices
+  devices: [],
+  loadDevices: async () => {
+    const devices = await invoke<Device[]>("list_devices");
+    set({ devices });
+  },
+  setActiveDevice: async (id) => {
+    await get().updateSetting("active_device_id", id);
+  },
 
   // Settings
   settings: {},
@@ -360,3 +382,12 @@ export const useAppStore = create<AppState>((set, get) => ({
   },
   clearToast: () => set({ toast: null }),
 }));
+
+/** The device chosen in Settings, falling back to the first one. */
+export function selectActiveDevice(state: Pick<AppState, "devices" | "settings">): Device | null {
+  return (
+    state.devices.find((d) => d.id === state.settings.active_device_id) ??
+    state.devices[0] ??
+    null
+  );
+}
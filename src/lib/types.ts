@@ -28,27 +28,8 @@ export interface Step {
   logo_text: string;
 }
 
-export interface AgoRecipeJson {
-  category: string;
-  name: string;
-  expanded_title: string;
-  steps: AgoStepJson[];
-}
-
-export interface AgoStepJson {
-  name: string;
-  time_min: number;
-  time_sec: number;
-  agitation: string;
-  compensation: string;
-  min_temperature: number;
-  rated_temperature: number;
-  max_temperature: number;
-  formula_designator: string;
-  logo_text: string;
-}
-
 export interface MdcEntry {
+  id: number;
   film: string;
   developer: string;
   dilution: string;
@@ -60,20 +41,216 @@ export interface MdcEntry {
   notes: string;
 }
 
-export type ViewType = "recipes" | "massdev" | "connection" | "uploads" | "settings";
+export interface Dilution {
+  parts: number[];
+  water: number;
+  base: "concentrate" | "stock";
+  notation: string;
+}
+
+export interface PartVolume {
+  label: string;
+  ml: number;
+}
+
+export interface ChemistryResult {
+  dilution: Dilution;
+  total_ml: number;
+  parts: PartVolume[];
+  water_ml: number;
+  concentrate_ml: number;
+  min_concentrate_ml: number | null;
+  warnings: string[];
+}
+
+export interface TankVolume {
+  tank: string;
+  volumes: { reel: string; ml: number }[];
+}
+
+export interface MdcPage {
+  entries: MdcEntry[];
+  total: number;
+  page: number;
+  page_size: number;
+}
+
+export interface FileImportResult {
+  path: string;
+  status: "imported" | "duplicate" | "invalid";
+  recipe_id: string | null;
+  recipe_name: string | null;
+  message: string | null;
+}
+
+export interface FolderImportReport {
+  folder: string;
+  imported: number;
+  duplicates: number;
+  invalid: number;
+  files: FileImportResult[];
+}
+
+export interface ExternalImportReport {
+  format: "csv" | "json";
+  imported: { source: string; recipe_id: string; name: string }[];
+  skipped: { source: string; reason: string }[];
+  unmapped_fields: string[];
+}
+
+export type ExportFormat = "ago" | "markdown" | "csv" | "text";
+
+export interface TrustedKey {
+  name: string;
+  public_key: string;
+}
+
+export interface BundleImportReport {
+  verification: {
+    status: "trusted" | "untrusted" | "unsigned";
+    signer: string | null;
+    public_key: string | null;
+    publisher: string;
+    warnings: string[];
+  };
+  imported: { source: string; recipe_id: string; name: string }[];
+}
+
+export interface ShareCode {
+  code: string;
+  svg: string;
+}
+
+export interface Device {
+  id: string;
+  name: string;
+  ip: string;
+  ssid: string;
+  credential_ref: string;
+  firmware_notes: string;
+  upload_endpoint: string;
+  upload_field: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export type NewDevice = Pick<Device, "name" | "ip" | "ssid"> &
+  Partial<Pick<Device, "credential_ref" | "firmware_notes" | "upload_endpoint" | "upload_field">>;
+
+export interface ConnectionState {
+  connected: boolean;
+  device_id: string;
+  interface: string;
+  current_network: string;
+  previous_network: string | null;
+}
+
+export type SessionOperation =
+  | { kind: "upload"; recipe_id: string }
+  | { kind: "list" }
+  | { kind: "delete"; filename: string };
+
+export interface OperationResult {
+  operation: SessionOperation;
+  ok: boolean;
+  message: string;
+  programs: { filename: string; name: string; expanded_title: string }[] | null;
+}
+
+export interface RestoreReport {
+  original_network: string | null;
+  switched: boolean;
+  restored: boolean;
+  error: string | null;
+}
+
+export interface SessionReport {
+  device_id: string;
+  device_name: string;
+  joined: boolean;
+  join_error: string | null;
+  operations: OperationResult[];
+  restore: RestoreReport;
+}
+
+export interface WifiNetwork {
+  ssid: string;
+  signal: number;
+  rssi_dbm: number | null;
+  security: string;
+  in_use: boolean;
+  ago_like: boolean;
+  device_id: string | null;
+}
+
+export interface DeviceProbe {
+  reachable: boolean;
+  latency_ms: number | null;
+  programs_api: boolean;
+  program_count: number | null;
+  advertised_methods: ("POST" | "PUT")[];
+  last_write_method: "POST" | "PUT" | null;
+  firmware_hints: string[];
+  probed_at: string;
+}
+
+export interface DiscoveredDevice {
+  ip: string;
+  latency_ms: number;
+  source: "scan" | "mdns";
+  hostname: string | null;
+  program_count: number;
+  firmware_hints: string[];
+  device_id: string | null;
+}
+
+export interface Collection {
+  id: string;
+  name: string;
+  description: string;
+  recipe_ids: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export type SyncAction = "upload" | "replace" | "keep" | "remove";
+
+export interface PlannedChange {
+  action: SyncAction;
+  label: string;
+  recipe_id: string | null;
+  filenames: string[];
+}
+
+export interface DeviceDeployReport {
+  device_id: string;
+  device_name: string;
+  status: "planned" | "synced" | "partial" | "failed";
+  plan: { changes: PlannedChange[] };
+  log: string[];
+  error: string | null;
+}
+
+export interface DeployReport {
+  collection_id: string;
+  collection_name: string;
+  dry_run: boolean;
+  devices: DeviceDeployReport[];
+  restored_network: string | null;
+  warnings: string[];
+}
+
+export type ViewType = "recipes" | "massdev" | "connection" | "uploads" | "deploy" | "settings";
 
 export type AgitationType = "Roll" | "Stick" | "Stand" | "Off";
 export type CompensationType = "On" | "Mon" | "Off";
 
 export interface AppSettings {
-  ago_ip: string;
-  ago_ssid: string;
-  ago_password: string;
-  ago_upload_endpoint: string;
-  ago_upload_field: string;
+  active_device_id: string;
   default_min_temp: string;
   default_rated_temp: string;
   default_max_temp: string;
   export_folder: string;
   auto_reconnect: string;
+  reconnect_idle_minutes: string;
 }